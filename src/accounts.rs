@@ -1,4 +1,7 @@
+use crate::error::LauncherError;
+use crate::i18n;
 use crate::state::AppState;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL, Engine};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tauri::Emitter;
@@ -12,14 +15,85 @@ pub struct Account {
     pub account_type: String,
     pub username: String,
     pub uuid: String,
-    #[serde(default, alias = "accessToken")]
+    // 令牌保存在 CredentialStore 中，不写入 accounts.json 也不发送给前端
+    #[serde(default, alias = "accessToken", skip_serializing)]
     pub access_token: Option<String>,
-    #[serde(default, alias = "refreshToken")]
+    #[serde(default, alias = "refreshToken", skip_serializing)]
     pub refresh_token: Option<String>,
     #[serde(default)]
     pub skin: Option<String>,
     #[serde(default, alias = "createdAt")]
     pub created_at: Option<i64>,
+    // Minecraft 令牌过期时间 (毫秒时间戳)
+    #[serde(default, alias = "tokenExpiresAt")]
+    pub token_expires_at: Option<i64>,
+    // Microsoft 令牌过期时间 (毫秒时间戳)
+    #[serde(default, alias = "msTokenExpiresAt")]
+    pub ms_token_expires_at: Option<i64>,
+    // 签发 Microsoft 令牌的端点，刷新时必须使用同一端点；旧账户没有记录时为 login.live.com
+    #[serde(default, alias = "msTokenUrl")]
+    pub ms_token_url: Option<String>,
+    // 外置登录 (Yggdrasil) 认证服务器 API 地址
+    #[serde(default, alias = "authServer")]
+    pub auth_server: Option<String>,
+    #[serde(default, alias = "authServerName")]
+    pub auth_server_name: Option<String>,
+    #[serde(default, alias = "clientToken")]
+    pub client_token: Option<String>,
+    // 皮肤模型: classic (Steve) / slim (Alex)
+    #[serde(default, alias = "skinModel")]
+    pub skin_model: Option<String>,
+    // 当前披风地址
+    #[serde(default)]
+    pub cape: Option<String>,
+}
+
+// Minecraft 令牌剩余有效期低于此值时在启动前刷新
+const TOKEN_REFRESH_MARGIN_MS: i64 = 5 * 60 * 1000;
+
+pub enum RefreshError {
+    // refresh_token 已失效，需要重新登录
+    InvalidGrant(String),
+    Other(String),
+}
+
+impl From<String> for RefreshError {
+    fn from(e: String) -> Self {
+        RefreshError::Other(e)
+    }
+}
+
+impl std::fmt::Display for RefreshError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RefreshError::InvalidGrant(e) | RefreshError::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl RefreshError {
+    /// 令牌被拒绝时要求重新登录，其他情况按认证失败处理
+    pub fn into_launcher_error(self, account_id: &str) -> LauncherError {
+        match self {
+            RefreshError::InvalidGrant(_) => LauncherError::ReloginRequired {
+                account_id: Some(account_id.to_string()),
+            },
+            RefreshError::Other(message) => LauncherError::Auth { message },
+        }
+    }
+}
+
+fn expires_at(expires_in: Option<i64>) -> Option<i64> {
+    expires_in.map(|secs| chrono::Utc::now().timestamp_millis() + secs * 1000)
+}
+
+/// 微软账户的 Minecraft 令牌是否即将过期（未记录过期时间的旧账户也视为需要刷新）
+pub fn needs_token_refresh(account: &Account) -> bool {
+    if account.account_type != "microsoft" || account.refresh_token.is_none() {
+        return false;
+    }
+    let now = chrono::Utc::now().timestamp_millis();
+    account.token_expires_at.is_none_or(|t| t - now < TOKEN_REFRESH_MARGIN_MS)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -32,7 +106,7 @@ pub struct AccountsResponse {
 #[tauri::command]
 pub async fn get_accounts(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
-) -> Result<AccountsResponse, String> {
+) -> Result<AccountsResponse, LauncherError> {
     let state = state.lock().await;
     Ok(AccountsResponse {
         accounts: state.accounts_data.accounts.clone(),
@@ -40,38 +114,104 @@ pub async fn get_accounts(
     })
 }
 
+/// 离线玩家 UUID，与服务端 offline 模式一致: UUID v3 (MD5("OfflinePlayer:<name>"))
+pub fn offline_uuid(username: &str) -> String {
+    use md5::{Digest, Md5};
+    let digest: [u8; 16] = Md5::digest(format!("OfflinePlayer:{}", username).as_bytes()).into();
+    uuid::Builder::from_md5_bytes(digest).into_uuid().simple().to_string()
+}
+
+/// 校验离线用户名，默认遵循原版规则 [A-Za-z0-9_]{3,16}
+/// allow_nonstandard 用于允许特殊用户名的服务器，仅要求不含空白且不超过 16 个字符
+fn validate_offline_username(username: &str, allow_nonstandard: bool) -> Result<(), String> {
+    let length = username.chars().count();
+    if allow_nonstandard {
+        if length == 0 || length > 16 {
+            return Err(i18n::t("account.errors.usernameLengthNonstandard", &serde_json::json!({})));
+        }
+        if username.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            return Err(i18n::t("account.errors.usernameWhitespace", &serde_json::json!({})));
+        }
+        return Ok(());
+    }
+    
+    if !(3..=16).contains(&length) {
+        return Err(i18n::t("account.errors.usernameLength", &serde_json::json!({})));
+    }
+    if !username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(i18n::t("account.errors.usernameChars", &serde_json::json!({})));
+    }
+    Ok(())
+}
+
+// 查找同名账户（不区分大小写，与服务端一致），跨所有账户类型
+fn find_duplicate_username<'a>(accounts: &'a [Account], username: &str, exclude_id: Option<&str>) -> Option<&'a Account> {
+    accounts.iter()
+        .filter(|a| Some(a.id.as_str()) != exclude_id)
+        .find(|a| a.username.eq_ignore_ascii_case(username))
+}
+
+fn duplicate_username_error(existing: &Account) -> LauncherError {
+    LauncherError::DuplicateUsername {
+        username: existing.username.clone(),
+        existing_account_id: existing.id.clone(),
+        existing_type: existing.account_type.clone(),
+    }
+}
+
+fn account_not_found(account_id: &str) -> LauncherError {
+    LauncherError::AccountNotFound { account_id: account_id.to_string() }
+}
+
 #[tauri::command]
 pub async fn add_offline_account(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     username: String,
-) -> Result<serde_json::Value, String> {
-    if username.trim().len() < 3 {
-        return Ok(serde_json::json!({
-            "success": false,
-            "error": "用户名至少需要3个字符"
-        }));
-    }
+    custom_uuid: Option<String>,
+    allow_nonstandard: Option<bool>,
+) -> Result<serde_json::Value, LauncherError> {
+    let username = username.trim().to_string();
+    validate_offline_username(&username, allow_nonstandard.unwrap_or(false))
+        .map_err(|reason| LauncherError::InvalidUsername { username: username.clone(), reason })?;
 
-    let uuid = Uuid::new_v4().to_string().replace("-", "");
+    // 默认使用与服务端一致的离线 UUID，也允许指定自定义 UUID
+    let uuid = match custom_uuid.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+        Some(custom) => Uuid::parse_str(custom)
+            .map_err(|_| LauncherError::InvalidUuid { value: custom.to_string() })?
+            .simple()
+            .to_string(),
+        None => offline_uuid(&username),
+    };
     let account = Account {
-        id: uuid.clone(),
+        id: Uuid::new_v4().simple().to_string(),
         account_type: "offline".to_string(),
-        username: username.trim().to_string(),
-        uuid: uuid.clone(),
+        username: username.clone(),
+        uuid,
         access_token: None,
         refresh_token: None,
         skin: None,
         created_at: Some(chrono::Utc::now().timestamp_millis()),
+        token_expires_at: None,
+        ms_token_expires_at: None,
+        ms_token_url: None,
+        auth_server: None,
+        auth_server_name: None,
+        client_token: None,
+        skin_model: None,
+        cape: None,
     };
 
     let mut state = state.lock().await;
+    if let Some(existing) = find_duplicate_username(&state.accounts_data.accounts, &username, None) {
+        return Err(duplicate_username_error(existing));
+    }
     state.accounts_data.accounts.push(account.clone());
     
     if state.accounts_data.current_account.is_none() {
         state.accounts_data.current_account = Some(account.id.clone());
     }
     
-    state.save_accounts().map_err(|e| e.to_string())?;
+    state.save_accounts().map_err(|e| LauncherError::Io { message: e.to_string() })?;
     
     Ok(serde_json::json!({
         "success": true,
@@ -85,20 +225,21 @@ pub async fn add_offline_account(
 pub async fn remove_account(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     account_id: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let mut state = state.lock().await;
     
     let index = state.accounts_data.accounts.iter()
-        .position(|a| a.id == account_id);
+        .position(|a| a.id == account_id)
+        .ok_or_else(|| account_not_found(&account_id))?;
     
-    if index.is_none() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "error": "账户不存在"
-        }));
-    }
+    let removed = state.accounts_data.accounts.remove(index);
     
-    state.accounts_data.accounts.remove(index.unwrap());
+    // 外置登录账户删除时吊销令牌
+    if removed.account_type == "yggdrasil" {
+        tokio::spawn(async move {
+            crate::yggdrasil::invalidate_token(&removed).await;
+        });
+    }
     
     if state.accounts_data.current_account.as_ref() == Some(&account_id) {
         state.accounts_data.current_account = state.accounts_data.accounts
@@ -106,7 +247,7 @@ pub async fn remove_account(
             .map(|a| a.id.clone());
     }
     
-    state.save_accounts().map_err(|e| e.to_string())?;
+    state.save_accounts().map_err(|e| LauncherError::Io { message: e.to_string() })?;
     
     Ok(serde_json::json!({
         "success": true,
@@ -119,19 +260,16 @@ pub async fn remove_account(
 pub async fn switch_account(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     account_id: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let mut state = state.lock().await;
     
     let exists = state.accounts_data.accounts.iter().any(|a| a.id == account_id);
     if !exists {
-        return Ok(serde_json::json!({
-            "success": false,
-            "error": "账户不存在"
-        }));
+        return Err(account_not_found(&account_id));
     }
     
     state.accounts_data.current_account = Some(account_id.clone());
-    state.save_accounts().map_err(|e| e.to_string())?;
+    state.save_accounts().map_err(|e| LauncherError::Io { message: e.to_string() })?;
     
     Ok(serde_json::json!({
         "success": true,
@@ -145,151 +283,276 @@ pub async fn update_account_username(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     account_id: String,
     new_username: String,
-) -> Result<serde_json::Value, String> {
+    allow_nonstandard: Option<bool>,
+) -> Result<serde_json::Value, LauncherError> {
+    let new_username = new_username.trim().to_string();
+    validate_offline_username(&new_username, allow_nonstandard.unwrap_or(false))
+        .map_err(|reason| LauncherError::InvalidUsername { username: new_username.clone(), reason })?;
+    
     let mut state = state.lock().await;
+    if let Some(existing) = find_duplicate_username(&state.accounts_data.accounts, &new_username, Some(&account_id)) {
+        return Err(duplicate_username_error(existing));
+    }
     
     let account = state.accounts_data.accounts.iter_mut()
         .find(|a| a.id == account_id);
     
     match account {
-        None => Ok(serde_json::json!({
-            "success": false,
-            "error": "账户不存在"
-        })),
+        None => Err(account_not_found(&account_id)),
         Some(acc) => {
             if acc.account_type != "offline" {
-                return Ok(serde_json::json!({
-                    "success": false,
-                    "error": "只能修改离线账户的用户名"
-                }));
+                return Err(LauncherError::UnsupportedAccountType {
+                    account_id: account_id.clone(),
+                    expected: "offline",
+                });
             }
-            acc.username = new_username.trim().to_string();
+            // UUID 由旧用户名生成时随用户名更新，自定义 UUID 保持不变
+            let old_uuid = acc.uuid.clone();
+            if old_uuid == offline_uuid(&acc.username) {
+                acc.uuid = offline_uuid(&new_username);
+            }
+            acc.username = new_username;
             let account_clone = acc.clone();
-            state.save_accounts().map_err(|e| e.to_string())?;
+            state.save_accounts().map_err(|e| LauncherError::Io { message: e.to_string() })?;
+            
+            // UUID 变化后存档中的玩家数据按新 UUID 查找，同步迁移，避免进度和背包丢失
+            let uuid_changed = old_uuid != account_clone.uuid;
+            let migration = if uuid_changed {
+                let mc_dir = state.launcher_settings.minecraft_dir.clone()
+                    .unwrap_or_else(|| crate::launcher::get_default_mc_dir().to_string_lossy().to_string());
+                drop(state);
+                // 迁移失败不影响改名，前端可用 migrate_offline_player_data 重试
+                Some(migrate_player_data(&mc_dir, &old_uuid, &account_clone.uuid)
+                    .unwrap_or_else(|e| serde_json::json!({ "error": e })))
+            } else {
+                None
+            };
             Ok(serde_json::json!({
                 "success": true,
-                "account": account_clone
+                "account": account_clone,
+                "uuidChanged": uuid_changed.then(|| serde_json::json!({
+                    "oldUuid": old_uuid,
+                    "newUuid": account_clone.uuid
+                })),
+                "playerDataMigration": migration
             }))
         }
     }
 }
 
+// 离线账户 UUID 变更后迁移各存档中的玩家数据，修改用户名时会自动执行，这里用于手动重试
+#[tauri::command]
+pub async fn migrate_offline_player_data(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    old_uuid: String,
+    new_uuid: String,
+) -> Result<serde_json::Value, LauncherError> {
+    let mc_dir = {
+        let app_state = state.lock().await;
+        app_state.launcher_settings.minecraft_dir.clone()
+            .unwrap_or_else(|| crate::launcher::get_default_mc_dir().to_string_lossy().to_string())
+    };
+    let mut result = migrate_player_data(&mc_dir, &old_uuid, &new_uuid)?;
+    result["success"] = serde_json::json!(true);
+    Ok(result)
+}
+
+// 在公共存档目录和版本隔离的存档目录中把 old_uuid 的玩家数据改名为 new_uuid
+fn migrate_player_data(mc_dir: &str, old_uuid: &str, new_uuid: &str) -> Result<serde_json::Value, LauncherError> {
+    let parse = |value: &str| Uuid::parse_str(value)
+        .map(|u| u.hyphenated().to_string())
+        .map_err(|_| LauncherError::InvalidUuid { value: value.to_string() });
+    let (old_uuid, new_uuid) = (parse(old_uuid)?, parse(new_uuid)?);
+    
+    let mc_path = std::path::Path::new(mc_dir);
+    
+    // 公共存档目录以及版本隔离的存档目录
+    let mut saves_dirs = vec![mc_path.join("saves")];
+    if let Ok(entries) = std::fs::read_dir(mc_path.join("versions")) {
+        saves_dirs.extend(entries.flatten().map(|e| e.path().join("saves")));
+    }
+    
+    let mut migrated = Vec::new();
+    let mut conflicts = Vec::new();
+    for world in saves_dirs.iter()
+        .filter_map(|d| std::fs::read_dir(d).ok())
+        .flat_map(|entries| entries.flatten().map(|e| e.path()))
+        .filter(|p| p.join("level.dat").exists())
+    {
+        let files = [
+            ("playerdata", "dat"),
+            ("playerdata", "dat_old"),
+            ("stats", "json"),
+            ("advancements", "json"),
+        ];
+        let mut moved = false;
+        for (dir, ext) in files {
+            let from = world.join(dir).join(format!("{}.{}", old_uuid, ext));
+            let to = world.join(dir).join(format!("{}.{}", new_uuid, ext));
+            if !from.exists() {
+                continue;
+            }
+            // 目标已存在时不覆盖，避免丢失新 UUID 下的进度
+            if to.exists() {
+                conflicts.push(to.to_string_lossy().to_string());
+                continue;
+            }
+            std::fs::rename(&from, &to).map_err(|e| LauncherError::Io {
+                message: format!("{:?}: {}", from, e),
+            })?;
+            moved = true;
+        }
+        if moved {
+            println!("[Accounts] Migrated player data in {:?}", world);
+            migrated.push(world.to_string_lossy().to_string());
+        }
+    }
+    
+    Ok(serde_json::json!({
+        "migratedWorlds": migrated,
+        "conflicts": conflicts
+    }))
+}
+
 // 微软登录 - 启动本地服务器接收回调
 #[tauri::command]
 pub async fn microsoft_login(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     app_handle: tauri::AppHandle,
-) -> Result<serde_json::Value, String> {
-    use tauri::Emitter;
-    
+) -> Result<serde_json::Value, LauncherError> {
     println!("[Microsoft Login] Starting login flow...");
     
+    let generation = begin_login();
     let client_id = std::env::var("AZURE_CLIENT_ID")
         .unwrap_or_else(|_| "d0c7a28a-ec25-4910-97ef-5cdabade8891".to_string());
-    let redirect_uri = std::env::var("REDIRECT_URI")
-        .unwrap_or_else(|_| "http://localhost:23456/auth/callback".to_string());
+    
+    // 绑定系统分配的端口，避免固定端口被占用
+    let server = tiny_http::Server::http("127.0.0.1:0").map_err(|e| {
+        println!("[Microsoft Login] Failed to start server: {}", e);
+        LauncherError::Auth { message: i18n::t("account.errors.localServer", &serde_json::json!({ "error": e.to_string() })) }
+    })?;
+    let port = server.server_addr().to_ip()
+        .map(|addr| addr.port())
+        .ok_or_else(|| LauncherError::Auth {
+            message: i18n::t("account.errors.localServer", &serde_json::json!({ "error": "no port" })),
+        })?;
+    let redirect_uri = format!("http://localhost:{}/auth/callback", port);
     
     println!("[Microsoft Login] Client ID: {}", client_id);
-    println!("[Microsoft Login] Redirect URI: {}", redirect_uri);
+    println!("[Microsoft Login] Local server started, redirect URI: {}", redirect_uri);
     
-    let oauth_state = uuid::Uuid::new_v4().to_string();
+    // PKCE 和 state 防止授权码被截获或伪造回调
+    let oauth_state = random_url_safe(16)?;
+    let code_verifier = random_url_safe(32)?;
+    let code_challenge = BASE64_URL.encode(
+        ring::digest::digest(&ring::digest::SHA256, code_verifier.as_bytes()).as_ref()
+    );
     let scope = "XboxLive.signin offline_access";
     
     let auth_url = format!(
-        "https://login.live.com/oauth20_authorize.srf?client_id={}&response_type=code&redirect_uri={}&scope={}&state={}&prompt=select_account",
+        "https://login.live.com/oauth20_authorize.srf?client_id={}&response_type=code&redirect_uri={}&scope={}&state={}&code_challenge={}&code_challenge_method=S256&prompt=select_account",
         client_id,
         urlencoding::encode(&redirect_uri),
         urlencoding::encode(scope),
-        oauth_state
+        oauth_state,
+        code_challenge
     );
     
-    println!("[Microsoft Login] Auth URL: {}", auth_url);
-    
-    // 启动本地 HTTP 服务器等待回调
-    let server = tiny_http::Server::http("127.0.0.1:23456").map_err(|e| {
-        println!("[Microsoft Login] Failed to start server: {}", e);
-        format!("无法启动本地服务器: {}", e)
-    })?;
-    
-    println!("[Microsoft Login] Local server started on port 23456");
-    
     // 打开浏览器
     if let Err(e) = open::that(&auth_url) {
         println!("[Microsoft Login] Failed to open browser: {}", e);
-        return Err(format!("无法打开浏览器: {}", e));
+        return Err(LauncherError::Auth {
+            message: i18n::t("account.errors.openBrowser", &serde_json::json!({ "error": e.to_string() })),
+        });
     }
     println!("[Microsoft Login] Browser opened successfully");
     
-    // 等待回调（设置超时）
-    let state_clone = state.inner().clone();
-    let app_handle_clone = app_handle.clone();
+    let success_page = auth_page(&app_handle, "success.html");
+    let error_page = auth_page(&app_handle, "error.html");
     
     // 在新线程中等待回调
     let result = tokio::task::spawn_blocking(move || {
         println!("[Microsoft Login] Waiting for callback...");
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(300);
         
-        // 使用 recv_timeout 设置超时 5 分钟
-        match server.recv_timeout(std::time::Duration::from_secs(300)) {
-            Ok(Some(request)) => {
-                let url = request.url().to_string();
-                println!("[Microsoft Login] Received request: {}", url);
-                
-                // 解析 code
-                if let Some(code) = url.split("code=").nth(1).and_then(|s| s.split('&').next()) {
-                    let code = urlencoding::decode(code).unwrap_or_default().to_string();
-                    println!("[Microsoft Login] Got authorization code");
-                    
-                    // 读取成功页面
-                    let html = std::fs::read_to_string("auth-pages/success.html")
-                        .unwrap_or_else(|_| "<html><body><h1>登录成功</h1><p>可以关闭此页面</p></body></html>".to_string());
-                    
-                    let response = tiny_http::Response::from_string(html)
-                        .with_header(
-                            tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/html; charset=utf-8"[..]).unwrap()
-                        );
-                    let _ = request.respond(response);
-                    
-                    return Ok(code);
-                } else if url.contains("error=") {
-                    let error = url.split("error_description=").nth(1)
-                        .and_then(|s| s.split('&').next())
-                        .map(|s| urlencoding::decode(s).unwrap_or_default().to_string())
-                        .unwrap_or_else(|| "登录被取消".to_string());
-                    
-                    // 读取错误页面
-                    let html = std::fs::read_to_string("auth-pages/error.html")
-                        .unwrap_or_else(|_| "<html><body><h1>登录失败</h1><p>请重试</p></body></html>".to_string());
-                    
-                    let response = tiny_http::Response::from_string(html)
-                        .with_header(
-                            tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/html; charset=utf-8"[..]).unwrap()
-                        );
-                    let _ = request.respond(response);
-                    
-                    return Err(error);
-                }
-                
-                Err("无效的回调".to_string())
+        let respond = |request: tiny_http::Request, status: u16, html: &str| {
+            let response = tiny_http::Response::from_string(html)
+                .with_status_code(status)
+                .with_header(
+                    tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/html; charset=utf-8"[..]).unwrap()
+                );
+            let _ = request.respond(response);
+        };
+        
+        loop {
+            if login_cancelled(generation) {
+                return Err(i18n::t("errors.loginCancelled", &serde_json::json!({})));
             }
-            Ok(None) => {
+            if std::time::Instant::now() >= deadline {
                 println!("[Microsoft Login] Timeout waiting for callback");
-                Err("等待登录超时".to_string())
+                return Err(i18n::t("account.errors.loginTimeout", &serde_json::json!({})));
             }
-            Err(e) => {
-                println!("[Microsoft Login] Server error: {}", e);
-                Err(format!("服务器错误: {}", e))
+            
+            // 短超时轮询，以便及时响应取消
+            let request = match server.recv_timeout(std::time::Duration::from_secs(1)) {
+                Ok(Some(request)) => request,
+                Ok(None) => continue,
+                Err(e) => {
+                    println!("[Microsoft Login] Server error: {}", e);
+                    return Err(i18n::t("account.errors.localServer", &serde_json::json!({ "error": e.to_string() })));
+                }
+            };
+            
+            let url = match url::Url::parse(&format!("http://localhost{}", request.url())) {
+                Ok(url) => url,
+                Err(_) => {
+                    respond(request, 400, "Bad Request");
+                    continue;
+                }
+            };
+            
+            // favicon 等其他请求直接忽略，继续等待真正的回调
+            if url.path() != "/auth/callback" {
+                respond(request, 404, "Not Found");
+                continue;
+            }
+            
+            let param = |name: &str| url.query_pairs()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.to_string());
+            
+            if param("state").as_deref() != Some(oauth_state.as_str()) {
+                println!("[Microsoft Login] Ignoring callback with mismatched state");
+                respond(request, 400, &error_page);
+                continue;
+            }
+            
+            if let Some(code) = param("code") {
+                println!("[Microsoft Login] Got authorization code");
+                respond(request, 200, &success_page);
+                return Ok(code);
             }
+            
+            let error = param("error_description")
+                .or_else(|| param("error"))
+                .unwrap_or_else(|| i18n::t("errors.loginCancelled", &serde_json::json!({})));
+            respond(request, 200, &error_page);
+            return Err(error);
         }
-    }).await.map_err(|e| e.to_string())?;
+    }).await.map_err(|e| LauncherError::Internal { message: e.to_string() })?;
     
-    let code = result?;
+    let code = match result {
+        Ok(code) => code,
+        Err(_) if login_cancelled(generation) => return Err(LauncherError::LoginCancelled),
+        Err(message) => return Err(LauncherError::Auth { message }),
+    };
     
     // 完成认证
     println!("[Microsoft Login] Completing authentication...");
-    let account = complete_microsoft_auth(&code).await?;
+    let account = complete_microsoft_auth(&state, &code, &redirect_uri, Some(&code_verifier)).await?;
     
     // 保存账户
-    let mut app_state = state_clone.lock().await;
+    let mut app_state = state.lock().await;
     let existing_idx = app_state.accounts_data.accounts.iter()
         .position(|a| a.id == account.id);
     
@@ -300,12 +563,12 @@ pub async fn microsoft_login(
     }
     
     app_state.accounts_data.current_account = Some(account.id.clone());
-    app_state.save_accounts().map_err(|e| e.to_string())?;
+    app_state.save_accounts().map_err(|e| LauncherError::Io { message: e.to_string() })?;
     
     println!("[Microsoft Login] Login successful: {}", account.username);
     
     // 通知前端
-    let _ = app_handle_clone.emit("auth-success", serde_json::json!({
+    let _ = app_handle.emit("auth-success", serde_json::json!({
         "account": &account
     }));
     
@@ -316,20 +579,42 @@ pub async fn microsoft_login(
     }))
 }
 
+fn random_url_safe(len: usize) -> Result<String, LauncherError> {
+    use ring::rand::SecureRandom;
+    
+    let mut bytes = vec![0u8; len];
+    ring::rand::SystemRandom::new().fill(&mut bytes)
+        .map_err(|_| LauncherError::Internal { message: "failed to generate random bytes".to_string() })?;
+    Ok(BASE64_URL.encode(bytes))
+}
+
+// 登录回调页面：优先使用资源目录中的文件，缺失时使用编译时内嵌的版本
+fn auth_page(app_handle: &tauri::AppHandle, name: &str) -> String {
+    use tauri::Manager;
+    
+    let from_resources = app_handle.path().resource_dir().ok()
+        .and_then(|dir| std::fs::read_to_string(dir.join("auth-pages").join(name)).ok());
+    
+    from_resources.unwrap_or_else(|| match name {
+        "success.html" => include_str!("../auth-pages/success.html").to_string(),
+        _ => include_str!("../auth-pages/error.html").to_string(),
+    })
+}
+
 
 // 处理手动输入的授权回调URL
 #[tauri::command]
 pub async fn manual_auth_callback(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     callback_url: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     println!("[Manual Auth] Received callback URL: {}", callback_url);
     
     // 解析URL获取code
     let url = url::Url::parse(&callback_url)
         .map_err(|e| {
             println!("[Manual Auth] URL parse error: {}", e);
-            "无效的回调URL".to_string()
+            LauncherError::Auth { message: i18n::t("account.errors.invalidCallbackUrl", &serde_json::json!({})) }
         })?;
     
     let code = url.query_pairs()
@@ -337,13 +622,13 @@ pub async fn manual_auth_callback(
         .map(|(_, v)| v.to_string())
         .ok_or_else(|| {
             println!("[Manual Auth] No code found in URL");
-            "未找到授权码".to_string()
+            LauncherError::Auth { message: i18n::t("account.errors.missingAuthCode", &serde_json::json!({})) }
         })?;
     
     println!("[Manual Auth] Found authorization code: {}...", &code[..code.len().min(20)]);
     
     // 完成认证流程
-    let account = complete_microsoft_auth(&code).await?;
+    let account = complete_microsoft_auth(&state, &code, &default_redirect_uri(), None).await?;
     println!("[Manual Auth] Auth completed, account: {} ({})", account.username, account.id);
     
     let mut app_state = state.lock().await;
@@ -363,7 +648,7 @@ pub async fn manual_auth_callback(
     app_state.accounts_data.current_account = Some(account.id.clone());
     app_state.save_accounts().map_err(|e| {
         println!("[Manual Auth] Save error: {}", e);
-        e.to_string()
+        LauncherError::Io { message: e.to_string() }
     })?;
     
     println!("[Manual Auth] Account saved successfully");
@@ -375,90 +660,396 @@ pub async fn manual_auth_callback(
     }))
 }
 
+// ═══════════════════════════════════════════════════════════
+// 设备代码登录 (无需本地服务器和同机浏览器)
+// ═══════════════════════════════════════════════════════════
+
+const LIVE_TOKEN_URL: &str = "https://login.live.com/oauth20_token.srf";
+const DEVICE_CODE_URL: &str = "https://login.microsoftonline.com/consumers/oauth2/v2.0/devicecode";
+const DEVICE_TOKEN_URL: &str = "https://login.microsoftonline.com/consumers/oauth2/v2.0/token";
+
+// 每次登录递增的编号，取消或开始新的登录后旧的轮询会自动退出
+static LOGIN_GENERATION: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+fn begin_login() -> u64 {
+    LOGIN_GENERATION.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1
+}
+
+fn login_cancelled(generation: u64) -> bool {
+    LOGIN_GENERATION.load(std::sync::atomic::Ordering::SeqCst) != generation
+}
+
+// 取消正在进行的微软登录
+#[tauri::command]
+pub async fn cancel_microsoft_login() -> Result<serde_json::Value, LauncherError> {
+    LOGIN_GENERATION.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    println!("[Microsoft Login] Login cancelled by user");
+    Ok(serde_json::json!({ "success": true }))
+}
+
+#[tauri::command]
+pub async fn microsoft_device_login(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, LauncherError> {
+    let generation = begin_login();
+    let client_id = std::env::var("AZURE_CLIENT_ID")
+        .unwrap_or_else(|_| "d0c7a28a-ec25-4910-97ef-5cdabade8891".to_string());
+    let client = reqwest::Client::new();
+    
+    // message 为 i18n::message 生成的 { messageKey, params, message }
+    let emit_progress = |stage: &str, mut message: serde_json::Value| {
+        message["stage"] = serde_json::json!(stage);
+        let _ = app_handle.emit("device-login-progress", message);
+    };
+    
+    println!("[Device Login] Requesting device code...");
+    let device: serde_json::Value = client
+        .post(DEVICE_CODE_URL)
+        .form(&[
+            ("client_id", client_id.as_str()),
+            ("scope", "XboxLive.signin offline_access"),
+        ])
+        .send()
+        .await?
+        .json()
+        .await?;
+    
+    if let Some(error) = device["error"].as_str() {
+        let description = device["error_description"].as_str().unwrap_or(error);
+        println!("[Device Login] Device code error: {}", description);
+        return Err(LauncherError::Auth { message: description.to_string() });
+    }
+    
+    let device_code = device["device_code"].as_str()
+        .ok_or_else(|| LauncherError::Auth {
+            message: i18n::t("account.errors.invalidDeviceCode", &serde_json::json!({})),
+        })?
+        .to_string();
+    let user_code = device["user_code"].as_str().unwrap_or_default();
+    let verification_uri = device["verification_uri"].as_str()
+        .unwrap_or("https://www.microsoft.com/link");
+    let expires_in = device["expires_in"].as_u64().unwrap_or(900);
+    let mut interval = device["interval"].as_u64().unwrap_or(5);
+    
+    println!("[Device Login] User code: {}, verification URI: {}", user_code, verification_uri);
+    let _ = app_handle.emit("device-code", serde_json::json!({
+        "userCode": user_code,
+        "verificationUri": verification_uri,
+        "expiresIn": expires_in,
+        "message": device["message"]
+    }));
+    
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(expires_in);
+    
+    let ms_token = loop {
+        tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+        
+        if login_cancelled(generation) {
+            return Err(LauncherError::LoginCancelled);
+        }
+        if std::time::Instant::now() >= deadline {
+            emit_progress("expired", i18n::message("account.deviceLogin.expired", serde_json::json!({})));
+            return Err(LauncherError::Auth { message: i18n::t("account.deviceLogin.expiredRelogin", &serde_json::json!({})) });
+        }
+        
+        let response: serde_json::Value = match client
+            .post(DEVICE_TOKEN_URL)
+            .form(&[
+                ("client_id", client_id.as_str()),
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ("device_code", device_code.as_str()),
+            ])
+            .send()
+            .await
+        {
+            Ok(r) => match r.json().await {
+                Ok(response) => response,
+                // 网关错误页等非 JSON 响应与网络错误一样处理
+                Err(e) => {
+                    println!("[Device Login] Poll response is not JSON: {}", e);
+                    continue;
+                }
+            },
+            Err(e) => {
+                // 网络波动时继续轮询
+                println!("[Device Login] Poll request failed: {}", e);
+                continue;
+            }
+        };
+        
+        match response["error"].as_str() {
+            None => break response,
+            Some("authorization_pending") => {
+                emit_progress("pending", i18n::message("account.deviceLogin.pending", serde_json::json!({})));
+            }
+            Some("slow_down") => {
+                interval += 5;
+                println!("[Device Login] Server asked to slow down, interval: {}s", interval);
+            }
+            Some(error) => {
+                let description = match error {
+                    "authorization_declined" => i18n::t("account.deviceLogin.declined", &serde_json::json!({})),
+                    "expired_token" => i18n::t("account.deviceLogin.expiredRelogin", &serde_json::json!({})),
+                    _ => response["error_description"].as_str().unwrap_or(error).to_string(),
+                };
+                println!("[Device Login] Poll error {}: {}", error, description);
+                let e = LauncherError::Auth { message: description };
+                emit_progress("error", i18n::message(&e.message_key(), e.context()));
+                return Err(e);
+            }
+        }
+    };
+    
+    emit_progress("authorized", i18n::message("account.deviceLogin.authorized", serde_json::json!({})));
+    
+    let access_token = ms_token["access_token"].as_str().ok_or_else(|| LauncherError::Auth {
+        message: i18n::t("account.errors.invalidTokenResponse", &serde_json::json!({})),
+    })?;
+    let refresh_token = ms_token["refresh_token"].as_str().map(|s| s.to_string());
+    let account = match build_microsoft_account(&state, access_token, refresh_token, ms_token["expires_in"].as_i64(), DEVICE_TOKEN_URL).await {
+        Ok(a) => a,
+        Err(e) => {
+            emit_progress("error", i18n::message(&e.message_key(), e.context()));
+            return Err(e);
+        }
+    };
+    
+    if login_cancelled(generation) {
+        return Err(LauncherError::LoginCancelled);
+    }
+    
+    let mut app_state = state.lock().await;
+    if let Some(existing) = app_state.accounts_data.accounts.iter_mut().find(|a| a.id == account.id) {
+        *existing = account.clone();
+    } else {
+        app_state.accounts_data.accounts.push(account.clone());
+    }
+    app_state.accounts_data.current_account = Some(account.id.clone());
+    app_state.save_accounts().map_err(|e| LauncherError::Io { message: e.to_string() })?;
+    
+    println!("[Device Login] Login successful: {}", account.username);
+    emit_progress("done", i18n::message("account.deviceLogin.done", serde_json::json!({})));
+    
+    Ok(serde_json::json!({
+        "success": true,
+        "account": account,
+        "currentAccount": app_state.accounts_data.current_account
+    }))
+}
+
+
 // 公开的认证函数，供 lib.rs 调用
-pub async fn complete_auth_with_code(code: &str) -> Result<Account, String> {
-    complete_microsoft_auth(code).await
+pub async fn complete_auth_with_code(state: &Arc<Mutex<AppState>>, code: &str) -> Result<Account, LauncherError> {
+    complete_microsoft_auth(state, code, &default_redirect_uri(), None).await
 }
 
-async fn complete_microsoft_auth(code: &str) -> Result<Account, String> {
+// 手动输入和 deep link 回调使用的重定向地址
+fn default_redirect_uri() -> String {
+    std::env::var("REDIRECT_URI")
+        .unwrap_or_else(|_| "http://localhost:23456/auth/callback".to_string())
+}
+
+async fn complete_microsoft_auth(
+    state: &Arc<Mutex<AppState>>,
+    code: &str,
+    redirect_uri: &str,
+    code_verifier: Option<&str>,
+) -> Result<Account, LauncherError> {
     println!("[MS Auth] Starting authentication with code...");
     
     let client_id = std::env::var("AZURE_CLIENT_ID")
         .unwrap_or_else(|_| "d0c7a28a-ec25-4910-97ef-5cdabade8891".to_string());
-    let redirect_uri = std::env::var("REDIRECT_URI")
-        .unwrap_or_else(|_| "http://localhost:23456/auth/callback".to_string());
     
     let client = reqwest::Client::new();
     
+    let mut form = vec![
+        ("client_id", client_id.as_str()),
+        ("code", code),
+        ("grant_type", "authorization_code"),
+        ("redirect_uri", redirect_uri),
+    ];
+    if let Some(verifier) = code_verifier {
+        form.push(("code_verifier", verifier));
+    }
+    
     // 1. 获取 Microsoft Token
     println!("[MS Auth] Step 1: Getting Microsoft token...");
     let ms_token: serde_json::Value = client
-        .post("https://login.live.com/oauth20_token.srf")
-        .form(&[
-            ("client_id", client_id.as_str()),
-            ("code", code),
-            ("grant_type", "authorization_code"),
-            ("redirect_uri", redirect_uri.as_str()),
-        ])
+        .post(LIVE_TOKEN_URL)
+        .form(&form)
         .send()
         .await
         .map_err(|e| {
             println!("[MS Auth] Token request failed: {}", e);
-            e.to_string()
+            e
         })?
         .json()
         .await
         .map_err(|e| {
             println!("[MS Auth] Token parse failed: {}", e);
-            e.to_string()
+            e
         })?;
     
     if ms_token.get("error").is_some() {
         let error_desc = ms_token["error_description"]
             .as_str()
-            .unwrap_or("获取Token失败");
+            .or_else(|| ms_token["error"].as_str())
+            .unwrap_or_default();
         println!("[MS Auth] Token error: {}", error_desc);
-        return Err(error_desc.to_string());
+        return Err(LauncherError::Auth { message: error_desc.to_string() });
     }
     
     let access_token = ms_token["access_token"].as_str().ok_or_else(|| {
         println!("[MS Auth] No access_token in response");
-        "无效的access_token".to_string()
+        LauncherError::Auth { message: i18n::t("account.errors.invalidTokenResponse", &serde_json::json!({})) }
     })?;
     let refresh_token = ms_token["refresh_token"].as_str().map(|s| s.to_string());
     println!("[MS Auth] Got Microsoft token successfully");
     
+    build_microsoft_account(state, access_token, refresh_token, ms_token["expires_in"].as_i64(), LIVE_TOKEN_URL).await
+}
+
+// Microsoft 令牌 -> Xbox Live -> XSTS -> Minecraft 令牌 -> 账户，各种登录方式共用
+// 没有角色时令牌暂存到 AppState，供 create_minecraft_profile 使用
+async fn build_microsoft_account(
+    state: &Arc<Mutex<AppState>>,
+    ms_access_token: &str,
+    refresh_token: Option<String>,
+    ms_expires_in: Option<i64>,
+    ms_token_url: &str,
+) -> Result<Account, LauncherError> {
+    let auth_failed = |message| LauncherError::Auth { message };
+    
     // 2-4. Xbox Live -> XSTS -> Minecraft Token
     println!("[MS Auth] Step 2-4: Getting Minecraft token...");
-    let mc_data = get_minecraft_token(access_token).await?;
+    let mc_data = get_minecraft_token(ms_access_token).await.map_err(auth_failed)?;
     println!("[MS Auth] Got Minecraft token successfully");
     
-    // 5. 获取 Minecraft Profile
+    let tokens = PendingProfile {
+        access_token: mc_data.access_token,
+        refresh_token,
+        token_expires_at: expires_at(mc_data.expires_in),
+        ms_token_expires_at: expires_at(ms_expires_in),
+        ms_token_url: ms_token_url.to_string(),
+    };
+    
+    // 5. 获取 Minecraft Profile，没有角色时检查是否拥有游戏
     println!("[MS Auth] Step 5: Getting Minecraft profile...");
-    let profile = get_minecraft_profile(&mc_data.access_token).await?;
+    let profile = match fetch_minecraft_profile(&tokens.access_token).await.map_err(auth_failed)? {
+        Some(profile) => profile,
+        None => {
+            let ownership = check_entitlements(&tokens.access_token).await.map_err(auth_failed)?;
+            println!("[MS Auth] No profile, ownership: {:?}", ownership);
+            if ownership == Ownership::None {
+                return Err(LauncherError::NoLicense);
+            }
+            state.lock().await.pending_microsoft_profile = Some(tokens);
+            return Err(LauncherError::NoProfile { game_pass: ownership == Ownership::GamePass });
+        }
+    };
     println!("[MS Auth] Got profile: {} ({})", profile.name, profile.id);
     
-    Ok(Account {
-        id: profile.id.clone(),
-        account_type: "microsoft".to_string(),
-        username: profile.name,
-        uuid: profile.id,
-        access_token: Some(mc_data.access_token),
-        refresh_token,
-        skin: profile.skin_url,
-        created_at: Some(chrono::Utc::now().timestamp_millis()),
-    })
+    Ok(tokens.into_account(profile))
+}
+
+/// 登录得到的令牌，创建角色前暂存
+pub struct PendingProfile {
+    access_token: String,
+    refresh_token: Option<String>,
+    token_expires_at: Option<i64>,
+    ms_token_expires_at: Option<i64>,
+    ms_token_url: String,
+}
+
+impl PendingProfile {
+    fn into_account(self, profile: MinecraftProfile) -> Account {
+        Account {
+            id: profile.id.clone(),
+            account_type: "microsoft".to_string(),
+            username: profile.name,
+            uuid: profile.id,
+            access_token: Some(self.access_token),
+            refresh_token: self.refresh_token,
+            skin: profile.skin_url,
+            created_at: Some(chrono::Utc::now().timestamp_millis()),
+            token_expires_at: self.token_expires_at,
+            ms_token_expires_at: self.ms_token_expires_at,
+            ms_token_url: Some(self.ms_token_url),
+            auth_server: None,
+            auth_server_name: None,
+            client_token: None,
+            skin_model: profile.skin_model,
+            cape: profile.cape_url,
+        }
+    }
 }
 
+#[derive(Debug, PartialEq)]
+pub enum Ownership {
+    Purchased,
+    GamePass,
+    None,
+}
+
+/// 通过 /entitlements/mcstore 检查账户是否拥有 Java 版
+pub async fn check_entitlements(mc_token: &str) -> Result<Ownership, String> {
+    let entitlements: serde_json::Value = reqwest::Client::new()
+        .get("https://api.minecraftservices.com/entitlements/mcstore")
+        .bearer_auth(mc_token)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+    
+    let items = entitlements["items"].as_array().cloned().unwrap_or_default();
+    let has = |name: &str| items.iter().any(|i| i["name"] == name);
+    
+    Ok(if has("game_pass_pc") || items.iter().any(|i| i["source"] == "GAMEPASS") {
+        Ownership::GamePass
+    } else if has("game_minecraft") || has("product_minecraft") {
+        Ownership::Purchased
+    } else {
+        Ownership::None
+    })
+}
 
 struct MinecraftTokenData {
     access_token: String,
+    expires_in: Option<i64>,
 }
 
-struct MinecraftProfile {
-    id: String,
-    name: String,
-    skin_url: Option<String>,
+pub(crate) struct MinecraftProfile {
+    pub id: String,
+    pub name: String,
+    pub skin_url: Option<String>,
+    pub skin_model: Option<String>,
+    pub cape_url: Option<String>,
+}
+
+impl MinecraftProfile {
+    /// 从 Minecraft 服务 profile 响应中解析，取 ACTIVE 状态的皮肤和披风
+    pub(crate) fn from_json(profile: &serde_json::Value) -> Option<Self> {
+        let active = |key: &str| profile[key].as_array().and_then(|items| {
+            items.iter()
+                .find(|i| i["state"] == "ACTIVE")
+                .or_else(|| items.first())
+                .cloned()
+        });
+        let skin = active("skins");
+        let cape = profile["capes"].as_array()
+            .and_then(|capes| capes.iter().find(|c| c["state"] == "ACTIVE").cloned());
+        
+        Some(Self {
+            id: profile["id"].as_str()?.to_string(),
+            name: profile["name"].as_str()?.to_string(),
+            skin_url: skin.as_ref().and_then(|s| s["url"].as_str()).map(|s| s.to_string()),
+            skin_model: skin.as_ref().and_then(|s| s["variant"].as_str()).map(|s| s.to_lowercase()),
+            cape_url: cape.as_ref().and_then(|c| c["url"].as_str()).map(|s| s.to_string()),
+        })
+    }
 }
 
 async fn get_minecraft_token(ms_access_token: &str) -> Result<MinecraftTokenData, String> {
@@ -493,7 +1084,7 @@ async fn get_minecraft_token(ms_access_token: &str) -> Result<MinecraftTokenData
     
     let xbl_token = xbl_response["Token"].as_str().ok_or_else(|| {
         println!("[MC Token] No XBL token in response: {:?}", xbl_response);
-        "无效的XBL Token".to_string()
+        i18n::t("account.errors.invalidXblToken", &serde_json::json!({}))
     })?;
     println!("[MC Token] Got XBL token");
     
@@ -526,26 +1117,26 @@ async fn get_minecraft_token(ms_access_token: &str) -> Result<MinecraftTokenData
     // 检查 XSTS 错误
     if let Some(xerr) = xsts_response.get("XErr") {
         let error_code = xerr.as_u64().unwrap_or(0);
-        let error_msg = match error_code {
-            2148916233 => "此账户没有 Xbox 账户，请先创建一个",
-            2148916235 => "Xbox Live 在您的国家/地区不可用",
-            2148916236 | 2148916237 => "此账户需要成人验证（韩国）",
-            2148916238 => "此账户是未成年人账户，需要添加到家庭组",
-            _ => "Xbox Live 认证失败",
+        let key = match error_code {
+            2148916233 => "noXboxAccount",
+            2148916235 => "xboxUnavailable",
+            2148916236 | 2148916237 => "adultVerification",
+            2148916238 => "childAccount",
+            _ => "xboxAuthFailed",
         };
-        println!("[MC Token] XSTS error {}: {}", error_code, error_msg);
-        return Err(error_msg.to_string());
+        println!("[MC Token] XSTS error {}: {}", error_code, key);
+        return Err(i18n::t(&format!("account.xsts.{}", key), &serde_json::json!({})));
     }
     
     let xsts_token = xsts_response["Token"].as_str().ok_or_else(|| {
         println!("[MC Token] No XSTS token in response: {:?}", xsts_response);
-        "无效的XSTS Token".to_string()
+        i18n::t("account.errors.invalidXstsToken", &serde_json::json!({}))
     })?;
     let uhs = xsts_response["DisplayClaims"]["xui"][0]["uhs"]
         .as_str()
         .ok_or_else(|| {
             println!("[MC Token] No UHS in response");
-            "无效的UHS".to_string()
+            i18n::t("account.errors.invalidXstsToken", &serde_json::json!({}))
         })?;
     println!("[MC Token] Got XSTS token");
     
@@ -574,20 +1165,29 @@ async fn get_minecraft_token(ms_access_token: &str) -> Result<MinecraftTokenData
         .as_str()
         .ok_or_else(|| {
             println!("[MC Token] No MC token in response: {:?}", mc_response);
-            "无效的Minecraft Token".to_string()
+            i18n::t("account.errors.invalidMinecraftToken", &serde_json::json!({}))
         })?
         .to_string();
     
     println!("[MC Token] Got Minecraft token successfully");
-    Ok(MinecraftTokenData { access_token: mc_token })
+    Ok(MinecraftTokenData {
+        access_token: mc_token,
+        expires_in: mc_response["expires_in"].as_i64(),
+    })
 }
 
 
 async fn get_minecraft_profile(mc_token: &str) -> Result<MinecraftProfile, String> {
+    fetch_minecraft_profile(mc_token).await?
+        .ok_or_else(|| i18n::t("errors.noProfile", &serde_json::json!({})))
+}
+
+// 获取角色信息，账户没有角色 (404) 时返回 None
+async fn fetch_minecraft_profile(mc_token: &str) -> Result<Option<MinecraftProfile>, String> {
     println!("[MC Profile] Getting Minecraft profile...");
     let client = reqwest::Client::new();
     
-    let profile: serde_json::Value = client
+    let response = client
         .get("https://api.minecraftservices.com/minecraft/profile")
         .header("Authorization", format!("Bearer {}", mc_token))
         .send()
@@ -595,7 +1195,12 @@ async fn get_minecraft_profile(mc_token: &str) -> Result<MinecraftProfile, Strin
         .map_err(|e| {
             println!("[MC Profile] Request failed: {}", e);
             e.to_string()
-        })?
+        })?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        println!("[MC Profile] Account has no profile");
+        return Ok(None);
+    }
+    let profile: serde_json::Value = response
         .json()
         .await
         .map_err(|e| {
@@ -606,62 +1211,153 @@ async fn get_minecraft_profile(mc_token: &str) -> Result<MinecraftProfile, Strin
     if profile.get("error").is_some() || profile.get("errorMessage").is_some() {
         let error_msg = profile["errorMessage"].as_str()
             .or(profile["error"].as_str())
-            .unwrap_or("无法获取Minecraft账户信息");
+            .unwrap_or_default();
         println!("[MC Profile] Error: {}", error_msg);
-        return Err("无法获取Minecraft账户信息，可能未购买游戏".to_string());
+        return Err(i18n::t("account.errors.profileRequestFailed", &serde_json::json!({ "message": error_msg })));
     }
     
-    let id = profile["id"].as_str().ok_or_else(|| {
-        println!("[MC Profile] No ID in response: {:?}", profile);
-        "无效的Profile ID".to_string()
-    })?.to_string();
-    let name = profile["name"].as_str().ok_or_else(|| {
-        println!("[MC Profile] No name in response");
-        "无效的Profile Name".to_string()
-    })?.to_string();
-    let skin_url = profile["skins"]
-        .as_array()
-        .and_then(|skins| skins.first())
-        .and_then(|skin| skin["url"].as_str())
-        .map(|s| s.to_string());
+    let profile = MinecraftProfile::from_json(&profile).ok_or_else(|| {
+        println!("[MC Profile] No ID or name in response: {:?}", profile);
+        i18n::t("account.errors.invalidProfile", &serde_json::json!({}))
+    })?;
     
-    println!("[MC Profile] Got profile: {} ({}), skin: {:?}", name, id, skin_url.is_some());
+    println!("[MC Profile] Got profile: {} ({}), skin: {:?}, cape: {:?}",
+        profile.name, profile.id, profile.skin_url.is_some(), profile.cape_url.is_some());
     
-    Ok(MinecraftProfile { id, name, skin_url })
+    Ok(Some(profile))
 }
 
 #[tauri::command]
 pub async fn refresh_microsoft_account(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     account_id: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let mut app_state = state.lock().await;
     
     let account = app_state.accounts_data.accounts.iter()
+        .find(|a| a.id == account_id)
+        .cloned()
+        .ok_or_else(|| account_not_found(&account_id))?;
+    if account.account_type != "microsoft" {
+        return Err(LauncherError::UnsupportedAccountType { account_id, expected: "microsoft" });
+    }
+    
+    let refreshed = refresh_microsoft_tokens(&account).await.map_err(|e| {
+        println!("[MS Refresh] Refresh failed: {}", e);
+        e.into_launcher_error(&account_id)
+    })?;
+    
+    // 更新账户
+    if let Some(acc) = app_state.accounts_data.accounts.iter_mut()
+        .find(|a| a.id == account_id) 
+    {
+        *acc = refreshed;
+    }
+    
+    app_state.save_accounts().map_err(|e| LauncherError::Io { message: e.to_string() })?;
+    
+    let updated = app_state.accounts_data.accounts.iter()
         .find(|a| a.id == account_id)
         .cloned();
     
-    let account = match account {
-        Some(a) if a.account_type == "microsoft" => a,
-        _ => return Ok(serde_json::json!({
-            "success": false,
-            "error": "账户不存在或不是微软账户"
-        })),
+    Ok(serde_json::json!({
+        "success": true,
+        "account": updated
+    }))
+}
+
+
+/// 获取可用的微软账户，令牌即将过期时先刷新并保存，皮肤和角色名管理共用
+pub async fn valid_microsoft_account(
+    state: &Arc<Mutex<AppState>>,
+    account_id: &str,
+) -> Result<Account, LauncherError> {
+    let account = {
+        let app_state = state.lock().await;
+        app_state.accounts_data.accounts.iter()
+            .find(|a| a.id == account_id)
+            .cloned()
+            .ok_or_else(|| account_not_found(account_id))?
     };
+    if account.account_type != "microsoft" {
+        return Err(LauncherError::UnsupportedAccountType {
+            account_id: account_id.to_string(),
+            expected: "microsoft",
+        });
+    }
+    if !needs_token_refresh(&account) {
+        return Ok(account);
+    }
     
+    let refreshed = refresh_microsoft_tokens(&account).await
+        .map_err(|e| e.into_launcher_error(account_id))?;
+    let mut app_state = state.lock().await;
+    if let Some(acc) = app_state.accounts_data.accounts.iter_mut().find(|a| a.id == account_id) {
+        *acc = refreshed.clone();
+    }
+    app_state.save_accounts().map_err(|e| LauncherError::Io { message: e.to_string() })?;
+    Ok(refreshed)
+}
+
+/// 使用 refresh_token 刷新微软账户的全部令牌，返回更新后的账户
+pub async fn refresh_microsoft_tokens(account: &Account) -> Result<Account, RefreshError> {
     let refresh_token = account.refresh_token.as_ref()
-        .ok_or("无refresh_token")?;
+        .ok_or_else(|| RefreshError::InvalidGrant("missing refresh_token".to_string()))?;
     
     let client_id = std::env::var("AZURE_CLIENT_ID")
         .unwrap_or_else(|_| "d0c7a28a-ec25-4910-97ef-5cdabade8891".to_string());
     
+    // 设备代码登录的令牌由 login.microsoftonline.com 签发，不能在 login.live.com 刷新；
+    // 旧账户没有记录签发端点，两个端点依次尝试
     let client = reqwest::Client::new();
+    let (token_url, ms_token) = match account.ms_token_url.as_deref() {
+        Some(url) => (url, request_microsoft_refresh(&client, url, &client_id, refresh_token, &account.username).await?),
+        None => match request_microsoft_refresh(&client, LIVE_TOKEN_URL, &client_id, refresh_token, &account.username).await {
+            Err(RefreshError::InvalidGrant(_)) => (
+                DEVICE_TOKEN_URL,
+                request_microsoft_refresh(&client, DEVICE_TOKEN_URL, &client_id, refresh_token, &account.username).await?,
+            ),
+            result => (LIVE_TOKEN_URL, result?),
+        },
+    };
+    
+    let access_token = ms_token["access_token"].as_str()
+        .ok_or_else(|| RefreshError::Other(i18n::t("account.errors.invalidTokenResponse", &serde_json::json!({}))))?;
+    
+    let mc_data = get_minecraft_token(access_token).await?;
+    let profile = get_minecraft_profile(&mc_data.access_token).await?;
     
+    let mut updated = account.clone();
+    updated.username = profile.name;
+    updated.access_token = Some(mc_data.access_token);
+    // 微软可能不返回新的 refresh_token，此时沿用旧的
+    if let Some(new_refresh) = ms_token["refresh_token"].as_str() {
+        updated.refresh_token = Some(new_refresh.to_string());
+    }
+    updated.skin = profile.skin_url;
+    updated.skin_model = profile.skin_model;
+    updated.cape = profile.cape_url;
+    updated.token_expires_at = expires_at(mc_data.expires_in);
+    updated.ms_token_expires_at = expires_at(ms_token["expires_in"].as_i64());
+    updated.ms_token_url = Some(token_url.to_string());
+    
+    println!("[MS Refresh] Tokens refreshed for {}", updated.username);
+    Ok(updated)
+}
+
+async fn request_microsoft_refresh(
+    client: &reqwest::Client,
+    token_url: &str,
+    client_id: &str,
+    refresh_token: &str,
+    username: &str,
+) -> Result<serde_json::Value, RefreshError> {
+    println!("[MS Refresh] Refreshing tokens for {} via {}", username, token_url);
     let ms_token: serde_json::Value = client
-        .post("https://login.live.com/oauth20_token.srf")
+        .post(token_url)
         .form(&[
-            ("client_id", client_id.as_str()),
-            ("refresh_token", refresh_token.as_str()),
+            ("client_id", client_id),
+            ("refresh_token", refresh_token),
             ("grant_type", "refresh_token"),
             ("scope", "XboxLive.signin offline_access"),
         ])
@@ -672,58 +1368,228 @@ pub async fn refresh_microsoft_account(
         .await
         .map_err(|e| e.to_string())?;
     
-    if ms_token.get("error").is_some() {
+    if let Some(error) = ms_token["error"].as_str() {
+        let description = ms_token["error_description"].as_str().unwrap_or(error).to_string();
+        return Err(if error == "invalid_grant" {
+            RefreshError::InvalidGrant(description)
+        } else {
+            RefreshError::Other(description)
+        });
+    }
+    Ok(ms_token)
+}
+
+// ═══════════════════════════════════════════════════════════
+// 角色名管理
+// ═══════════════════════════════════════════════════════════
+
+const MC_PROFILE_API: &str = "https://api.minecraftservices.com/minecraft/profile";
+
+// 角色名接口的错误信息
+fn profile_name_error(name: &str, status: reqwest::StatusCode, body: &serde_json::Value) -> LauncherError {
+    let detail = body.pointer("/details/status").and_then(|v| v.as_str());
+    let key = match detail {
+        Some("DUPLICATE") => "duplicate",
+        Some("NOT_ALLOWED") => "notAllowed",
+        _ if status == reqwest::StatusCode::FORBIDDEN => "cooldown",
+        _ if status == reqwest::StatusCode::BAD_REQUEST => "invalid",
+        _ => "failed",
+    };
+    let message = match body["errorMessage"].as_str() {
+        Some(message) if key == "failed" => message.to_string(),
+        _ => i18n::t(&format!("account.profileName.{}", key), &serde_json::json!({ "status": status.as_u16() })),
+    };
+    LauncherError::ProfileNameRejected {
+        name: name.to_string(),
+        status: detail.map(|d| d.to_string()),
+        message,
+    }
+}
+
+/// 按 Mojang 的角色名规则校验: 3-16 位英文字母、数字和下划线
+fn validate_profile_name(name: &str) -> Result<(), LauncherError> {
+    let valid = (3..=16).contains(&name.len())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        return Ok(());
+    }
+    Err(LauncherError::ProfileNameRejected {
+        name: name.to_string(),
+        status: Some("NOT_ALLOWED".to_string()),
+        message: i18n::t("account.profileName.invalid", &serde_json::json!({})),
+    })
+}
+
+// 查询角色名是否可用，未指定账户时使用登录时暂存的令牌（创建角色场景）
+#[tauri::command]
+pub async fn check_profile_name(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    name: String,
+    account_id: Option<String>,
+) -> Result<serde_json::Value, LauncherError> {
+    let name = name.trim().to_string();
+    if validate_profile_name(&name).is_err() {
         return Ok(serde_json::json!({
-            "success": false,
-            "error": "刷新失败"
+            "success": true,
+            "status": "NOT_ALLOWED",
+            "available": false
         }));
     }
     
-    let access_token = ms_token["access_token"].as_str().ok_or("无效token")?;
-    let new_refresh = ms_token["refresh_token"].as_str().map(|s| s.to_string());
+    let token = match &account_id {
+        Some(id) => valid_microsoft_account(&state, id).await?.access_token.unwrap_or_default(),
+        None => state.lock().await.pending_microsoft_profile.as_ref()
+            .map(|p| p.access_token.clone())
+            .ok_or(LauncherError::ReloginRequired { account_id: None })?,
+    };
     
-    let mc_data = get_minecraft_token(access_token).await?;
-    let profile = get_minecraft_profile(&mc_data.access_token).await?;
+    let response: serde_json::Value = reqwest::Client::new()
+        .get(format!("{}/name/{}/available", MC_PROFILE_API, urlencoding::encode(&name)))
+        .bearer_auth(token)
+        .send()
+        .await?
+        .json()
+        .await?;
     
-    // 更新账户
-    if let Some(acc) = app_state.accounts_data.accounts.iter_mut()
-        .find(|a| a.id == account_id) 
-    {
-        acc.username = profile.name;
-        acc.access_token = Some(mc_data.access_token);
-        acc.refresh_token = new_refresh;
-        acc.skin = profile.skin_url;
-    }
+    let status = response["status"].as_str().unwrap_or("NOT_ALLOWED");
+    Ok(serde_json::json!({
+        "success": true,
+        "status": status,
+        "available": status == "AVAILABLE"
+    }))
+}
+
+// 为尚未创建角色的账户创建角色，使用登录时暂存的令牌
+#[tauri::command]
+pub async fn create_minecraft_profile(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    name: String,
+) -> Result<serde_json::Value, LauncherError> {
+    let name = name.trim();
+    validate_profile_name(name)?;
+    let pending = state.lock().await.pending_microsoft_profile.take();
+    let Some(pending) = pending else {
+        return Err(LauncherError::ReloginRequired { account_id: None });
+    };
     
-    app_state.save_accounts().map_err(|e| e.to_string())?;
+    println!("[MS Auth] Creating profile {}", name);
+    let response = reqwest::Client::new()
+        .post(MC_PROFILE_API)
+        .bearer_auth(&pending.access_token)
+        .json(&serde_json::json!({ "profileName": name }))
+        .send()
+        .await;
+    let response = match response {
+        Ok(r) => r,
+        Err(e) => {
+            state.lock().await.pending_microsoft_profile = Some(pending);
+            return Err(e.into());
+        }
+    };
     
-    let updated = app_state.accounts_data.accounts.iter()
-        .find(|a| a.id == account_id)
-        .cloned();
+    let status = response.status();
+    let body: serde_json::Value = response.json().await.unwrap_or(serde_json::Value::Null);
+    let profile = status.is_success().then(|| MinecraftProfile::from_json(&body)).flatten();
+    let Some(profile) = profile else {
+        println!("[MS Auth] Create profile failed ({}): {:?}", status, body);
+        // 失败后保留令牌，允许换个名字重试
+        state.lock().await.pending_microsoft_profile = Some(pending);
+        return Err(profile_name_error(name, status, &body));
+    };
+    
+    let account = pending.into_account(profile);
+    let mut app_state = state.lock().await;
+    app_state.accounts_data.accounts.retain(|a| a.id != account.id);
+    app_state.accounts_data.accounts.push(account.clone());
+    app_state.accounts_data.current_account = Some(account.id.clone());
+    app_state.save_accounts().map_err(|e| LauncherError::Io { message: e.to_string() })?;
     
     Ok(serde_json::json!({
         "success": true,
-        "account": updated
+        "account": account,
+        "currentAccount": app_state.accounts_data.current_account
     }))
 }
 
+// 查询角色名修改状态（上次修改时间、是否允许修改）
+#[tauri::command]
+pub async fn get_name_change_info(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    account_id: String,
+) -> Result<serde_json::Value, LauncherError> {
+    let account = valid_microsoft_account(&state, &account_id).await?;
+    
+    let info: serde_json::Value = reqwest::Client::new()
+        .get(format!("{}/namechange", MC_PROFILE_API))
+        .bearer_auth(account.access_token.unwrap_or_default())
+        .send()
+        .await?
+        .json()
+        .await?;
+    
+    Ok(serde_json::json!({
+        "success": true,
+        "changedAt": info["changedAt"],
+        "createdAt": info["createdAt"],
+        "nameChangeAllowed": info["nameChangeAllowed"].as_bool().unwrap_or(false)
+    }))
+}
 
-// 皮肤管理
 #[tauri::command]
-pub async fn select_skin_file() -> Result<serde_json::Value, String> {
-    // 这个命令需要在前端使用 dialog plugin 实现
+pub async fn change_profile_name(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    account_id: String,
+    name: String,
+) -> Result<serde_json::Value, LauncherError> {
+    let name = name.trim();
+    validate_profile_name(name)?;
+    let account = valid_microsoft_account(&state, &account_id).await?;
+    
+    println!("[MS Auth] Changing profile name {} -> {}", account.username, name);
+    let response = reqwest::Client::new()
+        .put(format!("{}/name/{}", MC_PROFILE_API, urlencoding::encode(name)))
+        .bearer_auth(account.access_token.unwrap_or_default())
+        .send()
+        .await?;
+    
+    let status = response.status();
+    let body: serde_json::Value = response.json().await.unwrap_or(serde_json::Value::Null);
+    let profile = status.is_success().then(|| MinecraftProfile::from_json(&body)).flatten();
+    let Some(profile) = profile else {
+        println!("[MS Auth] Name change failed ({}): {:?}", status, body);
+        return Err(profile_name_error(name, status, &body));
+    };
+    
+    let mut app_state = state.lock().await;
+    let updated = app_state.accounts_data.accounts.iter_mut()
+        .find(|a| a.id == account_id)
+        .map(|acc| {
+            acc.username = profile.name;
+            acc.clone()
+        });
+    app_state.save_accounts().map_err(|e| LauncherError::Io { message: e.to_string() })?;
+    
     Ok(serde_json::json!({
-        "success": false,
-        "error": "请使用前端dialog选择文件"
+        "success": true,
+        "account": updated
     }))
 }
 
+// 皮肤管理
+#[tauri::command]
+pub async fn select_skin_file() -> Result<serde_json::Value, LauncherError> {
+    // 文件选择由前端的 dialog 插件完成，tauri-bridge 不会调用到这里
+    Err(LauncherError::Internal {
+        message: i18n::t("skins.errors.useFileDialog", &serde_json::json!({})),
+    })
+}
+
 #[tauri::command]
 pub async fn set_offline_skin(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     account_id: String,
     skin_path: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let mut app_state = state.lock().await;
     
     let skins_dir = app_state.get_skins_dir();
@@ -733,24 +1599,27 @@ pub async fn set_offline_skin(
         .find(|a| a.id == account_id);
     
     match account {
-        None => Ok(serde_json::json!({
-            "success": false,
-            "error": "账户不存在"
-        })),
-        Some(acc) if acc.account_type != "offline" => Ok(serde_json::json!({
-            "success": false,
-            "error": "只能设置离线账户的皮肤"
-        })),
+        None => Err(account_not_found(&account_id)),
+        Some(acc) if acc.account_type != "offline" => Err(LauncherError::UnsupportedAccountType {
+            account_id: account_id.clone(),
+            expected: "offline",
+        }),
         Some(acc) => {
-            std::fs::copy(&skin_path, &dest_path)
-                .map_err(|e| e.to_string())?;
+            let bytes = std::fs::read(&skin_path)?;
+            let skin = crate::skin_render::load_skin(&bytes)?;
+            // 保存转换后的皮肤，旧版皮肤也能在新版本中正确显示
+            std::fs::write(&dest_path, skin.to_png()?)?;
             
             acc.skin = Some(dest_path.to_string_lossy().to_string());
-            app_state.save_accounts().map_err(|e| e.to_string())?;
+            acc.skin_model = Some(skin.model().to_string());
+            let account = acc.clone();
+            app_state.save_accounts().map_err(|e| LauncherError::Io { message: e.to_string() })?;
             
             Ok(serde_json::json!({
                 "success": true,
-                "skinPath": dest_path.to_string_lossy()
+                "skinPath": dest_path.to_string_lossy(),
+                "account": account,
+                "converted": skin.converted
             }))
         }
     }
@@ -760,17 +1629,14 @@ pub async fn set_offline_skin(
 pub async fn get_skin_path(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     account_id: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let app_state = state.lock().await;
     
     let account = app_state.accounts_data.accounts.iter()
         .find(|a| a.id == account_id);
     
     match account {
-        None => Ok(serde_json::json!({
-            "success": false,
-            "error": "账户不存在"
-        })),
+        None => Err(account_not_found(&account_id)),
         Some(acc) => Ok(serde_json::json!({
             "success": true,
             "skinPath": acc.skin
@@ -782,7 +1648,7 @@ pub async fn get_skin_path(
 pub async fn get_account_avatar(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     account_id: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let app_state = state.lock().await;
     
     let avatars_dir = app_state.get_avatars_dir();
@@ -832,7 +1698,7 @@ pub async fn get_account_avatar(
 pub async fn refresh_account_avatar(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     account_id: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let app_state = state.lock().await;
     
     let avatars_dir = app_state.get_avatars_dir();
@@ -862,25 +1728,12 @@ pub async fn refresh_account_avatar(
     }))
 }
 
-async fn extract_avatar(skin_source: &str, dest_path: &std::path::Path) -> Result<String, String> {
+async fn extract_avatar(skin_source: &str, dest_path: &std::path::Path) -> Result<String, LauncherError> {
     println!("[Avatar] Extracting avatar from: {}", skin_source);
     
-    let img_data = if skin_source.starts_with("http") {
-        reqwest::get(skin_source)
-            .await
-            .map_err(|e| e.to_string())?
-            .bytes()
-            .await
-            .map_err(|e| e.to_string())?
-            .to_vec()
-    } else if std::path::Path::new(skin_source).exists() {
-        std::fs::read(skin_source).map_err(|e| e.to_string())?
-    } else {
-        return Err("皮肤文件不存在".to_string());
-    };
-    
-    let img = image::load_from_memory(&img_data)
-        .map_err(|e| e.to_string())?;
+    let img_data = crate::skin_render::fetch_skin_bytes(skin_source).await?;
+    // 校验尺寸并将旧版 64x32 皮肤转换为 64x64
+    let img = image::DynamicImage::ImageRgba8(crate::skin_render::load_skin(&img_data)?.image);
     
     println!("[Avatar] Skin size: {}x{}", img.width(), img.height());
     
@@ -931,10 +1784,74 @@ async fn extract_avatar(skin_source: &str, dest_path: &std::path::Path) -> Resul
     
     // 确保目录存在
     if let Some(parent) = dest_path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        std::fs::create_dir_all(parent)?;
     }
     
-    avatar.save(dest_path).map_err(|e| e.to_string())?;
+    avatar.save(dest_path).map_err(|e| LauncherError::Io { message: e.to_string() })?;
     
     Ok(dest_path.to_string_lossy().to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offline_uuid_matches_java_name_uuid_from_bytes() {
+        // Java: UUID.nameUUIDFromBytes("OfflinePlayer:Notch".getBytes(UTF_8))
+        assert_eq!(offline_uuid("Notch"), "b50ad385829d3141a2167e7d7539ba7f");
+        assert_eq!(offline_uuid("jeb_"), "a762f5604fce3236812ab80efff0b62b");
+    }
+
+    #[test]
+    fn offline_uuid_is_version_3_and_case_sensitive() {
+        let uuid = uuid::Uuid::parse_str(&offline_uuid("Steve")).unwrap();
+        assert_eq!(uuid.get_version_num(), 3);
+        assert_eq!(uuid.get_variant(), uuid::Variant::RFC4122);
+        assert_ne!(offline_uuid("Steve"), offline_uuid("steve"));
+    }
+    #[test]
+    fn vanilla_usernames_follow_the_official_rules() {
+        for name in ["Steve", "jeb_", "abc", "A_very_long_nam3"] {
+            assert!(validate_offline_username(name, false).is_ok(), "{} should be valid", name);
+        }
+        for name in ["ab", "seventeen_chars_x", "", "with space", "名字", "dash-name"] {
+            assert!(validate_offline_username(name, false).is_err(), "{} should be invalid", name);
+        }
+    }
+
+    #[test]
+    fn nonstandard_usernames_only_reject_whitespace_and_length() {
+        for name in ["名字", "a", "dash-name", "十六个字十六个字十六个字十六个字"] {
+            assert!(validate_offline_username(name, true).is_ok(), "{} should be valid", name);
+        }
+        for name in ["", "with space", "tab\tname", "十七个字十七个字十七个字十七个字多"] {
+            assert!(validate_offline_username(name, true).is_err(), "{} should be invalid", name);
+        }
+    }
+
+    fn account(id: &str, account_type: &str, username: &str) -> Account {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "type": account_type,
+            "username": username,
+            "uuid": offline_uuid(username)
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn duplicate_usernames_ignore_case_and_type() {
+        let accounts = vec![account("a", "offline", "Steve"), account("b", "microsoft", "Alex")];
+        assert_eq!(find_duplicate_username(&accounts, "steve", None).map(|a| a.id.as_str()), Some("a"));
+        assert_eq!(find_duplicate_username(&accounts, "ALEX", None).map(|a| a.id.as_str()), Some("b"));
+        assert!(find_duplicate_username(&accounts, "Herobrine", None).is_none());
+    }
+
+    #[test]
+    fn duplicate_check_skips_the_renamed_account() {
+        let accounts = vec![account("a", "offline", "Steve"), account("b", "offline", "Alex")];
+        assert!(find_duplicate_username(&accounts, "STEVE", Some("a")).is_none());
+        assert_eq!(find_duplicate_username(&accounts, "steve", Some("b")).map(|a| a.id.as_str()), Some("a"));
+    }
+}
@@ -0,0 +1,673 @@
+use crate::error::LauncherError;
+use crate::state::AppState;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{Emitter, Manager};
+use tokio::sync::Mutex;
+
+// 模组二分排查
+// 用 ddmin 算法寻找最小的崩溃模组组合：每轮只启用一部分模组（连同它们的必需依赖）并重新启动游戏，
+// 游戏退出时进程监控根据退出码和崩溃分析记录本轮结果，直到组合无法再缩小。
+// 排查结束或中止时把模组恢复为开始时的启用状态，有文件未能恢复时保留恢复记录以便重试
+
+/// 进程监控记录结果后发出，由 lib.rs 中的监听器启动下一轮
+pub const NEXT_ROUND_EVENT: &str = "mod-bisect-next";
+
+// 排查开始时写入数据目录，排查结束或中止后删除；启动器在排查中途退出时，下次启动据此恢复模组状态
+const RESTORE_FILE: &str = "mod_bisect_restore.json";
+
+// 这类崩溃说明该组合缺少依赖、根本无法加载，不算复现了原来的崩溃
+const UNRESOLVED_CATEGORIES: [&str; 2] = ["modResolution", "forgeDependency"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BisectStatus {
+    Running,
+    Finished,
+    Aborted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BisectOutcome {
+    Crashed,
+    Passed,
+    Unresolved,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BisectRun {
+    pub step: usize,
+    pub enabled: Vec<String>,
+    pub outcome: BisectOutcome,
+    pub category: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BisectSession {
+    pub version_id: String,
+    pub account_id: Option<String>,
+    pub mods_dir: PathBuf,
+    pub status: BisectStatus,
+    // 开始时已启用的模组文件（不含 .disabled 后缀），排查只在这些模组中进行
+    pub original: Vec<String>,
+    pub names: HashMap<String, String>,
+    // 模组文件 -> 其必需依赖所在的模组文件
+    #[serde(skip)]
+    dependencies: HashMap<String, Vec<String>>,
+    // 当前已知会崩溃的最小组合
+    pub failing: Vec<String>,
+    // ddmin 的划分粒度和本轮下一个待测组合的序号（前 granularity 个为子集，其后为补集）
+    granularity: usize,
+    next: usize,
+    pub testing: Vec<String>,
+    pub step: usize,
+    pub history: Vec<BisectRun>,
+    // 中止排查时结束的游戏进程，它们退出时不记录结果也不弹出崩溃窗口
+    #[serde(skip)]
+    stopped_pids: Vec<u32>,
+}
+
+impl BisectSession {
+    fn new(version_id: String, account_id: Option<String>, mods_dir: PathBuf) -> Result<Self, LauncherError> {
+        let index = crate::mods::build_mod_index(&mods_dir);
+        Self::from_index(version_id, account_id, mods_dir, index)
+    }
+
+    fn from_index(
+        version_id: String,
+        account_id: Option<String>,
+        mods_dir: PathBuf,
+        index: Vec<crate::mods::ModIndexEntry>,
+    ) -> Result<Self, LauncherError> {
+        let index: Vec<_> = index.into_iter().filter(|entry| entry.enabled).collect();
+        if index.is_empty() {
+            return Err(LauncherError::NoModsToBisect { mods_dir: mods_dir.to_string_lossy().to_string() });
+        }
+
+        // 依赖可能指向内嵌模组或 provides 别名（如 fabric-api-base），都映射到提供它的文件；
+        // 多个文件提供同一 ID 时以模组本身的 ID 为准
+        let mut files_by_id: HashMap<&str, &str> = HashMap::new();
+        for entry in &index {
+            for id in &entry.provides {
+                files_by_id.entry(id.as_str()).or_insert(entry.file.as_str());
+            }
+        }
+        for entry in &index {
+            if let Some(id) = entry.id.as_deref() {
+                files_by_id.insert(id, entry.file.as_str());
+            }
+        }
+        let dependencies = index.iter()
+            .map(|entry| {
+                let files = entry.dependencies.iter()
+                    .filter_map(|id| files_by_id.get(id.as_str()))
+                    .filter(|file| **file != entry.file)
+                    .map(|file| file.to_string())
+                    .collect();
+                (entry.file.clone(), files)
+            })
+            .collect();
+        let mut original: Vec<String> = index.iter().map(|entry| entry.file.clone()).collect();
+        original.sort();
+
+        Ok(BisectSession {
+            version_id,
+            account_id,
+            status: BisectStatus::Running,
+            names: index.iter().map(|entry| (entry.file.clone(), entry.name.clone())).collect(),
+            dependencies,
+            failing: original.clone(),
+            original,
+            mods_dir,
+            granularity: 2,
+            next: 0,
+            testing: Vec::new(),
+            step: 0,
+            history: Vec::new(),
+            stopped_pids: Vec::new(),
+        })
+    }
+
+    // 加上所有必需依赖后的组合
+    fn closure(&self, mods: &[String]) -> Vec<String> {
+        let mut result: BTreeSet<String> = BTreeSet::new();
+        let mut pending = mods.to_vec();
+        while let Some(file) = pending.pop() {
+            if result.insert(file.clone()) {
+                pending.extend(self.dependencies.get(&file).cloned().unwrap_or_default());
+            }
+        }
+        result.into_iter().collect()
+    }
+
+    // 把当前崩溃组合均分为 granularity 份，依次测试各子集和（份数大于 2 时）各补集
+    fn candidates(&self) -> Vec<Vec<String>> {
+        let n = self.granularity;
+        let len = self.failing.len();
+        let chunks: Vec<Vec<String>> = (0..n)
+            .map(|i| self.failing[i * len / n..(i + 1) * len / n].to_vec())
+            .collect();
+        let complements = chunks.iter()
+            .filter(|_| n > 2)
+            .map(|chunk| self.failing.iter().filter(|m| !chunk.contains(m)).cloned().collect());
+        chunks.clone().into_iter().chain(complements).collect()
+    }
+
+    // 下一轮要测试的组合，没有时说明当前组合已是最小
+    fn advance(&mut self) -> Option<Vec<String>> {
+        loop {
+            if self.failing.len() <= 1 {
+                return None;
+            }
+            let candidates = self.candidates();
+            while self.next < candidates.len() {
+                let config = self.closure(&candidates[self.next]);
+                self.next += 1;
+                // 依赖补全后与当前组合相同，或已测试过的组合不必再启动
+                if config.len() == self.failing.len() || self.history.iter().any(|run| run.enabled == config) {
+                    continue;
+                }
+                return Some(config);
+            }
+            if self.granularity >= self.failing.len() {
+                return None;
+            }
+            self.granularity = (self.granularity * 2).min(self.failing.len());
+            self.next = 0;
+        }
+    }
+
+    fn record(&mut self, outcome: BisectOutcome, category: Option<String>) {
+        self.history.push(BisectRun {
+            step: self.step,
+            enabled: self.testing.clone(),
+            outcome,
+            category,
+        });
+        if outcome == BisectOutcome::Crashed {
+            // 子集复现时从 2 份重新划分，补集复现时粒度减一
+            let was_subset = self.next <= self.granularity;
+            self.granularity = if was_subset { 2 } else { (self.granularity - 1).max(2) };
+            self.failing = self.testing.clone();
+            self.next = 0;
+        }
+    }
+
+    fn display_names(&self, files: &[String]) -> String {
+        files.iter()
+            .map(|file| self.names.get(file).cloned().unwrap_or_else(|| file.clone()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RestorePoint {
+    version_id: String,
+    mods_dir: PathBuf,
+    original: Vec<String>,
+}
+
+fn save_restore_point(data_dir: &Path, session: &BisectSession) -> Result<(), LauncherError> {
+    let point = RestorePoint {
+        version_id: session.version_id.clone(),
+        mods_dir: session.mods_dir.clone(),
+        original: session.original.clone(),
+    };
+    let content = serde_json::to_string_pretty(&point)
+        .map_err(|e| LauncherError::Internal { message: e.to_string() })?;
+    std::fs::write(data_dir.join(RESTORE_FILE), content)?;
+    Ok(())
+}
+
+fn clear_restore_point(data_dir: &Path) {
+    let _ = std::fs::remove_file(data_dir.join(RESTORE_FILE));
+}
+
+// 恢复开始时的模组状态，全部成功后才删除恢复记录，返回未能恢复的文件
+async fn restore(data_dir: &Path, mods_dir: &Path, original: &[String]) -> Vec<String> {
+    let failed = apply(mods_dir, original, original).await;
+    if failed.is_empty() {
+        clear_restore_point(data_dir);
+    } else {
+        println!("[Bisect] Keeping restore point, {} mods could not be restored", failed.len());
+    }
+    failed
+}
+
+/// 启动器启动和开始新的排查时调用：上次排查未能恢复时把模组恢复为排查开始时的启用状态，
+/// 返回仍未能恢复的文件
+pub async fn restore_interrupted(data_dir: &Path) -> Vec<String> {
+    let Ok(content) = std::fs::read_to_string(data_dir.join(RESTORE_FILE)) else {
+        return Vec::new();
+    };
+    match serde_json::from_str::<RestorePoint>(&content) {
+        Ok(point) => {
+            println!("[Bisect] Restoring mods of interrupted bisect for {}", point.version_id);
+            restore(data_dir, &point.mods_dir, &point.original).await
+        }
+        Err(e) => {
+            println!("[Bisect] Invalid restore point: {}", e);
+            clear_restore_point(data_dir);
+            Vec::new()
+        }
+    }
+}
+
+// 结束和中止消息中的恢复结果
+fn restore_params(session: &BisectSession, failed: &[String]) -> Value {
+    let restore = if failed.is_empty() {
+        crate::i18n::t("bisect.restored", &json!({}))
+    } else {
+        crate::i18n::t("bisect.restoreFailed", &json!({ "files": session.display_names(failed) }))
+    };
+    json!({ "restore": restore, "failed": failed })
+}
+
+fn emit(app_handle: &tauri::AppHandle, session: &BisectSession, key: &str, params: Value) {
+    println!("[Bisect] {}", crate::i18n::t(key, &params));
+    let mut payload = crate::i18n::message(key, params);
+    payload["session"] = json!(session);
+    let _ = app_handle.emit("mod-bisect", payload);
+}
+
+// 通过 toggle_mod 让 original 中的模组只有 enabled 中的处于启用状态，返回未能切换的文件
+// （如 Windows 上 jar 仍被游戏占用时重命名失败）
+async fn apply(mods_dir: &Path, original: &[String], enabled: &[String]) -> Vec<String> {
+    let mut failed = Vec::new();
+    for file in original {
+        let disabled = format!("{}.disabled", file);
+        let current = if mods_dir.join(file).exists() {
+            file.clone()
+        } else if mods_dir.join(&disabled).exists() {
+            disabled
+        } else {
+            println!("[Bisect] Mod file missing: {}", file);
+            continue;
+        };
+        let want = enabled.contains(file);
+        if want == (current == *file) {
+            continue;
+        }
+        if let Err(e) = crate::mods::toggle_mod(mods_dir.to_string_lossy().to_string(), current, want).await {
+            println!("[Bisect] Failed to toggle {}: {}", file, e);
+            failed.push(file.clone());
+        }
+    }
+    failed
+}
+
+/// 启用下一轮的模组组合并启动游戏；没有可测试的组合时结束排查
+pub async fn next_round(app_handle: &tauri::AppHandle) {
+    let state = app_handle.state::<Arc<Mutex<AppState>>>();
+    let mut app_state = state.lock().await;
+    let Some(session) = app_state.bisect_session.as_mut().filter(|s| s.status == BisectStatus::Running) else {
+        return;
+    };
+
+    let Some(config) = session.advance() else {
+        session.status = BisectStatus::Finished;
+        let session = session.clone();
+        let data_dir = app_state.data_dir.clone();
+        drop(app_state);
+        let failed = restore(&data_dir, &session.mods_dir, &session.original).await;
+        let mut params = restore_params(&session, &failed);
+        params["mods"] = json!(session.display_names(&session.failing));
+        emit(app_handle, &session, "bisect.finished", params);
+        return;
+    };
+    session.step += 1;
+    session.testing = config;
+    let session = session.clone();
+    drop(app_state);
+
+    let not_toggled = apply(&session.mods_dir, &session.original, &session.testing).await;
+    if !not_toggled.is_empty() {
+        // 本轮组合没有完全生效，结果不可信
+        if let Some((session, failed)) = abort(&state).await {
+            let mut params = restore_params(&session, &failed);
+            params["message"] = json!(session.display_names(&not_toggled));
+            emit(app_handle, &session, "bisect.launchFailed", params);
+        }
+        return;
+    }
+    emit(app_handle, &session, "bisect.testing", json!({
+        "step": session.step,
+        "enabled": session.testing.len(),
+        "total": session.original.len()
+    }));
+
+    // 上一轮的实例可能还未被进程监控标记为退出，强制作为新实例启动
+    let result = crate::minecraft::launch_game(
+        state.clone(),
+        app_handle.clone(),
+        session.version_id.clone(),
+        session.account_id.clone(),
+        Some(true),
+    ).await;
+    if let Err(e) = result {
+        if let Some((session, failed)) = abort(&state).await {
+            let mut params = restore_params(&session, &failed);
+            params["message"] = json!(e.to_string());
+            emit(app_handle, &session, "bisect.launchFailed", params);
+        }
+    }
+}
+
+/// 进程监控在游戏退出时调用，返回 true 表示该次运行属于排查，不需要弹出崩溃窗口
+pub async fn record_exit(
+    app_handle: &tauri::AppHandle,
+    pid: u32,
+    version_id: &str,
+    exit_code: i32,
+    category: Option<&str>,
+) -> bool {
+    let state = app_handle.state::<Arc<Mutex<AppState>>>();
+    let mut app_state = state.lock().await;
+    let Some(session) = app_state.bisect_session.as_mut().filter(|s| s.version_id == version_id) else {
+        return false;
+    };
+    if let Some(pos) = session.stopped_pids.iter().position(|stopped| *stopped == pid) {
+        session.stopped_pids.remove(pos);
+        return true;
+    }
+    if session.status != BisectStatus::Running {
+        return false;
+    }
+
+    let outcome = match category {
+        _ if exit_code == 0 => BisectOutcome::Passed,
+        Some(category) if UNRESOLVED_CATEGORIES.contains(&category) => BisectOutcome::Unresolved,
+        _ => BisectOutcome::Crashed,
+    };
+    session.record(outcome, category.map(str::to_string));
+    let key = match outcome {
+        BisectOutcome::Crashed => "bisect.crashed",
+        BisectOutcome::Passed => "bisect.passed",
+        BisectOutcome::Unresolved => "bisect.unresolved",
+    };
+    emit(app_handle, session, key, json!({ "step": session.step }));
+    drop(app_state);
+
+    let _ = app_handle.emit(NEXT_ROUND_EVENT, json!({ "versionId": version_id }));
+    true
+}
+
+// 排查中的游戏最多等待多久退出
+const STOP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+// 中止进行中的排查并恢复模组状态，返回会话和未能恢复的文件。
+// 排查启动的游戏仍在运行时先结束它，否则 Windows 上无法重命名被占用的 jar
+async fn abort(state: &Arc<Mutex<AppState>>) -> Option<(BisectSession, Vec<String>)> {
+    let mut app_state = state.lock().await;
+    let version_id = app_state.bisect_session.as_ref()
+        .filter(|s| s.status == BisectStatus::Running)?
+        .version_id.clone();
+    let pids: Vec<u32> = app_state.running_instances.read().await.iter()
+        .filter(|(_, instance)| !instance.exited && instance.version_id == version_id)
+        .map(|(pid, _)| *pid)
+        .collect();
+    let session = app_state.bisect_session.as_mut()?;
+    session.status = BisectStatus::Aborted;
+    session.stopped_pids.extend(&pids);
+    let session = session.clone();
+    let data_dir = app_state.data_dir.clone();
+    drop(app_state);
+
+    for pid in &pids {
+        println!("[Bisect] Stopping game process {}", pid);
+        crate::minecraft::terminate_process(*pid);
+    }
+    let deadline = std::time::Instant::now() + STOP_TIMEOUT;
+    while pids.iter().any(|pid| crate::minecraft::is_process_alive(*pid)) && std::time::Instant::now() < deadline {
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    }
+
+    let failed = restore(&data_dir, &session.mods_dir, &session.original).await;
+    Some((session, failed))
+}
+
+/// 开始排查指定版本的模组，当前启用的模组会被逐轮禁用一部分
+#[tauri::command]
+pub async fn start_mod_bisect(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    app_handle: tauri::AppHandle,
+    version_id: String,
+    account_id: Option<String>,
+) -> Result<Value, LauncherError> {
+    let mut app_state = state.lock().await;
+    if let Some(session) = app_state.bisect_session.as_ref().filter(|s| s.status == BisectStatus::Running) {
+        return Err(LauncherError::BisectInProgress { version_id: session.version_id.clone() });
+    }
+
+    let settings = &app_state.launcher_settings;
+    let mc_dir = settings.minecraft_dir.clone()
+        .unwrap_or_else(|| crate::launcher::get_default_mc_dir().to_string_lossy().to_string());
+    // 与启动时使用同一个游戏目录
+    let mods_dir = crate::mods::mods_dir_for_version(settings, &mc_dir, &version_id);
+
+    // 上次排查未能恢复的模组先恢复，否则新的恢复记录会覆盖它
+    let failed = restore_interrupted(&app_state.data_dir).await;
+    if !failed.is_empty() {
+        return Err(LauncherError::BisectRestoreFailed { files: failed.join(", ") });
+    }
+
+    let session = BisectSession::new(version_id, account_id, mods_dir)?;
+    save_restore_point(&app_state.data_dir, &session)?;
+    println!("[Bisect] Starting for {} with {} mods", session.version_id, session.original.len());
+    app_state.bisect_session = Some(session.clone());
+    drop(app_state);
+
+    emit(&app_handle, &session, "bisect.started", json!({ "total": session.original.len() }));
+    next_round(&app_handle).await;
+
+    let app_state = state.lock().await;
+    Ok(json!({
+        "success": true,
+        "session": app_state.bisect_session
+    }))
+}
+
+/// 中止排查并恢复开始时的模组启用状态
+#[tauri::command]
+pub async fn abort_mod_bisect(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    app_handle: tauri::AppHandle,
+) -> Result<Value, LauncherError> {
+    let aborted = abort(&state).await;
+    if let Some((session, failed)) = &aborted {
+        emit(&app_handle, session, "bisect.aborted", restore_params(session, failed));
+    }
+    Ok(json!({
+        "success": true,
+        "aborted": aborted.is_some(),
+        "failed": aborted.map(|(_, failed)| failed).unwrap_or_default()
+    }))
+}
+
+#[tauri::command]
+pub async fn get_mod_bisect_status(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+) -> Result<Value, LauncherError> {
+    let app_state = state.lock().await;
+    Ok(json!({
+        "success": true,
+        "session": app_state.bisect_session
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mods::ModIndexEntry;
+
+    fn entry(file: &str, id: &str, dependencies: &[&str], provides: &[&str]) -> ModIndexEntry {
+        ModIndexEntry {
+            file: file.to_string(),
+            id: Some(id.to_string()),
+            name: id.to_string(),
+            enabled: true,
+            packages: Vec::new(),
+            mixin_configs: Vec::new(),
+            dependencies: dependencies.iter().map(|s| s.to_string()).collect(),
+            provides: provides.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn session(index: Vec<ModIndexEntry>) -> BisectSession {
+        BisectSession::from_index("1.20.1".to_string(), None, PathBuf::from("mods"), index).unwrap()
+    }
+
+    fn plain(files: &[&str]) -> Vec<ModIndexEntry> {
+        files.iter().map(|file| entry(file, file.trim_end_matches(".jar"), &[], &[])).collect()
+    }
+
+    fn files(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    // 模拟进程监控：按 crashes 判定每轮结果，直到 advance 不再给出组合
+    fn run(session: &mut BisectSession, crashes: impl Fn(&[String]) -> bool) {
+        while let Some(config) = session.advance() {
+            assert!(session.step < 64, "bisect did not converge");
+            session.step += 1;
+            session.testing = config;
+            let outcome = if crashes(&session.testing) { BisectOutcome::Crashed } else { BisectOutcome::Passed };
+            session.record(outcome, None);
+        }
+    }
+
+    #[test]
+    fn closure_follows_nested_and_provided_ids() {
+        let session = session(vec![
+            entry("fabric-api.jar", "fabric-api", &[], &["fabric-api-base", "fabric-lifecycle-events-v1"]),
+            entry("cloth-config.jar", "cloth-config", &[], &["cloth-config2"]),
+            entry("sodium.jar", "sodium", &["fabric-lifecycle-events-v1"], &[]),
+            entry("menu.jar", "menu", &["cloth-config2", "fabric-api-base", "minecraft"], &[]),
+        ]);
+
+        assert_eq!(session.closure(&files(&["sodium.jar"])), files(&["fabric-api.jar", "sodium.jar"]));
+        assert_eq!(
+            session.closure(&files(&["menu.jar"])),
+            files(&["cloth-config.jar", "fabric-api.jar", "menu.jar"])
+        );
+    }
+
+    #[test]
+    fn own_id_wins_over_provided_alias() {
+        let session = session(vec![
+            entry("bundle.jar", "bundle", &[], &["sodium"]),
+            entry("sodium.jar", "sodium", &[], &[]),
+            entry("addon.jar", "addon", &["sodium"], &[]),
+        ]);
+
+        assert_eq!(session.closure(&files(&["addon.jar"])), files(&["addon.jar", "sodium.jar"]));
+    }
+
+    #[test]
+    fn closure_handles_dependency_cycles() {
+        let session = session(vec![
+            entry("a.jar", "a", &["b"], &[]),
+            entry("b.jar", "b", &["a"], &[]),
+            entry("c.jar", "c", &[], &[]),
+        ]);
+
+        assert_eq!(session.closure(&files(&["a.jar"])), files(&["a.jar", "b.jar"]));
+    }
+
+    #[test]
+    fn finds_single_crashing_mod() {
+        let mut session = session(plain(&["a.jar", "b.jar", "c.jar", "d.jar", "e.jar", "f.jar", "g.jar", "h.jar"]));
+        run(&mut session, |enabled| enabled.contains(&"e.jar".to_string()));
+
+        assert_eq!(session.failing, files(&["e.jar"]));
+    }
+
+    #[test]
+    fn finds_interacting_pair() {
+        let mut session = session(plain(&["a.jar", "b.jar", "c.jar", "d.jar", "e.jar", "f.jar", "g.jar", "h.jar"]));
+        run(&mut session, |enabled| {
+            enabled.contains(&"c.jar".to_string()) && enabled.contains(&"f.jar".to_string())
+        });
+
+        assert_eq!(session.failing, files(&["c.jar", "f.jar"]));
+    }
+
+    #[test]
+    fn keeps_required_dependencies_with_culprit() {
+        let mut index = plain(&["a.jar", "b.jar", "c.jar", "d.jar"]);
+        index.push(entry("lib.jar", "lib", &[], &[]));
+        index[0].dependencies = vec!["lib".to_string()];
+        let mut session = session(index);
+        run(&mut session, |enabled| enabled.contains(&"a.jar".to_string()));
+
+        assert_eq!(session.failing, files(&["a.jar", "lib.jar"]));
+        // 每轮的组合都带上了依赖
+        assert!(session.history.iter()
+            .filter(|run| run.enabled.contains(&"a.jar".to_string()))
+            .all(|run| run.enabled.contains(&"lib.jar".to_string())));
+    }
+
+    #[test]
+    fn never_repeats_a_configuration() {
+        let mut session = session(plain(&["a.jar", "b.jar", "c.jar", "d.jar", "e.jar", "f.jar"]));
+        run(&mut session, |enabled| {
+            enabled.contains(&"b.jar".to_string()) && enabled.contains(&"e.jar".to_string())
+        });
+
+        let mut tested: Vec<&Vec<String>> = session.history.iter().map(|run| &run.enabled).collect();
+        let total = tested.len();
+        tested.sort();
+        tested.dedup();
+        assert_eq!(tested.len(), total);
+        assert!(session.history.iter().all(|run| run.enabled.len() < session.original.len()));
+    }
+
+    #[test]
+    fn unresolved_round_keeps_failing_set() {
+        let mut session = session(plain(&["a.jar", "b.jar", "c.jar", "d.jar"]));
+        let config = session.advance().unwrap();
+        assert_eq!(config, files(&["a.jar", "b.jar"]));
+
+        session.step = 1;
+        session.testing = config;
+        session.record(BisectOutcome::Unresolved, Some("modResolution".to_string()));
+
+        assert_eq!(session.failing, session.original);
+        assert_eq!(session.history.len(), 1);
+        assert_eq!(session.history[0].outcome, BisectOutcome::Unresolved);
+        assert_eq!(session.advance(), Some(files(&["c.jar", "d.jar"])));
+    }
+
+    #[test]
+    fn crashing_subset_restarts_at_two_chunks() {
+        let mut session = session(plain(&["a.jar", "b.jar", "c.jar", "d.jar", "e.jar", "f.jar", "g.jar", "h.jar"]));
+        session.testing = session.advance().unwrap();
+        session.record(BisectOutcome::Crashed, None);
+
+        assert_eq!(session.failing, files(&["a.jar", "b.jar", "c.jar", "d.jar"]));
+        assert_eq!(session.granularity, 2);
+        assert_eq!(session.advance(), Some(files(&["a.jar", "b.jar"])));
+    }
+
+    #[test]
+    fn single_mod_needs_no_rounds() {
+        let mut session = session(plain(&["a.jar"]));
+        assert_eq!(session.advance(), None);
+    }
+
+    #[test]
+    fn rejects_index_without_enabled_mods() {
+        let mut index = plain(&["a.jar"]);
+        index[0].enabled = false;
+        let result = BisectSession::from_index("1.20.1".to_string(), None, PathBuf::from("mods"), index);
+        assert!(matches!(result, Err(LauncherError::NoModsToBisect { .. })));
+    }
+}
@@ -0,0 +1,755 @@
+use crate::mods::ModIndexEntry;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+
+// 基于特征库的崩溃分析
+// 内置特征库随程序打包，用户可在数据目录的 crash_signatures.json 中追加特征或按 id 覆盖内置特征。
+// 所有命中的特征都会返回并按严重程度、优先级排序，命名捕获组作为消息参数，
+// 其中 mod / mixin / class / library / file 组同时作为可疑对象返回。
+// 提供 mods 目录时，再结合可疑对象和堆栈把崩溃归因到具体的模组文件
+
+const BUNDLED_SIGNATURES: &str = include_str!("crash_signatures.json");
+const USER_SIGNATURES_FILE: &str = "crash_signatures.json";
+const CULPRIT_KINDS: [&str; 5] = ["mod", "mixin", "class", "library", "file"];
+const MAX_EVIDENCE: usize = 3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Severity {
+    Info,
+    Warning,
+    #[default]
+    Error,
+    Critical,
+}
+
+impl Severity {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+// 用户特征可以直接提供文本：字符串，或 { "zh-CN": ..., "en-US": ... }
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum Text {
+    Plain(String),
+    Localized(HashMap<String, String>),
+}
+
+impl Text {
+    fn render(&self, params: &Value) -> String {
+        let template = match self {
+            Text::Plain(text) => Some(text),
+            Text::Localized(texts) => texts
+                .get(&crate::i18n::language())
+                .or_else(|| texts.values().next()),
+        };
+        template
+            .map(|template| crate::i18n::interpolate(template, params))
+            .unwrap_or_default()
+    }
+}
+
+// details 规则按顺序匹配，没有 pattern 的规则作为默认值
+#[derive(Debug, Clone, Deserialize)]
+struct DetailRule {
+    #[serde(default)]
+    pattern: Option<String>,
+    #[serde(default)]
+    key: Option<String>,
+    #[serde(default)]
+    text: Option<Text>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Signature {
+    id: String,
+    #[serde(default)]
+    severity: Severity,
+    #[serde(default)]
+    priority: i32,
+    patterns: Vec<String>,
+    #[serde(default)]
+    details: Vec<DetailRule>,
+    // 仅在没有其他特征命中时使用，例如通用异常
+    #[serde(default)]
+    fallback: bool,
+    // 未提供时使用语言文件中的 crash.<id>.reason / crash.<id>.suggestions
+    #[serde(default)]
+    reason: Option<Text>,
+    #[serde(default)]
+    suggestions: Option<Vec<Text>>,
+}
+
+#[derive(Deserialize)]
+struct SignatureFile {
+    signatures: Vec<Signature>,
+}
+
+fn parse_signatures(content: &str, source: &str) -> Vec<Signature> {
+    match serde_json::from_str::<SignatureFile>(content) {
+        Ok(file) => file.signatures,
+        Err(e) => {
+            println!("[CrashAnalyzer] Invalid signature file {}: {}", source, e);
+            Vec::new()
+        }
+    }
+}
+
+static BUNDLED: LazyLock<Vec<Signature>> = LazyLock::new(|| parse_signatures(BUNDLED_SIGNATURES, "bundled"));
+
+// 加载内置特征库并合并用户特征
+fn load_signatures(data_dir: Option<&Path>) -> Vec<Signature> {
+    let mut signatures = BUNDLED.clone();
+
+    let Some(path) = data_dir.map(|dir| dir.join(USER_SIGNATURES_FILE)).filter(|p| p.exists()) else {
+        return signatures;
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(content) => {
+            for signature in parse_signatures(&content, &path.to_string_lossy()) {
+                match signatures.iter_mut().find(|s| s.id == signature.id) {
+                    Some(existing) => *existing = signature,
+                    None => signatures.push(signature),
+                }
+            }
+        }
+        Err(e) => println!("[CrashAnalyzer] Failed to read {:?}: {}", path, e),
+    }
+    signatures
+}
+
+// 编译结果按表达式缓存，用户特征文件中的表达式也只编译一次；无效的表达式同样缓存，只记录一次日志
+static COMPILED: LazyLock<Mutex<HashMap<String, Option<Regex>>>> = LazyLock::new(Default::default);
+
+fn compile(pattern: &str, signature_id: &str) -> Option<Regex> {
+    let mut compiled = COMPILED.lock().unwrap_or_else(|e| e.into_inner());
+    compiled
+        .entry(pattern.to_string())
+        .or_insert_with(|| {
+            Regex::new(pattern)
+                .map_err(|e| println!("[CrashAnalyzer] Invalid pattern in {}: {}", signature_id, e))
+                .ok()
+        })
+        .clone()
+}
+
+// 匹配位置所在的整行
+fn line_at(text: &str, offset: usize) -> String {
+    let start = text[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let end = text[offset..].find('\n').map(|i| offset + i).unwrap_or(text.len());
+    text[start..end].trim().to_string()
+}
+
+fn add_culprit(culprits: &mut Vec<Value>, kind: &str, name: &str, signature_id: &str) {
+    let exists = culprits.iter().any(|c| c["kind"] == kind && c["name"] == name);
+    if !exists && !name.is_empty() {
+        culprits.push(json!({ "kind": kind, "name": name, "signature": signature_id }));
+    }
+}
+
+struct Finding {
+    severity: Severity,
+    priority: i32,
+    match_count: usize,
+    value: Value,
+}
+
+fn evaluate(signature: &Signature, text: &str, exit_code: i32) -> Option<Finding> {
+    let mut params = Map::new();
+    params.insert("exitCode".to_string(), json!(exit_code));
+    let mut culprits = Vec::new();
+    let mut evidence: Vec<String> = Vec::new();
+    let mut match_count = 0;
+
+    for pattern in &signature.patterns {
+        let Some(re) = compile(pattern, &signature.id) else { continue };
+        for caps in re.captures_iter(text) {
+            match_count += 1;
+            if let Some(whole) = caps.get(0) {
+                let line = line_at(text, whole.start());
+                if evidence.len() < MAX_EVIDENCE && !evidence.contains(&line) {
+                    evidence.push(line);
+                }
+            }
+            for name in re.capture_names().flatten() {
+                let Some(value) = caps.name(name) else { continue };
+                let value = value.as_str().trim();
+                params.entry(name).or_insert_with(|| json!(value));
+                if CULPRIT_KINDS.contains(&name) {
+                    add_culprit(&mut culprits, name, value, &signature.id);
+                }
+            }
+        }
+    }
+    if match_count == 0 {
+        return None;
+    }
+
+    // 选择 details 规则，规则自身的捕获组取最后一次匹配（通常是最深层的 Caused by）
+    let mut detail = None;
+    for rule in &signature.details {
+        let Some(pattern) = &rule.pattern else {
+            detail = Some(rule);
+            break;
+        };
+        let Some(re) = compile(pattern, &signature.id) else { continue };
+        if let Some(caps) = re.captures_iter(text).last() {
+            for name in re.capture_names().flatten() {
+                if let Some(value) = caps.name(name) {
+                    params.insert(name.to_string(), json!(value.as_str().trim()));
+                }
+            }
+            detail = Some(rule);
+            break;
+        }
+    }
+
+    let params = Value::Object(params);
+    let reason_key = signature.reason.is_none().then(|| format!("crash.{}.reason", signature.id));
+    let suggestions_key = signature.suggestions.is_none().then(|| format!("crash.{}.suggestions", signature.id));
+    let details_key = match detail {
+        Some(rule) if rule.text.is_some() => None,
+        Some(rule) => rule.key.clone(),
+        None => Some("crash.exitCode".to_string()),
+    };
+
+    let mut value = json!({
+        "id": signature.id,
+        "severity": signature.severity.as_str(),
+        "matchCount": match_count,
+        "reasonKey": reason_key,
+        "detailsKey": details_key,
+        "suggestionsKey": suggestions_key,
+        "params": params,
+        "culprits": culprits,
+        "evidence": evidence
+    });
+    let (reason, details, suggestions) = render_finding(&value, signature, detail);
+    value["reason"] = json!(reason);
+    value["details"] = json!(details);
+    value["suggestions"] = json!(suggestions);
+
+    Some(Finding {
+        severity: signature.severity,
+        priority: signature.priority,
+        match_count,
+        value,
+    })
+}
+
+fn render_finding(
+    finding: &Value,
+    signature: &Signature,
+    detail: Option<&DetailRule>,
+) -> (String, String, Vec<String>) {
+    let params = &finding["params"];
+    let reason = match &signature.reason {
+        Some(text) => text.render(params),
+        None => crate::i18n::t(finding["reasonKey"].as_str().unwrap_or_default(), params),
+    };
+    let details = match detail.and_then(|rule| rule.text.as_ref()) {
+        Some(text) => text.render(params),
+        None => crate::i18n::t(finding["detailsKey"].as_str().unwrap_or_default(), params),
+    };
+    let suggestions = match &signature.suggestions {
+        Some(texts) => texts.iter().map(|text| text.render(params)).collect(),
+        None => crate::i18n::t_list(finding["suggestionsKey"].as_str().unwrap_or_default(), params),
+    };
+    (reason, details, suggestions)
+}
+
+/// 按当前语言重新渲染分析结果中的一条发现（语言可能在崩溃后被切换）
+/// 用户特征直接提供的文本没有键，保持原样
+pub fn localize_finding(finding: &Value) -> (String, String, Vec<String>) {
+    let params = &finding["params"];
+    let render = |key_field: &str, text_field: &str| {
+        finding[key_field].as_str()
+            .map(|key| crate::i18n::t(key, params))
+            .or_else(|| finding[text_field].as_str().map(|s| s.to_string()))
+            .unwrap_or_default()
+    };
+    let suggestions = match finding["suggestionsKey"].as_str() {
+        Some(key) => crate::i18n::t_list(key, params),
+        None => finding["suggestions"]
+            .as_array()
+            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default(),
+    };
+    (render("reasonKey", "reason"), render("detailsKey", "details"), suggestions)
+}
+
+// 可疑模组的证据权重，多条证据按 1 - Π(1 - w) 合并为置信度
+const WEIGHT_SUSPECTED_LIST: f64 = 0.9;
+const WEIGHT_CULPRIT: f64 = 0.8;
+const WEIGHT_TOP_FRAME: f64 = 0.6;
+const WEIGHT_FRAME: f64 = 0.1;
+const WEIGHT_FRAME_JAR: f64 = 0.25;
+const WEIGHT_MIXIN_HANDLER: f64 = 0.3;
+const WEIGHT_MIXIN_TRANSFORM: f64 = 0.15;
+const MAX_SUSPECTS: usize = 5;
+const MAX_REASONS: usize = 4;
+
+#[derive(Default)]
+struct Evidence {
+    weights: Vec<f64>,
+    reasons: Vec<Value>,
+}
+
+impl Evidence {
+    fn add(&mut self, weight: f64, key: &str, params: Value) {
+        self.weights.push(weight);
+        let reason = crate::i18n::message(key, params);
+        if self.reasons.len() < MAX_REASONS && !self.reasons.contains(&reason) {
+            self.reasons.push(reason);
+        }
+    }
+
+    fn confidence(&self) -> f64 {
+        1.0 - self.weights.iter().fold(1.0, |acc, w| acc * (1.0 - w))
+    }
+}
+
+fn find_by_id(index: &[ModIndexEntry], id: &str) -> Vec<usize> {
+    (0..index.len())
+        .filter(|&i| index[i].id.as_deref().is_some_and(|m| m.eq_ignore_ascii_case(id)))
+        .collect()
+}
+
+fn find_by_file(index: &[ModIndexEntry], file: &str) -> Vec<usize> {
+    (0..index.len())
+        .filter(|&i| index[i].file == file || index[i].file.trim_end_matches(".disabled") == file)
+        .collect()
+}
+
+fn find_by_mixin(index: &[ModIndexEntry], config: &str) -> Vec<usize> {
+    (0..index.len())
+        .filter(|&i| index[i].mixin_configs.iter().any(|c| c == config))
+        .collect()
+}
+
+// 类所属的模组：取最长匹配的包前缀，多个模组同时匹配时都返回
+fn find_by_class(index: &[ModIndexEntry], class: &str) -> Vec<usize> {
+    let mut best = 0;
+    let mut found = Vec::new();
+    for (i, entry) in index.iter().enumerate() {
+        let longest = entry.packages.iter()
+            .filter(|p| class.starts_with(p.as_str()) && class[p.len()..].starts_with('.'))
+            .map(|p| p.len())
+            .max()
+            .unwrap_or(0);
+        if longest > best {
+            best = longest;
+            found = vec![i];
+        } else if longest > 0 && longest == best {
+            found.push(i);
+        }
+    }
+    found
+}
+
+// 崩溃报告中的 "Suspected Mods: Name (id), Version: x"
+static SUSPECTED_LIST: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"Suspected Mods?: (.+)").unwrap());
+static SUSPECTED_ID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\(([\w\-]+)\)").unwrap());
+
+// 堆栈帧及其中的 jar 文件名、mixin 处理方法和 mixin 配置
+static FRAME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^at (?:[^\s(]+/)?([\w$]+(?:\.[\w$]+)+)\.[\w$<>]+\(").unwrap()
+});
+static FRAME_JAR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"~\[([^\]\s%!:/]+\.jar)").unwrap());
+static MIXIN_HANDLER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\$[a-z]{3}\d{3}\$([a-z0-9_]+)\$").unwrap());
+static MIXIN_TRANSFORM: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"pl:mixin:APP:([\w.\-]+\.json)").unwrap());
+
+/// 根据分析出的可疑对象和堆栈将崩溃归因到 mods 目录中的具体 jar
+fn suspect_mods(text: &str, culprits: &[Value], mods_dir: &Path, index: &[ModIndexEntry]) -> Vec<Value> {
+    let mut evidence: HashMap<usize, Evidence> = HashMap::new();
+    let mut add = |matches: Vec<usize>, weight: f64, key: &str, params: Value| {
+        // 多个模组同时匹配时平分权重
+        let weight = weight / matches.len().max(1) as f64;
+        for i in matches {
+            evidence.entry(i).or_default().add(weight, key, params.clone());
+        }
+    };
+
+    // 特征库提取的可疑对象
+    for culprit in culprits {
+        let name = culprit["name"].as_str().unwrap_or_default();
+        let (matches, key) = match culprit["kind"].as_str() {
+            Some("mod") => (find_by_id(index, name), "crash.suspects.reasons.modId"),
+            Some("mixin") => (find_by_mixin(index, name), "crash.suspects.reasons.mixinConfig"),
+            Some("file") => (find_by_file(index, name), "crash.suspects.reasons.file"),
+            _ => continue,
+        };
+        add(matches, WEIGHT_CULPRIT, key, json!({ "name": name }));
+    }
+
+    for caps in SUSPECTED_LIST.captures_iter(text) {
+        for id in SUSPECTED_ID.captures_iter(&caps[1]) {
+            add(find_by_id(index, &id[1]), WEIGHT_SUSPECTED_LIST, "crash.suspects.reasons.suspectedList", json!({ "name": &id[1] }));
+        }
+    }
+
+    // 堆栈帧：每段异常的第一个可归因帧权重最高
+    let mut block_attributed = false;
+    for line in text.lines().map(str::trim) {
+        let Some(caps) = FRAME.captures(line) else {
+            block_attributed = false;
+            continue;
+        };
+        let class = &caps[1];
+        let matches = find_by_class(index, class);
+        if !matches.is_empty() {
+            let weight = if block_attributed { WEIGHT_FRAME } else { WEIGHT_TOP_FRAME };
+            block_attributed = true;
+            add(matches, weight, "crash.suspects.reasons.stackFrame", json!({ "name": class }));
+        }
+        if let Some(jar) = FRAME_JAR.captures(line) {
+            add(find_by_file(index, &jar[1]), WEIGHT_FRAME_JAR, "crash.suspects.reasons.file", json!({ "name": &jar[1] }));
+        }
+        if let Some(handler) = MIXIN_HANDLER.captures(line) {
+            add(find_by_id(index, &handler[1]), WEIGHT_MIXIN_HANDLER, "crash.suspects.reasons.mixinHandler", json!({ "name": class }));
+        }
+        for config in MIXIN_TRANSFORM.captures_iter(line) {
+            add(find_by_mixin(index, &config[1]), WEIGHT_MIXIN_TRANSFORM, "crash.suspects.reasons.mixinConfig", json!({ "name": &config[1] }));
+        }
+    }
+
+    let mut suspects: Vec<(f64, Value)> = evidence
+        .into_iter()
+        .map(|(i, evidence)| {
+            let entry = &index[i];
+            let confidence = evidence.confidence();
+            let level = if confidence >= 0.75 { "high" } else if confidence >= 0.4 { "medium" } else { "low" };
+            // 已启用的模组给出禁用操作，对应 toggle_mod 命令
+            let action = entry.enabled.then(|| json!({
+                "command": "toggle_mod",
+                "args": {
+                    "modsDir": mods_dir.to_string_lossy(),
+                    "file": entry.file,
+                    "enabled": false
+                }
+            }));
+            (confidence, json!({
+                "file": entry.file,
+                "id": entry.id,
+                "name": entry.name,
+                "enabled": entry.enabled,
+                "confidence": (confidence * 100.0).round() / 100.0,
+                "level": level,
+                "reasons": evidence.reasons,
+                "action": action
+            }))
+        })
+        .collect();
+    suspects.sort_by(|a, b| b.0.total_cmp(&a.0));
+    suspects.into_iter().take(MAX_SUSPECTS).map(|(_, suspect)| suspect).collect()
+}
+
+fn unknown_finding(exit_code: i32) -> Value {
+    let params = json!({ "exitCode": exit_code });
+    json!({
+        "id": "unknown",
+        "severity": Severity::Info.as_str(),
+        "matchCount": 0,
+        "reason": crate::i18n::t("crash.unknown.reason", &params),
+        "details": crate::i18n::t("crash.exitCode", &params),
+        "suggestions": crate::i18n::t_list("crash.unknown.suggestions", &params),
+        "reasonKey": "crash.unknown.reason",
+        "detailsKey": "crash.exitCode",
+        "suggestionsKey": "crash.unknown.suggestions",
+        "params": params,
+        "culprits": [],
+        "evidence": []
+    })
+}
+
+/// 分析游戏日志，返回全部命中的特征、汇总的可疑对象和可疑模组
+/// 顶层的 reason / details / suggestions 等字段取自排名第一的发现
+pub fn analyze(logs: &[String], exit_code: i32, data_dir: Option<&Path>, mods_dir: Option<&Path>) -> Value {
+    // 进程监控记录的日志带有 [STDERR] / [STDOUT] 前缀，去掉后行首锚点才能匹配
+    let text = logs
+        .iter()
+        .map(|line| {
+            line.strip_prefix("[STDERR] ")
+                .or_else(|| line.strip_prefix("[STDOUT] "))
+                .unwrap_or(line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let signatures = load_signatures(data_dir);
+    let (fallbacks, primary): (Vec<_>, Vec<_>) = signatures.iter().partition(|s| s.fallback);
+    let mut findings: Vec<Finding> = primary
+        .iter()
+        .filter_map(|signature| evaluate(signature, &text, exit_code))
+        .collect();
+    if findings.is_empty() {
+        findings = fallbacks
+            .iter()
+            .filter_map(|signature| evaluate(signature, &text, exit_code))
+            .collect();
+    }
+    findings.sort_by(|a, b| {
+        b.severity.cmp(&a.severity)
+            .then(b.priority.cmp(&a.priority))
+            .then(b.match_count.cmp(&a.match_count))
+    });
+
+    let mut findings: Vec<Value> = findings.into_iter().map(|finding| finding.value).collect();
+    if findings.is_empty() {
+        findings.push(unknown_finding(exit_code));
+    }
+    println!(
+        "[CrashAnalyzer] Findings: {:?}",
+        findings.iter().filter_map(|f| f["id"].as_str()).collect::<Vec<_>>()
+    );
+
+    let mut culprits = Vec::new();
+    for culprit in findings.iter().flat_map(|f| f["culprits"].as_array().cloned().unwrap_or_default()) {
+        let kind = culprit["kind"].as_str().unwrap_or_default();
+        let name = culprit["name"].as_str().unwrap_or_default();
+        let signature = culprit["signature"].as_str().unwrap_or_default();
+        add_culprit(&mut culprits, kind, name, signature);
+    }
+
+    let suspected_mods = match mods_dir {
+        Some(mods_dir) => suspect_mods(&text, &culprits, mods_dir, &crate::mods::build_mod_index(mods_dir)),
+        None => Vec::new(),
+    };
+
+    let top = &findings[0];
+    json!({
+        "category": top["id"],
+        "severity": top["severity"],
+        "reason": top["reason"],
+        "details": top["details"],
+        "suggestions": top["suggestions"],
+        "reasonKey": top["reasonKey"],
+        "detailsKey": top["detailsKey"],
+        "detailsParams": top["params"],
+        "suggestionsKey": top["suggestionsKey"],
+        "culprits": culprits,
+        "suspectedMods": suspected_mods,
+        "findings": findings,
+        "exitCode": exit_code
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(log: &str) -> Value {
+        let logs: Vec<String> = log.lines().map(str::to_string).collect();
+        analyze(&logs, 1, None, None)
+    }
+
+    fn culprit_names(result: &Value, kind: &str) -> Vec<String> {
+        result["culprits"].as_array().unwrap().iter()
+            .filter(|c| c["kind"] == kind)
+            .map(|c| c["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn fabric_mod_resolution() {
+        let result = run("\
+[main/ERROR]: Incompatible mods found!
+net.fabricmc.loader.impl.FormattedException: Some of your mods are incompatible with the game or each other!
+A potential solution has been determined:
+\t - Install sodium, any version.
+Unmet dependency listing:
+\t - Mod 'Sodium Extra' (sodium-extra) 0.5.1 requires any version of sodium, which is missing!
+\tat net.fabricmc.loader.impl.FormattedException.ofLocalized(FormattedException.java:51)");
+        assert_eq!(result["category"], "modResolution");
+        assert_eq!(result["detailsKey"], "crash.modResolution.details.mod");
+        assert_eq!(culprit_names(&result, "mod"), ["sodium-extra"]);
+    }
+
+    #[test]
+    fn forge_missing_dependency() {
+        let result = run("\
+[main/ERROR] [net.minecraftforge.fml.loading.ModSorter/LOADING]: Missing or unsupported mandatory dependencies:
+\tMod ID: 'geckolib', Requested by: 'alexsmobs', Expected range: '[4.2,)', Actual version: '[MISSING]'");
+        assert_eq!(result["category"], "forgeDependency");
+        assert_eq!(result["detailsParams"]["dependency"], "geckolib");
+        assert_eq!(culprit_names(&result, "mod"), ["alexsmobs"]);
+    }
+
+    #[test]
+    fn out_of_memory() {
+        let result = run("\
+[STDERR] Exception in thread \"Render thread\" java.lang.OutOfMemoryError: Java heap space
+[STDERR] \tat java.base/java.util.Arrays.copyOf(Arrays.java:3537)");
+        assert_eq!(result["category"], "outOfMemory");
+        assert_eq!(result["severity"], "critical");
+        // 通用异常只在没有其他特征命中时使用
+        assert!(result["findings"].as_array().unwrap().iter().all(|f| f["id"] != "exception"));
+    }
+
+    #[test]
+    fn hs_err_native_crash() {
+        let result = run("\
+#
+# A fatal error has been detected by the Java Runtime Environment:
+#
+#  EXCEPTION_ACCESS_VIOLATION (0xc0000005) at pc=0x00007ffb1a2b3c4d, pid=1234, tid=5678
+#
+# JRE version: OpenJDK Runtime Environment Temurin-17.0.8+7 (17.0.8+7) (build 17.0.8+7)
+# Problematic frame:
+# C  [atio6axx.dll+0x1a2b3c]
+#");
+        assert_eq!(result["category"], "jvmCrash");
+        assert_eq!(result["detailsKey"], "crash.jvmCrash.details.frame");
+        assert_eq!(culprit_names(&result, "library"), ["atio6axx.dll"]);
+    }
+
+    #[test]
+    fn unsupported_class_version() {
+        let result = run("\
+Error: LinkageError occurred while loading main class net.minecraft.client.main.Main
+\tjava.lang.UnsupportedClassVersionError: net/minecraft/client/main/Main has been compiled by a more recent version of the Java Runtime (class file version 65.0), this version of the Java Runtime only recognizes class file versions up to 61.0");
+        assert_eq!(result["category"], "javaVersion");
+        assert_eq!(result["detailsKey"], "crash.javaVersion.details.java21");
+    }
+
+    #[test]
+    fn permission_needs_an_exception_line() {
+        let chat = run("[Render thread/INFO]: [CHAT] Access denied: permission denied for /op");
+        assert_ne!(chat["category"], "permission");
+
+        let result = run("\
+java.nio.file.AccessDeniedException: C:\\Users\\<user>\\AppData\\Roaming\\.minecraft\\options.txt
+\tat java.base/sun.nio.fs.WindowsException.translateToIOException(WindowsException.java:89)");
+        assert_eq!(result["category"], "permission");
+        let result = run("Caused by: java.io.IOException: Permission denied");
+        assert_eq!(result["category"], "permission");
+    }
+
+    #[test]
+    fn mod_conflict_needs_an_exception_line() {
+        let chat = run("[Server thread/INFO]: <Steve> is this a mod conflict? duplicate mods found lol");
+        assert_ne!(chat["category"], "modConflict");
+
+        let result = run("net.minecraftforge.fml.common.DuplicateModsFoundException: Found a duplicate mod jei");
+        assert_eq!(result["category"], "modConflict");
+    }
+
+    #[test]
+    fn unknown_when_nothing_matches() {
+        let result = run("[main/INFO]: Stopping!");
+        assert_eq!(result["category"], "unknown");
+        assert_eq!(result["exitCode"], 1);
+    }
+
+    fn indexed(file: &str, id: &str, packages: &[&str], mixin_configs: &[&str]) -> ModIndexEntry {
+        ModIndexEntry {
+            file: file.to_string(),
+            id: Some(id.to_string()),
+            name: id.to_string(),
+            enabled: true,
+            packages: packages.iter().map(|s| s.to_string()).collect(),
+            mixin_configs: mixin_configs.iter().map(|s| s.to_string()).collect(),
+            dependencies: Vec::new(),
+            provides: Vec::new(),
+        }
+    }
+
+    fn suspects(text: &str, culprits: &[Value], index: &[ModIndexEntry]) -> Vec<Value> {
+        suspect_mods(text, culprits, Path::new("mods"), index)
+    }
+
+    #[test]
+    fn class_goes_to_longest_package_prefix() {
+        let index = [
+            indexed("core.jar", "core", &["com.example"], &[]),
+            indexed("addon.jar", "addon", &["com.example.addon"], &[]),
+        ];
+        assert_eq!(find_by_class(&index, "com.example.addon.Thing"), [1]);
+        assert_eq!(find_by_class(&index, "com.example.Thing"), [0]);
+        // 前缀必须在包名分隔处结束
+        assert!(find_by_class(&index, "com.examplex.Thing").is_empty());
+    }
+
+    #[test]
+    fn shared_package_splits_the_weight() {
+        let index = [
+            indexed("a.jar", "a", &["com.shared.lib"], &[]),
+            indexed("b.jar", "b", &["com.shared.lib"], &[]),
+        ];
+        assert_eq!(find_by_class(&index, "com.shared.lib.Util"), [0, 1]);
+
+        let result = suspects("at com.shared.lib.Util.run(Util.java:1)", &[], &index);
+        assert_eq!(result.len(), 2);
+        for suspect in &result {
+            assert_eq!(suspect["confidence"], 0.3);
+            assert_eq!(suspect["level"], "low");
+        }
+    }
+
+    #[test]
+    fn mixin_config_culprit_is_high_confidence() {
+        let index = [indexed("sodium.jar", "sodium", &[], &["sodium.mixins.json"])];
+        let culprits = [json!({ "kind": "mixin", "name": "sodium.mixins.json", "signature": "mixin" })];
+        let result = suspects("", &culprits, &index);
+        assert_eq!(result[0]["file"], "sodium.jar");
+        assert_eq!(result[0]["confidence"], 0.8);
+        assert_eq!(result[0]["level"], "high");
+        assert_eq!(result[0]["action"]["args"]["file"], "sodium.jar");
+    }
+
+    #[test]
+    fn frame_jar_and_mixin_transform_are_matched() {
+        let index = [
+            indexed("bar-1.0.jar", "bar", &[], &[]),
+            indexed("foo.jar", "foo", &[], &["foo.mixins.json"]),
+        ];
+        let text = "at net.minecraft.client.Minecraft.run(Minecraft.java:10) ~[bar-1.0.jar%23100!/:?] {re:classloading,pl:mixin:APP:foo.mixins.json:MinecraftMixin,pl:mixin:A}";
+        let result = suspects(text, &[], &index);
+        let confidence = |file: &str| result.iter().find(|s| s["file"] == file).unwrap()["confidence"].clone();
+        assert_eq!(confidence("bar-1.0.jar"), 0.25);
+        assert_eq!(confidence("foo.jar"), 0.15);
+    }
+
+    #[test]
+    fn evidence_combines_into_levels() {
+        let index = [
+            indexed("example.jar", "examplemod", &["com.example"], &[]),
+            indexed("other.jar", "other", &["org.other"], &[]),
+        ];
+        let text = "Suspected Mods: Example Mod (examplemod), Version: 1.0
+java.lang.NullPointerException
+	at com.example.Thing.tick(Thing.java:5)
+	at org.other.Caller.call(Caller.java:9)
+Caused by: java.lang.IllegalStateException
+	at org.other.Caller.call(Caller.java:12)";
+        let result = suspects(text, &[], &index);
+        // 0.9 + 0.6 -> 0.96
+        assert_eq!(result[0]["file"], "example.jar");
+        assert_eq!(result[0]["confidence"], 0.96);
+        assert_eq!(result[0]["level"], "high");
+        // 第一段中排在后面的帧 0.1，第二段的第一帧 0.6 -> 0.64
+        assert_eq!(result[1]["file"], "other.jar");
+        assert_eq!(result[1]["confidence"], 0.64);
+        assert_eq!(result[1]["level"], "medium");
+    }
+
+    #[test]
+    fn disabled_mods_get_no_action() {
+        let mut entry = indexed("old.jar.disabled", "old", &["com.old"], &[]);
+        entry.enabled = false;
+        let result = suspects("at com.old.Main.run(Main.java:1)", &[], &[entry]);
+        assert_eq!(result[0]["action"], Value::Null);
+    }
+
+}
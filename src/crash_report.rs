@@ -0,0 +1,796 @@
+use crate::error::LauncherError;
+use regex::Regex;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+// 崩溃报告解析
+// 支持游戏生成的 crash-reports/*.txt 和 JVM 致命错误日志 hs_err_pid*.log，
+// 解析结果既通过 get_crash_report 命令提供给前端，也作为崩溃分析的输入
+
+const MINECRAFT_HEADER: &str = "---- Minecraft Crash Report ----";
+const JVM_HEADER: &str = "A fatal error has been detected by the Java Runtime Environment";
+const JVM_OOM_HEADER: &str = "There is insufficient memory for the Java Runtime Environment";
+const WALKTHROUGH: &str = "A detailed walkthrough of the error";
+const SYSTEM_DETAILS: &str = "System Details";
+// System Details 中的模组列表：Forge / NeoForge 为 Mod List（1.12 为 States 后的表格），Fabric / Quilt 各自一项
+const MOD_LIST_KEYS: [&str; 4] = ["Mod List", "Fabric Mods", "Quilt Mods", "States"];
+const MAX_JVM_FRAMES: usize = 50;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum CrashReport {
+    Minecraft(MinecraftCrashReport),
+    Jvm(JvmCrashReport),
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinecraftCrashReport {
+    pub comment: Option<String>,
+    pub time: Option<String>,
+    pub description: Option<String>,
+    pub exception: Option<CrashException>,
+    pub sections: Vec<CrashSection>,
+    pub minecraft_version: Option<String>,
+    pub java_version: Option<String>,
+    pub operating_system: Option<String>,
+    pub suspected_mods: Option<String>,
+    pub mods: Vec<CrashReportMod>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashException {
+    pub class: String,
+    pub message: Option<String>,
+    // 最深层的 Caused by
+    pub root_cause: Option<String>,
+    pub frames: Vec<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashSection {
+    pub name: String,
+    pub details: Vec<CrashDetail>,
+    pub stacktrace: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashDetail {
+    pub key: String,
+    pub value: String,
+    // 缩进的续行，例如模组列表
+    pub lines: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReportMod {
+    pub id: String,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub file: Option<String>,
+    pub state: Option<String>,
+    // Fabric 列表中内嵌（jar-in-jar）模组所属的上级模组
+    pub parent: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JvmCrashReport {
+    pub error: Option<String>,
+    pub error_detail: Option<String>,
+    pub signal: Option<String>,
+    pub pid: Option<u32>,
+    pub jre_version: Option<String>,
+    pub java_vm: Option<String>,
+    pub problematic_frame: Option<String>,
+    pub library: Option<String>,
+    pub current_thread: Option<String>,
+    pub host: Option<String>,
+    // 仅保留 -X 开头的内存 / GC 参数，命令行的其余部分包含账户令牌
+    pub jvm_options: Vec<String>,
+    pub frames: Vec<String>,
+    #[serde(skip)]
+    header: Vec<String>,
+}
+
+impl CrashReport {
+    /// 按内容识别报告类型并解析
+    pub fn parse(content: &str) -> Option<CrashReport> {
+        if content.contains(MINECRAFT_HEADER) {
+            Some(CrashReport::Minecraft(parse_minecraft(content)))
+        } else if content.contains(JVM_HEADER) || content.contains(JVM_OOM_HEADER) {
+            Some(CrashReport::Jvm(parse_jvm(content)))
+        } else {
+            None
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<CrashReport, LauncherError> {
+        // hs_err 的系统信息部分可能是本地编码，按有损 UTF-8 读取
+        let content = String::from_utf8_lossy(&std::fs::read(path)?).to_string();
+        CrashReport::parse(&content).ok_or_else(|| LauncherError::Internal {
+            message: crate::i18n::t("crashReport.errors.unrecognized", &serde_json::json!({ "path": path.display().to_string() })),
+        })
+    }
+
+    /// 交给崩溃分析的文本：异常、各段落的详情和堆栈，不包含系统信息和模组列表
+    pub fn analysis_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        match self {
+            CrashReport::Minecraft(report) => {
+                if let Some(description) = &report.description {
+                    lines.push(format!("Description: {}", description));
+                }
+                if let Some(exception) = &report.exception {
+                    lines.extend(exception.text.lines().map(str::to_string));
+                }
+                for section in report.sections.iter().filter(|s| s.name != SYSTEM_DETAILS) {
+                    lines.push(format!("-- {} --", section.name));
+                    for detail in &section.details {
+                        lines.push(format!("{}: {}", detail.key, detail.value));
+                        lines.extend(detail.lines.iter().cloned());
+                    }
+                    lines.extend(section.stacktrace.iter().map(|frame| format!("\t{}", frame)));
+                }
+            }
+            CrashReport::Jvm(report) => {
+                lines.extend(report.header.iter().cloned());
+                if let Some(thread) = &report.current_thread {
+                    lines.push(format!("Current thread: {}", thread));
+                }
+                lines.extend(report.frames.iter().cloned());
+            }
+        }
+        lines
+    }
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(['\t', ' ']).len()
+}
+
+fn parse_minecraft(content: &str) -> MinecraftCrashReport {
+    let mut report = MinecraftCrashReport::default();
+    let lines: Vec<&str> = content.lines().collect();
+    let mut i = lines.iter().position(|l| l.contains(MINECRAFT_HEADER)).map(|p| p + 1).unwrap_or(0);
+
+    // 头部：注释、时间、描述，然后是异常堆栈（到空行或详细说明为止）
+    while i < lines.len() {
+        let line = lines[i].trim();
+        if let Some(comment) = line.strip_prefix("//") {
+            report.comment = non_empty(comment);
+        } else if let Some(time) = line.strip_prefix("Time:") {
+            report.time = non_empty(time);
+        } else if let Some(description) = line.strip_prefix("Description:") {
+            report.description = non_empty(description);
+            i += 1;
+            while i < lines.len() && lines[i].trim().is_empty() {
+                i += 1;
+            }
+            let start = i;
+            while i < lines.len() && !lines[i].trim().is_empty() && !lines[i].starts_with(WALKTHROUGH) {
+                i += 1;
+            }
+            report.exception = parse_exception(&lines[start..i]);
+            break;
+        } else if line.starts_with(WALKTHROUGH) || line.starts_with("-- ") {
+            break;
+        }
+        i += 1;
+    }
+
+    // 段落："-- 名称 --" 后跟 Details: / Stacktrace: 或直接的 "键: 值"
+    let section_header = Regex::new(r"^-- (.+) --$").unwrap();
+    let mut in_stacktrace = false;
+    // 段落中第一个键的缩进，Head 的键不缩进而 System Details 的键缩进一级
+    let mut key_indent: Option<usize> = None;
+    for line in &lines[i..] {
+        let trimmed = line.trim();
+        if let Some(caps) = section_header.captures(trimmed) {
+            report.sections.push(CrashSection { name: caps[1].to_string(), ..Default::default() });
+            in_stacktrace = false;
+            key_indent = None;
+            continue;
+        }
+        let Some(section) = report.sections.last_mut() else { continue };
+        if trimmed.is_empty() {
+            continue;
+        }
+        if trimmed == "Details:" {
+            in_stacktrace = false;
+            continue;
+        }
+        if trimmed == "Stacktrace:" {
+            in_stacktrace = true;
+            continue;
+        }
+        if in_stacktrace && indent_of(line) > 0 {
+            section.stacktrace.push(trimmed.to_string());
+            continue;
+        }
+        in_stacktrace = false;
+
+        // 与段落首个键同级的 "键: 值" 开始新的详情，更深的缩进和表格行属于上一项
+        let indent = indent_of(line);
+        let is_key = indent <= key_indent.unwrap_or(1) && !trimmed.starts_with('|') && trimmed.contains(':');
+        if is_key && key_indent.is_none() {
+            key_indent = Some(indent);
+        }
+        match (is_key, section.details.last_mut()) {
+            (false, Some(detail)) => detail.lines.push(line.trim_end().to_string()),
+            _ => {
+                let (key, value) = trimmed.split_once(':').unwrap_or((trimmed, ""));
+                section.details.push(CrashDetail {
+                    key: key.trim().to_string(),
+                    value: value.trim().to_string(),
+                    lines: Vec::new(),
+                });
+            }
+        }
+    }
+
+    let sections = &report.sections;
+    let detail = |section: &str, keys: &[&str]| {
+        sections.iter()
+            .filter(|s| s.name == section)
+            .flat_map(|s| s.details.iter())
+            .find(|d| keys.contains(&d.key.as_str()))
+            // 值为空时取第一行续行，例如 1.20 Forge 的 Suspected Mod
+            .and_then(|d| non_empty(&d.value).or_else(|| d.lines.first().and_then(|l| non_empty(l))))
+    };
+    let minecraft_version = detail(SYSTEM_DETAILS, &["Minecraft Version"]);
+    let java_version = detail(SYSTEM_DETAILS, &["Java Version"]);
+    let operating_system = detail(SYSTEM_DETAILS, &["Operating System"]);
+    let suspected_mods = detail("Head", &["Suspected Mods", "Suspected Mod"])
+        .filter(|mods| mods != "NONE" && mods != "Unknown");
+    let mods = sections.iter()
+        .filter(|s| s.name == SYSTEM_DETAILS)
+        .flat_map(|s| s.details.iter())
+        .filter(|d| MOD_LIST_KEYS.contains(&d.key.as_str()))
+        .flat_map(|d| parse_mod_list(&d.key, &d.lines))
+        .collect();
+
+    MinecraftCrashReport {
+        minecraft_version,
+        java_version,
+        operating_system,
+        suspected_mods,
+        mods,
+        ..report
+    }
+}
+
+fn parse_exception(lines: &[&str]) -> Option<CrashException> {
+    let first = lines.first()?.trim();
+    let (class, message) = match first.split_once(": ") {
+        Some((class, message)) => (class.to_string(), non_empty(message)),
+        None => (first.to_string(), None),
+    };
+    let root_cause = lines.iter()
+        .rev()
+        .find_map(|l| l.trim().strip_prefix("Caused by:"))
+        .and_then(non_empty);
+    let frames = lines.iter()
+        .map(|l| l.trim())
+        .filter(|l| l.starts_with("at "))
+        .map(str::to_string)
+        .collect();
+    Some(CrashException {
+        class,
+        message,
+        root_cause,
+        frames,
+        text: lines.join("\n"),
+    })
+}
+
+// 1.13 - 1.16 Forge: "minecraft.jar Minecraft {minecraft@1.15.2 DONE}"
+static LEGACY_FORGE_MOD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(\S+)\s+(.+?)\s+\{([^@\s]+)@(\S+)\s+(\w+)\}").unwrap()
+});
+
+// Fabric: "fabric-api: Fabric API 0.92.2+1.20.1"
+static FABRIC_MOD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([\w\-.]+):\s+(.+?)(?:\s+(\S*\d\S*))?$").unwrap()
+});
+
+fn parse_mod_list(key: &str, lines: &[String]) -> Vec<CrashReportMod> {
+    let mut mods = Vec::new();
+    let mut header: Option<Vec<String>> = None;
+    let base_indent = lines.iter().filter(|l| !l.trim().is_empty()).map(|l| indent_of(l)).min().unwrap_or(0);
+    let mut parent: Option<String> = None;
+
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        if trimmed.contains('|') {
+            let cells: Vec<String> = trimmed.trim_matches('|').split('|').map(|c| c.trim().to_string()).collect();
+            // Markdown 表格的分隔行
+            if cells.iter().all(|c| c.chars().all(|ch| matches!(ch, '-' | ':' | ' '))) {
+                continue;
+            }
+            // 1.12 Forge 和 Quilt 的表格带表头，按列名取值
+            if header.is_none() && cells.iter().any(|c| c.eq_ignore_ascii_case("id")) {
+                header = Some(cells.iter().map(|c| c.to_lowercase()).collect());
+                continue;
+            }
+            let column = |names: &[&str]| {
+                header.as_ref()?
+                    .iter()
+                    .position(|h| names.contains(&h.as_str()))
+                    .and_then(|i| cells.get(i))
+                    .and_then(|c| non_empty(c))
+            };
+            let entry = match &header {
+                Some(_) => CrashReportMod {
+                    id: column(&["id"]).unwrap_or_default(),
+                    name: column(&["mod", "name"]),
+                    version: column(&["version"]),
+                    file: column(&["source", "file(s)", "file"]),
+                    state: column(&["state"]),
+                    parent: None,
+                },
+                // 1.17+ Forge / NeoForge：文件 | 名称 | ID | 版本 | 状态 | 签名
+                None => CrashReportMod {
+                    id: cells.get(2).cloned().unwrap_or_default(),
+                    name: cells.get(1).and_then(|c| non_empty(c)),
+                    version: cells.get(3).and_then(|c| non_empty(c)),
+                    file: cells.first().and_then(|c| non_empty(c)),
+                    state: cells.get(4).and_then(|c| non_empty(c)),
+                    parent: None,
+                },
+            };
+            if !entry.id.is_empty() {
+                mods.push(entry);
+            }
+        } else if let Some(caps) = LEGACY_FORGE_MOD.captures(trimmed) {
+            mods.push(CrashReportMod {
+                id: caps[3].to_string(),
+                name: Some(caps[2].to_string()),
+                version: Some(caps[4].to_string()),
+                file: Some(caps[1].to_string()),
+                state: Some(caps[5].to_string()),
+                parent: None,
+            });
+        } else if key == "Fabric Mods" {
+            let Some(caps) = FABRIC_MOD.captures(trimmed) else { continue };
+            let id = caps[1].to_string();
+            let nested = indent_of(line) > base_indent;
+            mods.push(CrashReportMod {
+                id: id.clone(),
+                name: Some(caps[2].to_string()),
+                version: caps.get(3).map(|v| v.as_str().to_string()),
+                parent: if nested { parent.clone() } else { None },
+                ..Default::default()
+            });
+            if !nested {
+                parent = Some(id);
+            }
+        }
+    }
+    mods
+}
+
+fn parse_jvm(content: &str) -> JvmCrashReport {
+    let mut report = JvmCrashReport::default();
+    let lines: Vec<&str> = content.lines().collect();
+
+    // 以 # 开头的头部
+    report.header = lines.iter()
+        .skip_while(|l| !l.starts_with('#'))
+        .take_while(|l| l.starts_with('#'))
+        .map(|l| l.to_string())
+        .collect();
+    let header: Vec<&str> = report.header.iter()
+        .map(|l| l.trim_start_matches('#').trim())
+        .filter(|l| !l.is_empty())
+        .collect();
+
+    let mut summary = header.iter().skip_while(|l| l.starts_with(JVM_HEADER));
+    report.error = summary.next().map(|l| l.to_string());
+    report.error_detail = summary.next()
+        .filter(|l| !l.starts_with("JRE version:") && !l.starts_with("Possible reasons"))
+        .map(|l| l.to_string());
+    if let Some(error) = &report.error {
+        report.signal = Regex::new(r"^([A-Z_]+) \(0x[0-9a-fA-F]+\)").unwrap()
+            .captures(error)
+            .map(|caps| caps[1].to_string());
+        report.pid = Regex::new(r"pid=(\d+)").unwrap()
+            .captures(error)
+            .and_then(|caps| caps[1].parse().ok());
+    }
+    for (i, line) in header.iter().enumerate() {
+        if let Some(version) = line.strip_prefix("JRE version:") {
+            report.jre_version = non_empty(version);
+        } else if let Some(vm) = line.strip_prefix("Java VM:") {
+            report.java_vm = non_empty(vm);
+        } else if line.starts_with("Problematic frame:") {
+            report.problematic_frame = header.get(i + 1).map(|l| l.to_string());
+        }
+    }
+    report.library = report.problematic_frame.as_deref().and_then(|frame| {
+        let start = frame.find('[')? + 1;
+        let end = frame[start..].find(['+', ']']).map(|e| start + e)?;
+        non_empty(&frame[start..end])
+    });
+
+    // 正文：摘要、线程和原生堆栈
+    let mut in_frames = false;
+    for line in &lines {
+        if let Some(command) = line.strip_prefix("Command Line:") {
+            report.jvm_options = command.split_whitespace()
+                .filter(|arg| arg.starts_with("-X"))
+                .map(str::to_string)
+                .collect();
+        } else if let Some(host) = line.strip_prefix("Host:") {
+            report.host = non_empty(host);
+        } else if let Some(thread) = line.strip_prefix("Current thread") {
+            report.current_thread = thread.split_once(':').and_then(|(_, t)| non_empty(t));
+        } else if line.starts_with("Native frames:") {
+            in_frames = true;
+        } else if in_frames {
+            if line.trim().is_empty() || report.frames.len() >= MAX_JVM_FRAMES {
+                in_frames = false;
+            } else {
+                report.frames.push(line.trim_end().to_string());
+            }
+        }
+    }
+    report
+}
+
+/// 查找最近生成的崩溃报告，max_age 之前的旧报告不视为本次崩溃
+pub fn find_recent_crash_report(game_dir: &Path, max_age: Duration) -> Option<PathBuf> {
+    let entries = std::fs::read_dir(game_dir.join("crash-reports")).ok()?;
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().map(|ext| ext == "txt").unwrap_or(false))
+        .filter_map(|e| {
+            let modified = e.metadata().and_then(|m| m.modified()).ok()?;
+            let age = modified.elapsed().unwrap_or_default();
+            (age < max_age).then(|| (modified, e.path()))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+/// 查找 JVM 致命错误日志，JVM 将其写在游戏进程的工作目录下
+pub fn find_jvm_error_log(game_dir: &Path, pid: u32) -> Option<PathBuf> {
+    let path = game_dir.join(format!("hs_err_pid{}.log", pid));
+    path.exists().then_some(path)
+}
+
+// 崩溃报告在 5 分钟内生成才认为属于本次崩溃
+const RECENT_REPORT_AGE: Duration = Duration::from_secs(300);
+
+/// 读取并解析本次崩溃的崩溃报告和 JVM 错误日志，解析失败的文件会被跳过
+pub fn collect_recent(game_dir: &Path, pid: u32) -> Vec<(PathBuf, CrashReport)> {
+    [find_recent_crash_report(game_dir, RECENT_REPORT_AGE), find_jvm_error_log(game_dir, pid)]
+        .into_iter()
+        .flatten()
+        .filter_map(|path| match CrashReport::read(&path) {
+            Ok(report) => {
+                println!("[CrashReport] Parsed {:?}", path);
+                Some((path, report))
+            }
+            Err(e) => {
+                println!("[CrashReport] Failed to parse {:?}: {}", path, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// 解析指定的崩溃报告或 JVM 错误日志
+#[tauri::command]
+pub async fn get_crash_report(path: String) -> Result<serde_json::Value, LauncherError> {
+    println!("[CrashReport] Parsing {}", path);
+    let report = CrashReport::read(Path::new(&path))?;
+    Ok(serde_json::json!({
+        "success": true,
+        "path": path,
+        "report": report
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minecraft(content: &str) -> MinecraftCrashReport {
+        match CrashReport::parse(content) {
+            Some(CrashReport::Minecraft(report)) => report,
+            other => panic!("expected a Minecraft crash report, got {:?}", other),
+        }
+    }
+
+    fn find<'a>(mods: &'a [CrashReportMod], id: &str) -> &'a CrashReportMod {
+        mods.iter().find(|m| m.id == id).unwrap_or_else(|| panic!("mod {} not parsed", id))
+    }
+
+    #[test]
+    fn parses_header_sections_and_system_details() {
+        let report = minecraft("\
+---- Minecraft Crash Report ----
+// Shall we play a game?
+
+Time: 2024-03-01 12:00:00
+Description: Ticking entity
+
+java.lang.NullPointerException: Cannot invoke \"Object.hashCode()\"
+\tat net.minecraft.world.entity.Entity.tick(Entity.java:100)
+\tat net.minecraft.world.level.Level.guardEntityTick(Level.java:200)
+Caused by: java.lang.IllegalStateException: broken
+\tat example.Mod.run(Mod.java:1)
+
+
+A detailed walkthrough of the error, its code path and all known details is as follows:
+---------------------------------------------------------------------------------------
+
+-- Head --
+Thread: Server thread
+Suspected Mod: 
+\tExample Mod (examplemod), Version: 1.0
+Stacktrace:
+\tat net.minecraft.world.entity.Entity.tick(Entity.java:100)
+
+-- System Details --
+Details:
+\tMinecraft Version: 1.20.1
+\tOperating System: Windows 10 (amd64) version 10.0
+\tJava Version: 17.0.8, Eclipse Adoptium
+");
+        assert_eq!(report.comment.as_deref(), Some("Shall we play a game?"));
+        assert_eq!(report.time.as_deref(), Some("2024-03-01 12:00:00"));
+        assert_eq!(report.description.as_deref(), Some("Ticking entity"));
+
+        let exception = report.exception.unwrap();
+        assert_eq!(exception.class, "java.lang.NullPointerException");
+        assert_eq!(exception.message.as_deref(), Some("Cannot invoke \"Object.hashCode()\""));
+        assert_eq!(exception.root_cause.as_deref(), Some("java.lang.IllegalStateException: broken"));
+        assert_eq!(exception.frames.len(), 3);
+
+        let names: Vec<_> = report.sections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Head", "System Details"]);
+        let head = &report.sections[0];
+        assert_eq!(head.details[1].key, "Suspected Mod");
+        assert_eq!(head.details[1].lines, ["\tExample Mod (examplemod), Version: 1.0"]);
+        assert_eq!(head.stacktrace, ["at net.minecraft.world.entity.Entity.tick(Entity.java:100)"]);
+        assert_eq!(report.suspected_mods.as_deref(), Some("Example Mod (examplemod), Version: 1.0"));
+
+        assert_eq!(report.minecraft_version.as_deref(), Some("1.20.1"));
+        assert_eq!(report.java_version.as_deref(), Some("17.0.8, Eclipse Adoptium"));
+        assert_eq!(report.operating_system.as_deref(), Some("Windows 10 (amd64) version 10.0"));
+        assert!(report.mods.is_empty());
+    }
+
+    #[test]
+    fn parses_forge_1_12_states_table() {
+        let report = minecraft("\
+---- Minecraft Crash Report ----
+Description: Initializing game
+
+java.lang.RuntimeException: boom
+
+-- System Details --
+Details:
+\tMinecraft Version: 1.12.2
+\tFML: MCP 9.42 Powered by Forge 14.23.5.2859 3 mods loaded, 3 mods active
+\tStates: 'U' = Unloaded 'L' = Loaded 'C' = Constructed 'E' = Errored
+
+\t| State  | ID        | Version      | Source                        | Signature |
+\t|:------ |:--------- |:------------ |:----------------------------- |:--------- |
+\t| LCH    | minecraft | 1.12.2       | minecraft.jar                 | None      |
+\t| LCH    | forge     | 14.23.5.2859 | forge-1.12.2-14.23.5.2859.jar | e3c3d50c  |
+\t| LCE    | jei       | 4.16.1.301   | jei_1.12.2-4.16.1.301.jar     | None      |
+\tLoaded coremods (and transformers): 
+");
+        assert_eq!(report.minecraft_version.as_deref(), Some("1.12.2"));
+        assert_eq!(report.mods.len(), 3);
+        let jei = find(&report.mods, "jei");
+        assert_eq!(jei.version.as_deref(), Some("4.16.1.301"));
+        assert_eq!(jei.file.as_deref(), Some("jei_1.12.2-4.16.1.301.jar"));
+        assert_eq!(jei.state.as_deref(), Some("LCE"));
+        assert_eq!(jei.name, None);
+        assert_eq!(find(&report.mods, "forge").version.as_deref(), Some("14.23.5.2859"));
+    }
+
+    #[test]
+    fn parses_forge_1_16_legacy_mod_list() {
+        let report = minecraft("\
+---- Minecraft Crash Report ----
+Description: Mod loading error has occurred
+
+java.lang.Exception: Mod Loading has failed
+
+-- System Details --
+Details:
+\tMinecraft Version: 1.16.5
+\tMod List: 
+\t\tforge-1.16.5-36.2.39.jar Forge {forge@36.2.39 DONE}
+\t\tjei-1.16.5-7.7.1.152.jar Just Enough Items {jei@7.7.1.152 ERROR}
+\tCrash Report UUID: 7c6b0b1e
+");
+        assert_eq!(report.mods.len(), 2);
+        let jei = find(&report.mods, "jei");
+        assert_eq!(jei.name.as_deref(), Some("Just Enough Items"));
+        assert_eq!(jei.version.as_deref(), Some("7.7.1.152"));
+        assert_eq!(jei.file.as_deref(), Some("jei-1.16.5-7.7.1.152.jar"));
+        assert_eq!(jei.state.as_deref(), Some("ERROR"));
+    }
+
+    #[test]
+    fn parses_forge_and_neoforge_1_20_mod_table() {
+        let report = minecraft("\
+---- Minecraft Crash Report ----
+Description: Mod loading error has occurred
+
+java.lang.Exception: Mod Loading has failed
+
+-- System Details --
+Details:
+\tMinecraft Version: 1.20.1
+\tMod List: 
+\t\tserver-1.20.1-20230612.114412-srg.jar             |Minecraft                     |minecraft                     |1.20.1              |DONE      |Manifest: a1:d4:5e:04
+\t\tneoforge-20.4.237-universal.jar                   |NeoForge                      |neoforge                      |20.4.237            |DONE      |Manifest: NOSIGNATURE
+\t\tcreate-1.20.1-0.5.1.f.jar                         |Create                        |create                        |0.5.1.f             |ERROR     |Manifest: NOSIGNATURE
+\tCrash Report UUID: 7c6b0b1e
+");
+        assert_eq!(report.mods.len(), 3);
+        let create = find(&report.mods, "create");
+        assert_eq!(create.name.as_deref(), Some("Create"));
+        assert_eq!(create.version.as_deref(), Some("0.5.1.f"));
+        assert_eq!(create.file.as_deref(), Some("create-1.20.1-0.5.1.f.jar"));
+        assert_eq!(create.state.as_deref(), Some("ERROR"));
+        assert_eq!(find(&report.mods, "neoforge").version.as_deref(), Some("20.4.237"));
+    }
+
+    #[test]
+    fn parses_fabric_mod_list_with_nested_mods() {
+        let report = minecraft("\
+---- Minecraft Crash Report ----
+Description: Initializing game
+
+java.lang.RuntimeException: Could not execute entrypoint stage 'client'
+
+-- System Details --
+Details:
+\tMinecraft Version: 1.20.1
+\tFabric Mods: 
+\t\tfabric-api: Fabric API 0.92.2+1.20.1
+\t\t\tfabric-api-base: Fabric API Base 0.4.31+1802ada577
+\t\t\tfabric-rendering-v1: Fabric Rendering (v1) 3.0.8+b0d9a7cd77
+\t\tfabricloader: Fabric Loader 0.15.11
+\t\tjava: OpenJDK 64-Bit Server VM 17
+\t\tsodium: Sodium 0.5.8+mc1.20.1
+\tLaunched Version: fabric-loader-0.15.11-1.20.1
+");
+        let ids: Vec<_> = report.mods.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["fabric-api", "fabric-api-base", "fabric-rendering-v1", "fabricloader", "java", "sodium"]);
+
+        let api = find(&report.mods, "fabric-api");
+        assert_eq!(api.name.as_deref(), Some("Fabric API"));
+        assert_eq!(api.version.as_deref(), Some("0.92.2+1.20.1"));
+        assert_eq!(api.parent, None);
+        let rendering = find(&report.mods, "fabric-rendering-v1");
+        assert_eq!(rendering.name.as_deref(), Some("Fabric Rendering (v1)"));
+        assert_eq!(rendering.parent.as_deref(), Some("fabric-api"));
+        assert_eq!(find(&report.mods, "fabric-api-base").parent.as_deref(), Some("fabric-api"));
+
+        let java = find(&report.mods, "java");
+        assert_eq!(java.name.as_deref(), Some("OpenJDK 64-Bit Server VM"));
+        assert_eq!(java.version.as_deref(), Some("17"));
+        assert_eq!(find(&report.mods, "sodium").parent, None);
+    }
+
+    const HS_ERR: &str = "\
+#
+# A fatal error has been detected by the Java Runtime Environment:
+#
+#  EXCEPTION_ACCESS_VIOLATION (0xc0000005) at pc=0x00007ffb1c2d3e4f, pid=12345, tid=6789
+#
+# JRE version: OpenJDK Runtime Environment Temurin-17.0.8+7 (17.0.8+7) (build 17.0.8+7)
+# Java VM: OpenJDK 64-Bit Server VM Temurin-17.0.8+7 (17.0.8+7, mixed mode, tiered, g1 gc, windows-amd64)
+# Problematic frame:
+# C  [atio6axx.dll+0x1a3e4f]
+#
+# No core dump will be written. Minidumps are not enabled by default on client versions of Windows
+#
+# The crash happened outside the Java Virtual Machine in native code.
+# See problematic frame for where to report the bug.
+#
+
+---------------  S U M M A R Y ------------
+
+Command Line: -Xmx4096m -Xms1024m -XX:+UseG1GC -Djava.library.path=C:\\mc\\natives net.minecraft.client.main.Main --username Steve --accessToken eyJhbGciOiJIUzI1NiJ9.secret --uuid 069a79f444e94726a5befca90e38aaf5
+
+Host: AMD Ryzen 7 5800X 8-Core Processor, 16 cores, 31G,  Windows 10 , 64 bit Build 19041
+Time: Fri Mar  1 12:00:00 2024 elapsed time: 42.000000 seconds (0d 0h 0m 42s)
+
+---------------  T H R E A D  ---------------
+
+Current thread (0x000001d8c8a5b0e0):  JavaThread \"Render thread\" [_thread_in_native, id=6789]
+
+Native frames: (J=compiled Java code, j=interpreted, Vv=VM code, C=native code)
+C  [atio6axx.dll+0x1a3e4f]
+C  [lwjgl_opengl.dll+0xe6b3]
+j  org.lwjgl.opengl.GL11C.glDrawElements(IJJ)V+0
+
+Java frames: (J=compiled Java code, j=interpreted, Vv=VM code)
+j  org.lwjgl.opengl.GL11C.nglDrawElements(IIIJ)V+0
+";
+
+    #[test]
+    fn parses_jvm_error_log() {
+        let Some(CrashReport::Jvm(report)) = CrashReport::parse(HS_ERR) else {
+            panic!("expected a JVM crash report");
+        };
+        assert_eq!(report.signal.as_deref(), Some("EXCEPTION_ACCESS_VIOLATION"));
+        assert_eq!(report.pid, Some(12345));
+        assert_eq!(report.error_detail, None);
+        assert_eq!(report.jre_version.as_deref(), Some("OpenJDK Runtime Environment Temurin-17.0.8+7 (17.0.8+7) (build 17.0.8+7)"));
+        assert_eq!(report.problematic_frame.as_deref(), Some("C  [atio6axx.dll+0x1a3e4f]"));
+        assert_eq!(report.library.as_deref(), Some("atio6axx.dll"));
+        assert_eq!(report.current_thread.as_deref(), Some("JavaThread \"Render thread\" [_thread_in_native, id=6789]"));
+        assert_eq!(report.host.as_deref(), Some("AMD Ryzen 7 5800X 8-Core Processor, 16 cores, 31G,  Windows 10 , 64 bit Build 19041"));
+        assert_eq!(report.frames.len(), 3);
+        assert_eq!(report.frames[1], "C  [lwjgl_opengl.dll+0xe6b3]");
+    }
+
+    #[test]
+    fn jvm_options_keep_only_x_flags() {
+        let Some(CrashReport::Jvm(report)) = CrashReport::parse(HS_ERR) else {
+            panic!("expected a JVM crash report");
+        };
+        assert_eq!(report.jvm_options, ["-Xmx4096m", "-Xms1024m", "-XX:+UseG1GC"]);
+
+        // 令牌和用户名不能出现在序列化结果或分析文本中
+        let serialized = serde_json::to_string(&CrashReport::Jvm(report.clone())).unwrap();
+        let analysis = CrashReport::Jvm(report).analysis_lines().join("\n");
+        for secret in ["eyJhbGciOiJIUzI1NiJ9", "Steve", "069a79f444e94726a5befca90e38aaf5"] {
+            assert!(!serialized.contains(secret), "{} leaked into {}", secret, serialized);
+            assert!(!analysis.contains(secret), "{} leaked into {}", secret, analysis);
+        }
+    }
+
+    #[test]
+    fn parses_linux_signal_with_detail() {
+        let Some(CrashReport::Jvm(report)) = CrashReport::parse("\
+#
+# A fatal error has been detected by the Java Runtime Environment:
+#
+#  SIGSEGV (0xb) at pc=0x00007f3a2c1b2a10, pid=4242, tid=4300
+#
+# JRE version: OpenJDK Runtime Environment (21.0.2+13) (build 21.0.2+13)
+# Problematic frame:
+# C  [libglfw.so+0x2a10]  _glfwPlatformPollEvents+0x20
+#
+") else {
+            panic!("expected a JVM crash report");
+        };
+        assert_eq!(report.signal.as_deref(), Some("SIGSEGV"));
+        assert_eq!(report.pid, Some(4242));
+        assert_eq!(report.library.as_deref(), Some("libglfw.so"));
+        assert!(report.jvm_options.is_empty());
+    }
+
+    #[test]
+    fn unrecognized_content_is_not_a_report() {
+        assert!(CrashReport::parse("[12:00:00] [main/INFO]: Loading Minecraft").is_none());
+    }
+}
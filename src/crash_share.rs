@@ -0,0 +1,251 @@
+use crate::error::LauncherError;
+use crate::launcher::CrashShareSettings;
+use crate::redaction::Redactor;
+use crate::state::AppState;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+// 崩溃报告分享
+// 把崩溃摘要、崩溃报告文件和日志合并为一份文本，经 redaction 脱敏后上传到粘贴服务并返回链接。
+// 先以 preview 调用查看将要上传的完整内容，确认分享时上传的正是这份预览
+
+// latest.log 只上传末尾部分，mclo.gs 单份日志限制为 25000 行
+const MAX_LOG_LINES: usize = 2000;
+
+enum PasteBackend {
+    // mclo.gs 兼容 API：POST {api}/1/log，表单字段 content，返回 { success, url }
+    Mclogs { api_url: String },
+    // 自建端点：POST 纯文本，返回链接文本或 { url }
+    Raw { url: String },
+}
+
+impl PasteBackend {
+    fn from_settings(settings: &CrashShareSettings) -> Result<Self, LauncherError> {
+        let api_url = settings.api_url.trim().trim_end_matches('/').to_string();
+        if url::Url::parse(&api_url).is_err() {
+            return Err(LauncherError::Internal {
+                message: crate::i18n::t("crashShare.errors.invalidUrl", &serde_json::json!({ "url": settings.api_url })),
+            });
+        }
+        match settings.backend.as_str() {
+            "mclogs" => Ok(PasteBackend::Mclogs { api_url }),
+            "raw" => Ok(PasteBackend::Raw { url: api_url }),
+            other => Err(LauncherError::Internal {
+                message: crate::i18n::t("crashShare.errors.unknownBackend", &serde_json::json!({ "backend": other })),
+            }),
+        }
+    }
+
+    async fn upload(&self, content: &str) -> Result<String, LauncherError> {
+        let client = reqwest::Client::new();
+        match self {
+            PasteBackend::Mclogs { api_url } => {
+                let response: Value = client
+                    .post(format!("{}/1/log", api_url))
+                    .form(&[("content", content)])
+                    .send()
+                    .await?
+                    .json()
+                    .await?;
+                match response["url"].as_str() {
+                    Some(url) if response["success"].as_bool().unwrap_or(true) => Ok(url.to_string()),
+                    _ => Err(LauncherError::Network {
+                        message: response["error"].as_str()
+                            .map(|error| error.to_string())
+                            .unwrap_or_else(|| crate::i18n::t("crashShare.errors.uploadFailed", &serde_json::json!({}))),
+                    }),
+                }
+            }
+            PasteBackend::Raw { url } => {
+                let response = client
+                    .post(url)
+                    .header("Content-Type", "text/plain; charset=utf-8")
+                    .body(content.to_string())
+                    .send()
+                    .await?
+                    .error_for_status()?;
+                let body = response.text().await?;
+                let link = serde_json::from_str::<Value>(&body)
+                    .ok()
+                    .and_then(|json| json["url"].as_str().map(|s| s.to_string()))
+                    .unwrap_or_else(|| body.trim().to_string());
+                if link.starts_with("http://") || link.starts_with("https://") {
+                    Ok(link)
+                } else {
+                    Err(LauncherError::Network {
+                        message: crate::i18n::t("crashShare.errors.noLink", &serde_json::json!({ "response": link })),
+                    })
+                }
+            }
+        }
+    }
+}
+
+fn read_tail(path: &Path, lines: usize) -> Option<String> {
+    let content = String::from_utf8_lossy(&std::fs::read(path).ok()?).to_string();
+    let all: Vec<&str> = content.lines().collect();
+    Some(all[all.len().saturating_sub(lines)..].join("\n"))
+}
+
+// 组装上传内容：摘要、解析到的崩溃报告文件、游戏输出和 latest.log 末尾，返回脱敏后的文本和脱敏统计
+fn build_content(app_state: &AppState) -> Result<(String, Value), LauncherError> {
+    let crash_data = app_state.last_crash_data.as_ref()
+        .ok_or(LauncherError::NoCrashData)?;
+
+    let mut sections = vec![("crash_report.txt".to_string(), crate::minecraft::render_crash_summary(app_state, crash_data))];
+    for entry in crash_data["crashInfo"]["crashReports"].as_array().into_iter().flatten() {
+        let path = Path::new(entry["path"].as_str().unwrap_or_default());
+        if let (Some(name), Ok(content)) = (path.file_name(), std::fs::read(path)) {
+            sections.push((name.to_string_lossy().to_string(), String::from_utf8_lossy(&content).to_string()));
+        }
+    }
+    let error_logs: Vec<&str> = crash_data["errorLogs"].as_array()
+        .map(|logs| logs.iter().filter_map(|l| l.as_str()).collect())
+        .unwrap_or_default();
+    if !error_logs.is_empty() {
+        sections.push(("game_stderr.log".to_string(), error_logs.join("\n")));
+    }
+    // latest.log 位于崩溃版本实际使用的游戏目录（开启版本隔离时为 versions/<id>）
+    let settings = &app_state.launcher_settings;
+    let mc_dir = settings.minecraft_dir.clone()
+        .unwrap_or_else(|| crate::launcher::get_default_mc_dir().to_string_lossy().to_string());
+    let version_id = crash_data["versionId"].as_str().unwrap_or_default();
+    let game_dir = crate::mods::game_dir_for_version(settings, &mc_dir, version_id);
+    if let Some(log) = read_tail(&game_dir.join("logs").join("latest.log"), MAX_LOG_LINES) {
+        sections.push(("logs/latest.log".to_string(), log));
+    }
+
+    let content = sections.iter()
+        .map(|(name, content)| format!("==================== {} ====================\n{}", name, content.trim_end()))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    let mut redactor = Redactor::from_state(app_state);
+    let content = redactor.redact(&content);
+    Ok((content, redactor.summary()))
+}
+
+/// 分享最近一次崩溃的报告。preview 为 true 时只返回将要上传的内容，
+/// 之后不带 preview 调用会上传这份预览；没有预览时直接生成并上传
+#[tauri::command]
+pub async fn share_crash_report(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    preview: Option<bool>,
+) -> Result<Value, LauncherError> {
+    let mut app_state = state.lock().await;
+    let settings = app_state.launcher_settings.crash_share.clone();
+
+    if preview.unwrap_or(false) {
+        let (content, redaction) = build_content(&app_state)?;
+        app_state.crash_share_preview = Some(content.clone());
+        return Ok(json!({
+            "success": true,
+            "preview": true,
+            "backend": settings.backend,
+            "apiUrl": settings.api_url,
+            "content": content,
+            "redaction": redaction
+        }));
+    }
+
+    let backend = PasteBackend::from_settings(&settings)?;
+    let content = match app_state.crash_share_preview.take() {
+        Some(content) => content,
+        None => build_content(&app_state)?.0,
+    };
+    drop(app_state);
+
+    println!("[CrashShare] Uploading {} bytes to {} ({})", content.len(), settings.api_url, settings.backend);
+    let url = backend.upload(&content).await?;
+    println!("[CrashShare] Shared at {}", url);
+    Ok(json!({
+        "success": true,
+        "url": url
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 本地替身服务：接收一次请求，返回 (路径, 请求体)，并以给定内容响应
+    fn stand_in(response: &'static str) -> (String, std::thread::JoinHandle<(String, String)>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let port = server.server_addr().to_ip().unwrap().port();
+        let handle = std::thread::spawn(move || {
+            let mut request = server.recv().unwrap();
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            let path = request.url().to_string();
+            request.respond(tiny_http::Response::from_string(response)).unwrap();
+            (path, body)
+        });
+        (format!("http://127.0.0.1:{}", port), handle)
+    }
+
+    fn settings(backend: &str, api_url: &str) -> CrashShareSettings {
+        CrashShareSettings { backend: backend.to_string(), api_url: api_url.to_string() }
+    }
+
+    const PREVIEW: &str = "==================== crash_report.txt ====================\n\
+        Exception: a=b&c+d 100%\n\t<user> 崩溃\n";
+
+    #[tokio::test]
+    async fn mclogs_posts_preview_and_returns_url() {
+        let (base, handle) = stand_in(r#"{"success":true,"id":"abc","url":"https://mclo.gs/abc"}"#);
+        let backend = PasteBackend::from_settings(&settings("mclogs", &format!("{}/", base))).unwrap();
+
+        let url = backend.upload(PREVIEW).await.unwrap();
+        let (path, body) = handle.join().unwrap();
+
+        assert_eq!(url, "https://mclo.gs/abc");
+        assert_eq!(path, "/1/log");
+        let content = url::form_urlencoded::parse(body.as_bytes())
+            .find(|(key, _)| key == "content")
+            .map(|(_, value)| value.to_string());
+        assert_eq!(content.as_deref(), Some(PREVIEW));
+    }
+
+    #[tokio::test]
+    async fn mclogs_reports_service_error() {
+        let (base, handle) = stand_in(r#"{"success":false,"error":"Content too large"}"#);
+        let backend = PasteBackend::from_settings(&settings("mclogs", &base)).unwrap();
+
+        let error = backend.upload(PREVIEW).await.unwrap_err();
+        handle.join().unwrap();
+
+        assert!(matches!(error, LauncherError::Network { message } if message == "Content too large"));
+    }
+
+    #[tokio::test]
+    async fn raw_posts_preview_and_parses_url() {
+        for response in ["https://paste.example/r/1\n", r#"{"url":"https://paste.example/r/1"}"#] {
+            let (base, handle) = stand_in(response);
+            let backend = PasteBackend::from_settings(&settings("raw", &format!("{}/upload", base))).unwrap();
+
+            let url = backend.upload(PREVIEW).await.unwrap();
+            let (path, body) = handle.join().unwrap();
+
+            assert_eq!(url, "https://paste.example/r/1");
+            assert_eq!(path, "/upload");
+            assert_eq!(body, PREVIEW);
+        }
+    }
+
+    #[tokio::test]
+    async fn raw_rejects_response_without_link() {
+        let (base, handle) = stand_in("ok");
+        let backend = PasteBackend::from_settings(&settings("raw", &base)).unwrap();
+
+        assert!(backend.upload(PREVIEW).await.is_err());
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        assert!(PasteBackend::from_settings(&settings("mclogs", "not a url")).is_err());
+        assert!(PasteBackend::from_settings(&settings("pastebin", "https://example.com")).is_err());
+    }
+}
@@ -0,0 +1,420 @@
+use crate::accounts::Account;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::Duration;
+
+const KEYRING_SERVICE: &str = "imagine-launcher";
+const MASTER_KEY_FILE: &str = "master.key";
+const CREDENTIALS_FILE: &str = "credentials.enc";
+
+/// 账户的敏感信息，不写入 accounts.json
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct AccountSecrets {
+    #[serde(default)]
+    pub access_token: Option<String>,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+}
+
+impl AccountSecrets {
+    pub fn from_account(account: &Account) -> Self {
+        Self {
+            access_token: account.access_token.clone(),
+            refresh_token: account.refresh_token.clone(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.access_token.is_none() && self.refresh_token.is_none()
+    }
+}
+
+// 加密文件格式
+#[derive(Serialize, Deserialize)]
+struct EncryptedFile {
+    version: u32,
+    nonce: String,
+    data: String,
+}
+
+// 钥匙串操作可能长时间阻塞（Secret Service 等待解锁、D-Bus 无响应等），全部放在专用线程上执行：
+// 读写最多等待 KEYRING_TIMEOUT，调用方持有 AppState 锁时也不会被无限阻塞
+const KEYRING_TIMEOUT: Duration = Duration::from_secs(5);
+
+enum Job {
+    Load { account_id: String, reply: mpsc::Sender<Option<AccountSecrets>> },
+    Sync { accounts: Vec<(String, AccountSecrets)>, reply: mpsc::Sender<Result<(), String>> },
+    Flush(mpsc::Sender<()>),
+}
+
+/// 账户凭据存储
+/// 优先使用系统钥匙串 (Windows 凭据管理器、macOS 钥匙串、Linux Secret Service)，不可用时退回到
+/// credentials.enc。加密文件的主密钥 master.key 与它保存在同一目录并仅限当前用户读写，
+/// 只能防止凭据文件被单独复制或误分享，能读取整个数据目录的程序仍可解密
+pub struct CredentialStore {
+    files: FileStore,
+    jobs: mpsc::Sender<Job>,
+    // 钥匙串读取超时后不再等待，直接读取加密文件
+    keyring_stalled: AtomicBool,
+}
+
+impl CredentialStore {
+    pub fn new(data_dir: &Path) -> Self {
+        let (jobs, receiver) = mpsc::channel();
+        let worker = Worker {
+            files: FileStore { data_dir: data_dir.to_path_buf() },
+            saved: HashMap::new(),
+        };
+        let spawned = std::thread::Builder::new()
+            .name("credentials".to_string())
+            .spawn(move || worker.run(receiver));
+        if let Err(e) = spawned {
+            println!("[Credentials] Failed to start keyring worker: {}", e);
+        }
+
+        Self {
+            files: FileStore { data_dir: data_dir.to_path_buf() },
+            jobs,
+            keyring_stalled: AtomicBool::new(false),
+        }
+    }
+
+    pub fn load(&self, account_id: &str) -> Option<AccountSecrets> {
+        if !self.keyring_stalled.load(Ordering::Relaxed) {
+            let (reply, result) = mpsc::channel();
+            let job = Job::Load { account_id: account_id.to_string(), reply };
+            if self.jobs.send(job).is_ok() {
+                match result.recv_timeout(KEYRING_TIMEOUT) {
+                    Ok(secrets) => return secrets,
+                    Err(_) => {
+                        println!("[Credentials] Keyring did not respond in {:?}, reading encrypted file", KEYRING_TIMEOUT);
+                        self.keyring_stalled.store(true, Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+        self.files.read_logged().remove(account_id)
+    }
+
+    /// 将内存中的账户凭据同步到存储，并删除已移除账户的凭据。
+    /// 写入在后台线程完成，这里等待结果，返回 Ok 时所有凭据都已保存
+    pub fn sync(&self, accounts: &[Account]) -> Result<(), String> {
+        let secrets = accounts.iter()
+            .map(|account| (account.id.clone(), AccountSecrets::from_account(account)))
+            .collect();
+        let (reply, result) = mpsc::channel();
+        self.jobs.send(Job::Sync { accounts: secrets, reply })
+            .map_err(|_| credential_error("workerExited", serde_json::json!({})))?;
+        match result.recv_timeout(KEYRING_TIMEOUT) {
+            Ok(result) => result,
+            // 写入仍在排队，完成前不能当作已保存
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(credential_error("workerTimeout", serde_json::json!({}))),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(credential_error("workerExited", serde_json::json!({}))),
+        }
+    }
+
+    /// 等待排队中的写入完成，退出前调用
+    pub fn flush(&self) {
+        let (reply, done) = mpsc::channel();
+        if self.jobs.send(Job::Flush(reply)).is_ok() && done.recv_timeout(KEYRING_TIMEOUT).is_err() {
+            println!("[Credentials] Pending credential writes did not finish before exit");
+        }
+    }
+}
+
+// 后台线程持有的状态
+struct Worker {
+    files: FileStore,
+    // 已持久化的凭据，避免每次保存账户都重复写入钥匙串
+    saved: HashMap<String, AccountSecrets>,
+}
+
+impl Worker {
+    fn run(mut self, jobs: mpsc::Receiver<Job>) {
+        for job in jobs {
+            match job {
+                Job::Load { account_id, reply } => {
+                    let secrets = self.load(&account_id);
+                    let _ = reply.send(secrets);
+                }
+                Job::Sync { accounts, reply } => {
+                    let result = self.sync(&accounts);
+                    if let Err(e) = &result {
+                        println!("[Credentials] Failed to save credentials: {}", e);
+                    }
+                    let _ = reply.send(result);
+                }
+                Job::Flush(reply) => {
+                    let _ = reply.send(());
+                }
+            }
+        }
+    }
+
+    fn load(&mut self, account_id: &str) -> Option<AccountSecrets> {
+        let secrets = system_keyring::get(account_id)
+            .and_then(|s| serde_json::from_str::<AccountSecrets>(&s).ok())
+            .or_else(|| self.files.read_logged().remove(account_id))?;
+
+        self.saved.insert(account_id.to_string(), secrets.clone());
+        Some(secrets)
+    }
+
+    fn store(&mut self, account_id: &str, secrets: &AccountSecrets) -> Result<(), String> {
+        if self.saved.get(account_id) == Some(secrets) {
+            return Ok(());
+        }
+
+        let value = serde_json::to_string(secrets).map_err(|e| e.to_string())?;
+
+        match system_keyring::set(account_id, &value) {
+            Ok(()) => {
+                // 已存入钥匙串，清理加密文件中的旧副本；文件无法解密时保持原样
+                let mut file_store = self.files.read_logged();
+                if file_store.remove(account_id).is_some() {
+                    self.files.write(&file_store)?;
+                }
+            }
+            Err(e) => {
+                println!("[Credentials] Keyring unavailable ({}), using encrypted file", e);
+                let mut file_store = self.files.read()?;
+                file_store.insert(account_id.to_string(), secrets.clone());
+                self.files.write(&file_store)?;
+            }
+        }
+
+        self.saved.insert(account_id.to_string(), secrets.clone());
+        Ok(())
+    }
+
+    fn delete(&mut self, account_id: &str) -> Result<(), String> {
+        system_keyring::delete(account_id);
+
+        let mut file_store = self.files.read()?;
+        if file_store.remove(account_id).is_some() {
+            self.files.write(&file_store)?;
+        }
+
+        self.saved.remove(account_id);
+        Ok(())
+    }
+
+    // 单个账户失败不影响其余账户，所有错误汇总后返回
+    fn sync(&mut self, accounts: &[(String, AccountSecrets)]) -> Result<(), String> {
+        let mut errors = Vec::new();
+        for (account_id, secrets) in accounts {
+            let result = if secrets.is_empty() {
+                if self.saved.contains_key(account_id) {
+                    self.delete(account_id)
+                } else {
+                    Ok(())
+                }
+            } else {
+                self.store(account_id, secrets)
+            };
+            if let Err(e) = result {
+                errors.push(format!("{}: {}", account_id, e));
+            }
+        }
+
+        let removed: Vec<String> = self.saved.keys()
+            .filter(|id| !accounts.iter().any(|(account_id, _)| account_id == *id))
+            .cloned()
+            .collect();
+        for id in removed {
+            if let Err(e) = self.delete(&id) {
+                errors.push(format!("{}: {}", id, e));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+}
+
+// 钥匙串不可用时使用的 credentials.enc
+struct FileStore {
+    data_dir: PathBuf,
+}
+
+impl FileStore {
+    // 只在主密钥不存在时生成新密钥，其他读取错误原样返回，避免覆盖后无法解密已有凭据
+    fn master_key(&self) -> Result<LessSafeKey, String> {
+        let key_path = self.data_dir.join(MASTER_KEY_FILE);
+
+        let key_bytes = match fs::read(&key_path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => create_master_key(&key_path)?,
+            Err(e) => return Err(credential_error("readMasterKey", serde_json::json!({ "message": e.to_string() }))),
+        };
+        if key_bytes.len() != 32 {
+            return Err(credential_error("masterKeyLength", serde_json::json!({ "length": key_bytes.len() })));
+        }
+
+        let unbound = UnboundKey::new(&AES_256_GCM, &key_bytes).map_err(|_| credential_error("invalidMasterKey", serde_json::json!({})))?;
+        Ok(LessSafeKey::new(unbound))
+    }
+
+    // 文件不存在时为空；无法读取或解密时返回错误，调用方不得再写入该文件
+    fn read(&self) -> Result<HashMap<String, AccountSecrets>, String> {
+        let path = self.data_dir.join(CREDENTIALS_FILE);
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+
+        let decrypt = || -> Result<HashMap<String, AccountSecrets>, String> {
+            let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            let file: EncryptedFile = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+
+            let nonce_bytes = BASE64.decode(&file.nonce).map_err(|e| e.to_string())?;
+            let nonce = Nonce::try_assume_unique_for_key(&nonce_bytes).map_err(|_| credential_error("invalidNonce", serde_json::json!({})))?;
+            let mut data = BASE64.decode(&file.data).map_err(|e| e.to_string())?;
+
+            let plain = self.master_key()?
+                .open_in_place(nonce, Aad::from(KEYRING_SERVICE.as_bytes()), &mut data)
+                .map_err(|_| credential_error("decryptFailed", serde_json::json!({})))?;
+            serde_json::from_slice(plain).map_err(|e| e.to_string())
+        };
+
+        decrypt().map_err(|e| credential_error("unreadable", serde_json::json!({ "file": CREDENTIALS_FILE, "message": e })))
+    }
+
+    // 只读取时使用，失败按空处理
+    fn read_logged(&self) -> HashMap<String, AccountSecrets> {
+        self.read().unwrap_or_else(|e| {
+            println!("[Credentials] Failed to read {}: {}", CREDENTIALS_FILE, e);
+            HashMap::new()
+        })
+    }
+
+    fn write(&self, store: &HashMap<String, AccountSecrets>) -> Result<(), String> {
+        let path = self.data_dir.join(CREDENTIALS_FILE);
+        if store.is_empty() {
+            if path.exists() {
+                fs::remove_file(&path).map_err(|e| e.to_string())?;
+            }
+            return Ok(());
+        }
+
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        SystemRandom::new().fill(&mut nonce_bytes).map_err(|_| credential_error("randomFailed", serde_json::json!({})))?;
+
+        let mut data = serde_json::to_vec(store).map_err(|e| e.to_string())?;
+        self.master_key()?
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce_bytes),
+                Aad::from(KEYRING_SERVICE.as_bytes()),
+                &mut data,
+            )
+            .map_err(|_| credential_error("encryptFailed", serde_json::json!({})))?;
+
+        let file = EncryptedFile {
+            version: 1,
+            nonce: BASE64.encode(nonce_bytes),
+            data: BASE64.encode(&data),
+        };
+        let content = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+        fs::write(&path, content).map_err(|e| credential_error("writeFailed", serde_json::json!({ "message": e.to_string() })))?;
+        restrict_permissions(&path);
+        Ok(())
+    }
+}
+
+// 新建主密钥文件，创建时即仅限当前用户读写；其他进程抢先创建时使用已有的密钥
+fn create_master_key(key_path: &Path) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![0u8; 32];
+    SystemRandom::new().fill(&mut bytes).map_err(|_| credential_error("randomFailed", serde_json::json!({})))?;
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = match options.open(key_path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+            return fs::read(key_path)
+                .map_err(|e| credential_error("readMasterKey", serde_json::json!({ "message": e.to_string() })));
+        }
+        Err(e) => return Err(credential_error("writeMasterKey", serde_json::json!({ "message": e.to_string() }))),
+    };
+    file.write_all(&bytes)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| credential_error("writeMasterKey", serde_json::json!({ "message": e.to_string() })))?;
+    Ok(bytes)
+}
+
+// 凭据存储的错误信息，按当前语言渲染 credentials.errors.*
+fn credential_error(key: &str, params: serde_json::Value) -> String {
+    crate::i18n::t(&format!("credentials.errors.{}", key), &params)
+}
+
+// 仅允许当前用户读写
+fn restrict_permissions(path: &Path) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(path, fs::Permissions::from_mode(0o600));
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+/// 收集所有账户的令牌，用于从导出内容中剔除
+pub fn collect_secrets(accounts: &[Account]) -> Vec<String> {
+    accounts.iter()
+        .flat_map(|a| [a.access_token.clone(), a.refresh_token.clone()])
+        .flatten()
+        .filter(|t| t.len() >= 8)
+        .collect()
+}
+
+// 系统钥匙串
+#[cfg(any(target_os = "linux", target_os = "windows", target_os = "macos"))]
+mod system_keyring {
+    use super::KEYRING_SERVICE;
+
+    fn entry(account_id: &str) -> keyring::Result<keyring::Entry> {
+        keyring::Entry::new(KEYRING_SERVICE, account_id)
+    }
+
+    pub fn get(account_id: &str) -> Option<String> {
+        entry(account_id).and_then(|e| e.get_password()).ok()
+    }
+
+    pub fn set(account_id: &str, secret: &str) -> Result<(), String> {
+        entry(account_id)
+            .and_then(|e| e.set_password(secret))
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn delete(account_id: &str) {
+        let _ = entry(account_id).and_then(|e| e.delete_credential());
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+mod system_keyring {
+    pub fn get(_account_id: &str) -> Option<String> {
+        None
+    }
+
+    pub fn set(_account_id: &str, _secret: &str) -> Result<(), String> {
+        Err(crate::i18n::t("credentials.errors.keyringUnsupported", &serde_json::json!({})))
+    }
+
+    pub fn delete(_account_id: &str) {}
+}
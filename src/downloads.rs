@@ -1,35 +1,91 @@
 // Downloads Module - MC版本下载和加载器安装
+use crate::error::LauncherError;
 use crate::launcher::get_default_mc_dir;
 use crate::state::AppState;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
 use tauri::Emitter;
 use tokio::sync::Mutex;
 use futures_util::StreamExt;
 
-#[derive(Debug, Serialize, Deserialize)]
+const VERSION_MANIFEST_URL: &str = "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
+
+// 实验性快照和战斗测试版不在官方清单中，Fabric 维护了同格式的清单（可用 EXTRA_VERSIONS_URL 替换）
+const EXTRA_VERSIONS_URL: &str = "https://maven.fabricmc.net/net/minecraft/experimental_versions.json";
+
+// 愚人节版本（官方清单中类型为 snapshot/old_alpha，这里单独标记）
+const APRIL_FOOLS_VERSIONS: &[&str] = &[
+    "15w14a",
+    "1.RV-Pre1",
+    "3D Shareware v1.34",
+    "20w14infinite",
+    "22w13oneblockatatime",
+    "23w13a_or_b",
+    "24w14potato",
+    "25w14craftmine",
+    "2point0_red",
+    "2point0_blue",
+    "2point0_purple",
+];
+
+// 实验性快照的 ID 特征，用于标记用户自定义列表和官方清单中的同类版本
+const EXPERIMENTAL_VERSION_MARKERS: &[&str] = &["experimental", "combat"];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct VersionManifest {
     pub latest: LatestVersions,
     pub versions: Vec<VersionEntry>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LatestVersions {
     pub release: String,
     pub snapshot: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct VersionEntry {
     pub id: String,
     #[serde(rename = "type")]
     pub version_type: String,
     pub url: String,
+    #[serde(default)]
     pub time: String,
     pub release_time: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compliance_level: Option<u32>,
+    #[serde(default)]
+    pub installed: bool,
+}
+
+// 额外版本列表（实验性快照、愚人节版本等），格式与官方清单的 versions 相同
+#[derive(Debug, Deserialize, Default)]
+struct ExtraVersions {
+    #[serde(default)]
+    versions: Vec<VersionEntry>,
+}
+
+// 缓存元数据，用于条件请求
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct CacheMeta {
+    #[serde(default)]
+    etag: Option<String>,
+    #[serde(default)]
+    last_modified: Option<String>,
+    #[serde(default)]
+    fetched_at: Option<i64>,
+}
+
+struct CachedResponse {
+    body: String,
+    offline: bool,
+    fetched_at: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -109,10 +165,10 @@ async fn download_file_with_progress(
         .get(url)
         .send()
         .await
-        .map_err(|e| format!("下载失败: {}", e))?;
+        .map_err(|e| install_error("downloadFailed", serde_json::json!({ "message": e.to_string() })))?;
     
     if !response.status().is_success() {
-        return Err(format!("下载失败: HTTP {}", response.status()));
+        return Err(install_error("downloadFailed", serde_json::json!({ "message": format!("HTTP {}", response.status()) })));
     }
     
     let total_size = response.content_length().unwrap_or(0);
@@ -136,7 +192,6 @@ async fn download_file_with_progress(
         // 每 100ms 更新一次进度
         if last_update.elapsed().as_millis() >= 100 {
             let speed = ((downloaded - last_downloaded) as f64 / last_update.elapsed().as_secs_f64()) as u64;
-            let speed_str = format_speed(speed);
             
             let progress = if total_size > 0 {
                 base_progress + ((downloaded as f32 / total_size as f32) * progress_range as f32) as u32
@@ -144,8 +199,7 @@ async fn download_file_with_progress(
                 base_progress
             };
             
-            let status = format!("下载 {} ({})", file_name, speed_str);
-            send_progress_detailed(app_handle, progress, &status, file_name, downloaded, total_size, speed);
+            send_progress_detailed(app_handle, progress, file_name, downloaded, total_size, speed);
             
             last_update = Instant::now();
             last_downloaded = downloaded;
@@ -170,15 +224,17 @@ fn format_speed(bytes_per_sec: u64) -> String {
 fn send_progress_detailed(
     app_handle: &tauri::AppHandle,
     percent: u32,
-    status: &str,
     file: &str,
     downloaded: u64,
     total: u64,
     speed: u64,
 ) {
+    let params = serde_json::json!({ "file": file, "speed": format_speed(speed) });
     let _ = app_handle.emit("download-progress", serde_json::json!({
         "percent": percent,
-        "status": status,
+        "status": crate::i18n::t("progress.downloadingFile", &params),
+        "statusKey": "progress.downloadingFile",
+        "params": params,
         "file": file,
         "downloaded": downloaded,
         "total": total,
@@ -187,21 +243,248 @@ fn send_progress_detailed(
     }));
 }
 
+// 获取版本清单（带缓存，离线时使用缓存）
+#[tauri::command]
+pub async fn get_version_manifest(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    force_refresh: Option<bool>,
+) -> Result<serde_json::Value, LauncherError> {
+    let app_state = state.lock().await;
+    let mc_dir = app_state.launcher_settings.minecraft_dir.clone()
+        .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string());
+    let cache_dir = app_state.get_cache_dir();
+    drop(app_state);
+    
+    let (manifest, offline, fetched_at) = load_version_manifest(&cache_dir, force_refresh.unwrap_or(false)).await
+        .map_err(|message| LauncherError::Network { message })?;
+    
+    let mut manifest = manifest;
+    let versions_dir = Path::new(&mc_dir).join("versions");
+    for entry in manifest.versions.iter_mut() {
+        entry.installed = versions_dir.join(&entry.id).join(format!("{}.json", entry.id)).exists();
+    }
+    
+    Ok(serde_json::json!({
+        "success": true,
+        "offline": offline,
+        "fetchedAt": fetched_at,
+        "latest": manifest.latest,
+        "versions": manifest.versions
+    }))
+}
+
+// 加载并合并版本清单：官方清单 + 额外版本列表
+async fn load_version_manifest(
+    cache_dir: &Path,
+    force_refresh: bool,
+) -> Result<(VersionManifest, bool, Option<i64>), String> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(15))
+        .build()
+        .map_err(|e| e.to_string())?;
+    
+    let cached = fetch_cached(
+        &client,
+        VERSION_MANIFEST_URL,
+        &cache_dir.join("version_manifest_v2.json"),
+        force_refresh,
+    ).await?;
+    
+    let mut manifest: VersionManifest = serde_json::from_str(&cached.body)
+        .map_err(|e| install_error("parseManifest", serde_json::json!({ "message": e.to_string() })))?;
+    
+    // 额外版本列表：实验性快照清单 + 用户自定义文件
+    let mut extras = Vec::new();
+    let extra_url = std::env::var("EXTRA_VERSIONS_URL").unwrap_or_else(|_| EXTRA_VERSIONS_URL.to_string());
+    match fetch_cached(&client, &extra_url, &cache_dir.join("extra_versions.json"), force_refresh).await {
+        Ok(extra) => match serde_json::from_str::<ExtraVersions>(&extra.body) {
+            // 该清单中的版本都是实验性快照，不论原始类型
+            Ok(list) => extras.extend(list.versions.into_iter().map(|mut entry| {
+                entry.version_type = "experimental".to_string();
+                entry
+            })),
+            Err(e) => println!("[Manifest] Invalid extra versions list: {}", e),
+        },
+        Err(e) => println!("[Manifest] Failed to load extra versions: {}", e),
+    }
+    let user_extra_path = cache_dir.join("extra_versions.user.json");
+    if let Ok(content) = std::fs::read_to_string(&user_extra_path) {
+        match serde_json::from_str::<ExtraVersions>(&content) {
+            Ok(list) => extras.extend(list.versions),
+            Err(e) => println!("[Manifest] Invalid user extra versions file: {}", e),
+        }
+    }
+    
+    merge_extra_versions(&mut manifest, extras);
+    
+    println!("[Manifest] Loaded {} versions (offline: {})", manifest.versions.len(), cached.offline);
+    Ok((manifest, cached.offline, cached.fetched_at))
+}
+
+fn merge_extra_versions(manifest: &mut VersionManifest, extras: Vec<VersionEntry>) {
+    for extra in extras {
+        if extra.url.is_empty() {
+            continue;
+        }
+        if let Some(existing) = manifest.versions.iter_mut().find(|v| v.id == extra.id) {
+            existing.version_type = extra.version_type;
+        } else {
+            manifest.versions.push(extra);
+        }
+    }
+    
+    for entry in manifest.versions.iter_mut() {
+        let id_lower = entry.id.to_lowercase();
+        if APRIL_FOOLS_VERSIONS.contains(&entry.id.as_str()) {
+            entry.version_type = "april_fools".to_string();
+        } else if EXPERIMENTAL_VERSION_MARKERS.iter().any(|m| id_lower.contains(m)) {
+            entry.version_type = "experimental".to_string();
+        }
+    }
+    
+    manifest.versions.sort_by(|a, b| b.release_time.cmp(&a.release_time));
+}
+
+// 带 ETag / If-Modified-Since 的缓存请求，网络不可用时回退到缓存
+async fn fetch_cached(
+    client: &reqwest::Client,
+    url: &str,
+    cache_path: &Path,
+    force_refresh: bool,
+) -> Result<CachedResponse, String> {
+    use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+    
+    let meta_path = cache_path.with_extension("meta.json");
+    let meta: CacheMeta = std::fs::read_to_string(&meta_path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default();
+    let cached_body = std::fs::read_to_string(cache_path).ok();
+    
+    let mut request = client.get(url);
+    if cached_body.is_some() && !force_refresh {
+        if let Some(etag) = &meta.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &meta.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+    
+    let offline_fallback = |reason: String| -> Result<CachedResponse, String> {
+        match &cached_body {
+            Some(body) => {
+                println!("[Cache] {} unavailable ({}), using cached copy", url, reason);
+                Ok(CachedResponse {
+                    body: body.clone(),
+                    offline: true,
+                    fetched_at: meta.fetched_at,
+                })
+            }
+            None => Err(install_error("fetchFailed", serde_json::json!({ "url": url, "message": reason }))),
+        }
+    };
+    
+    let response = match request.send().await {
+        Ok(r) => r,
+        Err(e) => return offline_fallback(e.to_string()),
+    };
+    
+    let now = chrono::Utc::now().timestamp_millis();
+    
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        if let Some(body) = cached_body.clone() {
+            let meta = CacheMeta { fetched_at: Some(now), ..meta };
+            if let Ok(content) = serde_json::to_string_pretty(&meta) {
+                let _ = std::fs::write(&meta_path, content);
+            }
+            return Ok(CachedResponse { body, offline: false, fetched_at: Some(now) });
+        }
+    }
+    
+    if !response.status().is_success() {
+        return offline_fallback(format!("HTTP {}", response.status()));
+    }
+    
+    let header_value = |name: reqwest::header::HeaderName| {
+        response.headers().get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+    };
+    let new_meta = CacheMeta {
+        etag: header_value(ETAG),
+        last_modified: header_value(LAST_MODIFIED),
+        fetched_at: Some(now),
+    };
+    
+    let body = match response.text().await {
+        Ok(b) => b,
+        Err(e) => return offline_fallback(e.to_string()),
+    };
+    
+    if let Some(parent) = cache_path.parent() {
+        std::fs::create_dir_all(parent).ok();
+    }
+    if let Err(e) = std::fs::write(cache_path, &body) {
+        println!("[Cache] Failed to write {:?}: {}", cache_path, e);
+    } else if let Ok(content) = serde_json::to_string_pretty(&new_meta) {
+        let _ = std::fs::write(&meta_path, content);
+    }
+    
+    Ok(CachedResponse { body, offline: false, fetched_at: Some(now) })
+}
+
+/// 安装失败时附带组件和版本信息
+pub fn install_failed(component: &str, mc_version: &str, loader_version: Option<&str>) -> impl FnOnce(String) -> LauncherError {
+    let component = component.to_string();
+    let mc_version = mc_version.to_string();
+    let loader_version = loader_version.map(|v| v.to_string());
+    move |message| LauncherError::InstallFailed { component, mc_version, loader_version, message }
+}
+
+// 安装和下载过程中的错误信息，按当前语言渲染 install.errors.*
+fn install_error(key: &str, params: serde_json::Value) -> String {
+    crate::i18n::t(&format!("install.errors.{}", key), &params)
+}
+
 // 下载 Minecraft 版本
 #[tauri::command]
 pub async fn download_minecraft_version(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     app_handle: tauri::AppHandle,
     version_id: String,
-    manifest_url: String,
+    manifest_url: Option<String>,
+) -> Result<serde_json::Value, LauncherError> {
+    download_version_files(&state, app_handle, version_id.clone(), manifest_url).await
+        .map_err(install_failed("minecraft", &version_id, None))
+}
+
+async fn download_version_files(
+    state: &Arc<Mutex<AppState>>,
+    app_handle: tauri::AppHandle,
+    version_id: String,
+    manifest_url: Option<String>,
 ) -> Result<serde_json::Value, String> {
     println!("[Download] Starting download for version: {}", version_id);
     
     let app_state = state.lock().await;
     let mc_dir = app_state.launcher_settings.minecraft_dir.clone()
         .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string());
+    let cache_dir = app_state.get_cache_dir();
     drop(app_state);
     
+    // 未指定版本 JSON 地址时从版本清单中查找
+    let manifest_url = match manifest_url.filter(|u| !u.is_empty()) {
+        Some(url) => url,
+        None => {
+            let (manifest, _, _) = load_version_manifest(&cache_dir, false).await?;
+            manifest.versions.into_iter()
+                .find(|v| v.id == version_id)
+                .map(|v| v.url)
+                .ok_or_else(|| install_error("versionNotInManifest", serde_json::json!({ "versionId": version_id })))?
+        }
+    };
+    
     let mc_path = Path::new(&mc_dir);
     let versions_dir = mc_path.join("versions");
     let version_dir = versions_dir.join(&version_id);
@@ -217,7 +500,7 @@ pub async fn download_minecraft_version(
         .map_err(|e| e.to_string())?;
     
     // 1. 下载版本 JSON
-    send_progress(&app_handle, 2, "下载版本信息...", "");
+    send_progress(&app_handle, 2, "progress.fetchingVersionInfo", serde_json::json!({}), "");
     
     let version_json: VersionJson = client
         .get(&manifest_url)
@@ -234,7 +517,7 @@ pub async fn download_minecraft_version(
     std::fs::write(&json_path, json_content).map_err(|e| e.to_string())?;
     
     // 2. 下载客户端 JAR（带进度）
-    send_progress(&app_handle, 5, "下载游戏客户端...", &format!("{}.jar", version_id));
+    send_progress(&app_handle, 5, "progress.downloadingClient", serde_json::json!({}), &format!("{}.jar", version_id));
     
     if let Some(downloads) = &version_json.downloads {
         if let Some(client_info) = &downloads.client {
@@ -252,7 +535,7 @@ pub async fn download_minecraft_version(
     }
     
     // 3. 下载库文件
-    send_progress(&app_handle, 30, "下载依赖库...", "");
+    send_progress(&app_handle, 30, "progress.downloadingLibraries", serde_json::json!({}), "");
     
     if let Some(libraries) = &version_json.libraries {
         let total = libraries.len();
@@ -295,7 +578,7 @@ pub async fn download_minecraft_version(
     }
     
     // 4. 下载资源索引
-    send_progress(&app_handle, 75, "下载资源索引...", "");
+    send_progress(&app_handle, 75, "progress.downloadingAssetIndex", serde_json::json!({}), "");
     
     if let Some(asset_index) = &version_json.asset_index {
         let indexes_dir = mc_path.join("assets").join("indexes");
@@ -307,7 +590,7 @@ pub async fn download_minecraft_version(
         }
     }
     
-    send_progress(&app_handle, 100, "下载完成", "");
+    send_progress(&app_handle, 100, "progress.downloadComplete", serde_json::json!({}), "");
     
     Ok(serde_json::json!({
         "success": true,
@@ -322,6 +605,16 @@ pub async fn install_fabric(
     app_handle: tauri::AppHandle,
     mc_version: String,
     loader_version: String,
+) -> Result<serde_json::Value, LauncherError> {
+    let on_error = install_failed("fabric", &mc_version, Some(&loader_version));
+    install_fabric_version(&state, app_handle, mc_version, loader_version).await.map_err(on_error)
+}
+
+async fn install_fabric_version(
+    state: &Arc<Mutex<AppState>>,
+    app_handle: tauri::AppHandle,
+    mc_version: String,
+    loader_version: String,
 ) -> Result<serde_json::Value, String> {
     println!("[Fabric] Installing Fabric {} for MC {}", loader_version, mc_version);
     
@@ -336,7 +629,7 @@ pub async fn install_fabric(
     let client = reqwest::Client::new();
     
     // 获取 Fabric 版本 JSON
-    send_progress(&app_handle, 10, "获取 Fabric 配置...", "");
+    send_progress(&app_handle, 10, "progress.fetchingLoaderProfile", serde_json::json!({ "loader": "Fabric" }), "");
     
     let fabric_json_url = format!(
         "https://meta.fabricmc.net/v2/versions/loader/{}/{}/profile/json",
@@ -347,28 +640,28 @@ pub async fn install_fabric(
         .get(&fabric_json_url)
         .send()
         .await
-        .map_err(|e| format!("获取 Fabric 配置失败: {}", e))?
+        .map_err(|e| install_error("fetchLoaderProfile", serde_json::json!({ "loader": "Fabric", "message": e.to_string() })))?
         .json()
         .await
-        .map_err(|e| format!("解析 Fabric 配置失败: {}", e))?;
+        .map_err(|e| install_error("parseLoaderProfile", serde_json::json!({ "loader": "Fabric", "message": e.to_string() })))?;
     
     // 版本 ID
     let version_id = fabric_json["id"].as_str()
-        .ok_or("无效的 Fabric 配置")?
+        .ok_or_else(|| install_error("invalidLoaderProfile", serde_json::json!({ "loader": "Fabric" })))?
         .to_string();
     
     let version_dir = versions_dir.join(&version_id);
     std::fs::create_dir_all(&version_dir).map_err(|e| e.to_string())?;
     
     // 保存版本 JSON
-    send_progress(&app_handle, 30, "保存版本配置...", "");
+    send_progress(&app_handle, 30, "progress.savingVersionProfile", serde_json::json!({}), "");
     
     let json_path = version_dir.join(format!("{}.json", version_id));
     let json_content = serde_json::to_string_pretty(&fabric_json).map_err(|e| e.to_string())?;
     std::fs::write(&json_path, json_content).map_err(|e| e.to_string())?;
     
     // 下载 Fabric 库
-    send_progress(&app_handle, 50, "下载 Fabric 库...", "");
+    send_progress(&app_handle, 50, "progress.downloadingLoaderLibraries", serde_json::json!({ "loader": "Fabric" }), "");
     
     let libraries_dir = mc_path.join("libraries");
     
@@ -395,7 +688,7 @@ pub async fn install_fabric(
                         
                         let download_url = format!("{}{}", url, path);
                         let progress = 50 + (i as f32 / total as f32 * 40.0) as u32;
-                        send_progress(&app_handle, progress, "下载 Fabric 库...", &path);
+                        send_progress(&app_handle, progress, "progress.downloadingLoaderLibraries", serde_json::json!({ "loader": "Fabric" }), &path);
                         
                         if let Err(e) = download_file(&client, &download_url, &lib_path).await {
                             println!("[Fabric] Failed to download {}: {}", path, e);
@@ -406,7 +699,7 @@ pub async fn install_fabric(
         }
     }
     
-    send_progress(&app_handle, 100, "安装完成", "");
+    send_progress(&app_handle, 100, "progress.installComplete", serde_json::json!({}), "");
     
     Ok(serde_json::json!({
         "success": true,
@@ -421,6 +714,16 @@ pub async fn install_forge(
     app_handle: tauri::AppHandle,
     mc_version: String,
     forge_version: String,
+) -> Result<serde_json::Value, LauncherError> {
+    let on_error = install_failed("forge", &mc_version, Some(&forge_version));
+    install_forge_version(&state, app_handle, mc_version, forge_version).await.map_err(on_error)
+}
+
+async fn install_forge_version(
+    state: &Arc<Mutex<AppState>>,
+    app_handle: tauri::AppHandle,
+    mc_version: String,
+    forge_version: String,
 ) -> Result<serde_json::Value, String> {
     println!("[Forge] Installing Forge {} for MC {}", forge_version, mc_version);
     
@@ -435,7 +738,7 @@ pub async fn install_forge(
     let client = reqwest::Client::new();
     
     // 使用 BMCLAPI 获取 Forge 安装信息
-    send_progress(&app_handle, 10, "获取 Forge 配置...", "");
+    send_progress(&app_handle, 10, "progress.fetchingLoaderProfile", serde_json::json!({ "loader": "Forge" }), "");
     
     // 获取 Forge 版本详情
     let forge_list_url = format!(
@@ -447,17 +750,17 @@ pub async fn install_forge(
         .get(&forge_list_url)
         .send()
         .await
-        .map_err(|e| format!("获取 Forge 列表失败: {}", e))?
+        .map_err(|e| install_error("fetchForgeList", serde_json::json!({ "message": e.to_string() })))?
         .json()
         .await
-        .map_err(|e| format!("解析 Forge 列表失败: {}", e))?;
+        .map_err(|e| install_error("parseForgeList", serde_json::json!({ "message": e.to_string() })))?;
     
     // 找到对应版本
     let forge_info = forge_list.iter()
         .find(|f| f["version"].as_str() == Some(&forge_version))
-        .ok_or("未找到指定的 Forge 版本")?;
+        .ok_or_else(|| install_error("forgeVersionNotFound", serde_json::json!({})))?;
     
-    let build = forge_info["build"].as_i64().ok_or("无效的 Forge 构建号")?;
+    let build = forge_info["build"].as_i64().ok_or_else(|| install_error("invalidForgeBuild", serde_json::json!({})))?;
     
     // 获取安装配置
     let install_url = format!(
@@ -465,7 +768,7 @@ pub async fn install_forge(
         build
     );
     
-    send_progress(&app_handle, 30, "下载 Forge 安装器...", "");
+    send_progress(&app_handle, 30, "progress.downloadingForgeInstaller", serde_json::json!({}), "");
     
     // 下载 Forge 安装器到临时目录
     let temp_dir = std::env::temp_dir().join("imagine_launcher");
@@ -479,7 +782,7 @@ pub async fn install_forge(
     let version_dir = versions_dir.join(&version_id);
     std::fs::create_dir_all(&version_dir).map_err(|e| e.to_string())?;
     
-    send_progress(&app_handle, 60, "解压 Forge 文件...", "");
+    send_progress(&app_handle, 60, "progress.extractingForge", serde_json::json!({}), "");
     
     // 解压安装器获取版本 JSON
     let file = std::fs::File::open(&installer_path).map_err(|e| e.to_string())?;
@@ -509,7 +812,7 @@ pub async fn install_forge(
     // 清理临时文件
     std::fs::remove_file(&installer_path).ok();
     
-    send_progress(&app_handle, 100, "安装完成", "");
+    send_progress(&app_handle, 100, "progress.installComplete", serde_json::json!({}), "");
     
     Ok(serde_json::json!({
         "success": true,
@@ -524,6 +827,16 @@ pub async fn install_quilt(
     app_handle: tauri::AppHandle,
     mc_version: String,
     loader_version: String,
+) -> Result<serde_json::Value, LauncherError> {
+    let on_error = install_failed("quilt", &mc_version, Some(&loader_version));
+    install_quilt_version(&state, app_handle, mc_version, loader_version).await.map_err(on_error)
+}
+
+async fn install_quilt_version(
+    state: &Arc<Mutex<AppState>>,
+    app_handle: tauri::AppHandle,
+    mc_version: String,
+    loader_version: String,
 ) -> Result<serde_json::Value, String> {
     println!("[Quilt] Installing Quilt {} for MC {}", loader_version, mc_version);
     
@@ -538,7 +851,7 @@ pub async fn install_quilt(
     let client = reqwest::Client::new();
     
     // 获取 Quilt 版本 JSON
-    send_progress(&app_handle, 10, "获取 Quilt 配置...", "");
+    send_progress(&app_handle, 10, "progress.fetchingLoaderProfile", serde_json::json!({ "loader": "Quilt" }), "");
     
     let quilt_json_url = format!(
         "https://meta.quiltmc.org/v3/versions/loader/{}/{}/profile/json",
@@ -549,28 +862,28 @@ pub async fn install_quilt(
         .get(&quilt_json_url)
         .send()
         .await
-        .map_err(|e| format!("获取 Quilt 配置失败: {}", e))?
+        .map_err(|e| install_error("fetchLoaderProfile", serde_json::json!({ "loader": "Quilt", "message": e.to_string() })))?
         .json()
         .await
-        .map_err(|e| format!("解析 Quilt 配置失败: {}", e))?;
+        .map_err(|e| install_error("parseLoaderProfile", serde_json::json!({ "loader": "Quilt", "message": e.to_string() })))?;
     
     // 版本 ID
     let version_id = quilt_json["id"].as_str()
-        .ok_or("无效的 Quilt 配置")?
+        .ok_or_else(|| install_error("invalidLoaderProfile", serde_json::json!({ "loader": "Quilt" })))?
         .to_string();
     
     let version_dir = versions_dir.join(&version_id);
     std::fs::create_dir_all(&version_dir).map_err(|e| e.to_string())?;
     
     // 保存版本 JSON
-    send_progress(&app_handle, 30, "保存版本配置...", "");
+    send_progress(&app_handle, 30, "progress.savingVersionProfile", serde_json::json!({}), "");
     
     let json_path = version_dir.join(format!("{}.json", version_id));
     let json_content = serde_json::to_string_pretty(&quilt_json).map_err(|e| e.to_string())?;
     std::fs::write(&json_path, json_content).map_err(|e| e.to_string())?;
     
     // 下载 Quilt 库
-    send_progress(&app_handle, 50, "下载 Quilt 库...", "");
+    send_progress(&app_handle, 50, "progress.downloadingLoaderLibraries", serde_json::json!({ "loader": "Quilt" }), "");
     
     let libraries_dir = mc_path.join("libraries");
     
@@ -597,7 +910,7 @@ pub async fn install_quilt(
                         
                         let download_url = format!("{}{}", url, path);
                         let progress = 50 + (i as f32 / total as f32 * 40.0) as u32;
-                        send_progress(&app_handle, progress, "下载 Quilt 库...", &path);
+                        send_progress(&app_handle, progress, "progress.downloadingLoaderLibraries", serde_json::json!({ "loader": "Quilt" }), &path);
                         
                         if let Err(e) = download_file(&client, &download_url, &lib_path).await {
                             println!("[Quilt] Failed to download {}: {}", path, e);
@@ -608,7 +921,7 @@ pub async fn install_quilt(
         }
     }
     
-    send_progress(&app_handle, 100, "安装完成", "");
+    send_progress(&app_handle, 100, "progress.installComplete", serde_json::json!({}), "");
     
     Ok(serde_json::json!({
         "success": true,
@@ -618,31 +931,31 @@ pub async fn install_quilt(
 
 // 打开 URL
 #[tauri::command]
-pub async fn open_url(url: String) -> Result<(), String> {
-    open::that(&url).map_err(|e| e.to_string())
+pub async fn open_url(url: String) -> Result<(), LauncherError> {
+    open::that(&url).map_err(LauncherError::from)
 }
 
 // 获取 Forge 支持的 MC 版本列表 (代理 API 避免 CORS)
 #[tauri::command]
-pub async fn get_forge_mc_versions() -> Result<serde_json::Value, String> {
+pub async fn get_forge_mc_versions() -> Result<serde_json::Value, LauncherError> {
     let client = reqwest::Client::new();
     let response = client
         .get("https://bmclapi2.bangbang93.com/forge/minecraft")
         .send()
         .await
-        .map_err(|e| format!("获取 Forge 版本列表失败: {}", e))?;
+        .map_err(|e| LauncherError::Network { message: install_error("fetchForgeList", serde_json::json!({ "message": e.to_string() })) })?;
     
     let versions: serde_json::Value = response
         .json()
         .await
-        .map_err(|e| format!("解析 Forge 版本列表失败: {}", e))?;
+        .map_err(|e| LauncherError::Network { message: install_error("parseForgeList", serde_json::json!({ "message": e.to_string() })) })?;
     
     Ok(versions)
 }
 
 // 获取指定 MC 版本的 Forge 版本列表 (代理 API 避免 CORS)
 #[tauri::command]
-pub async fn get_forge_versions(mc_version: String) -> Result<serde_json::Value, String> {
+pub async fn get_forge_versions(mc_version: String) -> Result<serde_json::Value, LauncherError> {
     let client = reqwest::Client::new();
     let url = format!("https://bmclapi2.bangbang93.com/forge/minecraft/{}", mc_version);
     
@@ -650,21 +963,24 @@ pub async fn get_forge_versions(mc_version: String) -> Result<serde_json::Value,
         .get(&url)
         .send()
         .await
-        .map_err(|e| format!("获取 Forge 版本失败: {}", e))?;
+        .map_err(|e| LauncherError::Network { message: install_error("fetchForgeList", serde_json::json!({ "message": e.to_string() })) })?;
     
     let versions: serde_json::Value = response
         .json()
         .await
-        .map_err(|e| format!("解析 Forge 版本失败: {}", e))?;
+        .map_err(|e| LauncherError::Network { message: install_error("parseForgeList", serde_json::json!({ "message": e.to_string() })) })?;
     
     Ok(versions)
 }
 
 // 辅助函数
-fn send_progress(app_handle: &tauri::AppHandle, percent: u32, status: &str, file: &str) {
+// status 为按当前语言渲染的文本，statusKey 和 params 供前端自行翻译
+fn send_progress(app_handle: &tauri::AppHandle, percent: u32, key: &str, params: serde_json::Value, file: &str) {
     let _ = app_handle.emit("download-progress", serde_json::json!({
         "percent": percent,
-        "status": status,
+        "status": crate::i18n::t(key, &params),
+        "statusKey": key,
+        "params": params,
         "file": file
     }));
 }
@@ -674,10 +990,10 @@ async fn download_file(client: &reqwest::Client, url: &str, path: &Path) -> Resu
         .get(url)
         .send()
         .await
-        .map_err(|e| format!("下载失败: {}", e))?;
+        .map_err(|e| install_error("downloadFailed", serde_json::json!({ "message": e.to_string() })))?;
     
     if !response.status().is_success() {
-        return Err(format!("下载失败: HTTP {}", response.status()));
+        return Err(install_error("downloadFailed", serde_json::json!({ "message": format!("HTTP {}", response.status()) })));
     }
     
     let bytes = response.bytes().await.map_err(|e| e.to_string())?;
@@ -747,7 +1063,7 @@ pub async fn complete_game_files(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     app_handle: tauri::AppHandle,
     version_id: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     println!("[Complete] Checking game files for version: {}", version_id);
     
     let app_state = state.lock().await;
@@ -773,8 +1089,7 @@ pub async fn complete_game_files(
     
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(60))
-        .build()
-        .map_err(|e| e.to_string())?;
+        .build()?;
     
     let mut downloaded_count = 0;
     let mut failed_count = 0;
@@ -782,15 +1097,18 @@ pub async fn complete_game_files(
     // 1. 读取并合并版本 JSON
     let json_path = version_dir.join(format!("{}.json", version_id));
     if !json_path.exists() {
-        return Err(format!("版本文件不存在: {}", json_path.display()));
+        return Err(LauncherError::FileNotFound { path: json_path.display().to_string() });
     }
     
-    let json_content = std::fs::read_to_string(&json_path).map_err(|e| e.to_string())?;
-    let version_json: serde_json::Value = serde_json::from_str(&json_content).map_err(|e| e.to_string())?;
-    let merged = merge_version_json(&mc_dir, &version_json)?;
+    let on_error = install_failed("minecraft", &version_id, None);
+    let json_content = std::fs::read_to_string(&json_path)?;
+    let merged = serde_json::from_str::<serde_json::Value>(&json_content)
+        .map_err(|e| e.to_string())
+        .and_then(|version_json| merge_version_json(&mc_dir, &version_json))
+        .map_err(on_error)?;
     
     // 2. 检查版本 JAR（带 SHA-1 校验）
-    send_progress(&app_handle, 5, "检查游戏客户端...", "");
+    send_progress(&app_handle, 5, "progress.checkingClient", serde_json::json!({}), "");
     
     let jar_path = version_dir.join(format!("{}.jar", version_id));
     let expected_sha1 = merged.pointer("/downloads/client/sha1").and_then(|v| v.as_str());
@@ -825,7 +1143,7 @@ pub async fn complete_game_files(
     }
     
     // 3. 检查库文件（带 SHA-1 校验）
-    send_progress(&app_handle, 15, "检查依赖库...", "");
+    send_progress(&app_handle, 15, "progress.checkingLibraries", serde_json::json!({}), "");
     
     if let Some(libraries) = merged["libraries"].as_array() {
         let total = libraries.len();
@@ -868,7 +1186,7 @@ pub async fn complete_game_files(
                 }
                 let progress = 15 + (i as f32 / total as f32 * 35.0) as u32;
                 let file_name = lib_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                send_progress(&app_handle, progress, "下载依赖库...", file_name);
+                send_progress(&app_handle, progress, "progress.downloadingLibraries", serde_json::json!({}), file_name);
                 
                 match download_file(&client, &lib_url, &lib_path).await {
                     Ok(_) => downloaded_count += 1,
@@ -879,7 +1197,7 @@ pub async fn complete_game_files(
     }
     
     // 4. 检查资源索引
-    send_progress(&app_handle, 55, "检查资源索引...", "");
+    send_progress(&app_handle, 55, "progress.checkingAssetIndex", serde_json::json!({}), "");
     
     let indexes_dir = assets_dir.join("indexes");
     std::fs::create_dir_all(&indexes_dir).ok();
@@ -906,7 +1224,7 @@ pub async fn complete_game_files(
         }
         
         // 5. 检查资源文件（全部下载，带 SHA-1 校验）
-        send_progress(&app_handle, 60, "检查资源文件...", "");
+        send_progress(&app_handle, 60, "progress.checkingAssets", serde_json::json!({}), "");
         
         if index_path.exists() {
             if let Ok(content) = std::fs::read_to_string(&index_path) {
@@ -949,7 +1267,7 @@ pub async fn complete_game_files(
                             
                             let progress = 60 + (i as f32 / total.max(1) as f32 * 35.0) as u32;
                             if i % 50 == 0 {
-                                send_progress(&app_handle, progress, &format!("下载资源 ({}/{})", i, total), name);
+                                send_progress(&app_handle, progress, "progress.downloadingAssets", serde_json::json!({ "current": i, "total": total }), name);
                             }
                             
                             match download_file(&client, &url, &asset_path).await {
@@ -963,7 +1281,7 @@ pub async fn complete_game_files(
         }
     }
     
-    send_progress(&app_handle, 100, "补全完成", "");
+    send_progress(&app_handle, 100, "progress.completeFinished", serde_json::json!({}), "");
     
     println!("[Complete] Done: downloaded={}, failed={}", downloaded_count, failed_count);
     
@@ -1046,3 +1364,334 @@ fn merge_version_json(mc_dir: &str, version_json: &serde_json::Value) -> Result<
     
     Ok(merged)
 }
+
+
+// ═══════════════════════════════════════════════════════════
+// Java 运行时下载 (Mojang java-runtime 清单)
+// ═══════════════════════════════════════════════════════════
+
+const JAVA_RUNTIME_MANIFEST_URL: &str = "https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
+
+// 当前平台在 java-runtime 清单中的名称
+fn java_runtime_platform() -> &'static str {
+    if cfg!(target_os = "windows") {
+        if cfg!(target_arch = "x86_64") {
+            "windows-x64"
+        } else if cfg!(target_arch = "aarch64") {
+            "windows-arm64"
+        } else {
+            "windows-x86"
+        }
+    } else if cfg!(target_os = "macos") {
+        if cfg!(target_arch = "aarch64") {
+            "mac-os-arm64"
+        } else {
+            "mac-os"
+        }
+    } else if cfg!(target_arch = "x86") {
+        "linux-i386"
+    } else {
+        "linux"
+    }
+}
+
+/// 沿继承链查找版本 JSON 中的 javaVersion 字段
+pub fn find_java_version_info(mc_dir: &str, version_id: &str) -> Option<serde_json::Value> {
+    find_inherited_field(mc_dir, version_id, "javaVersion")
+}
+
+/// 沿 inheritsFrom 继承链查找版本 JSON 中的字段，子版本优先
+pub fn find_inherited_field(mc_dir: &str, version_id: &str, key: &str) -> Option<serde_json::Value> {
+    let mut current = version_id.to_string();
+    // 防止继承链成环
+    for _ in 0..8 {
+        let json_path = Path::new(mc_dir)
+            .join("versions")
+            .join(&current)
+            .join(format!("{}.json", current));
+        let content = std::fs::read_to_string(&json_path).ok()?;
+        let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+        
+        if let Some(value) = json.get(key) {
+            return Some(value.clone());
+        }
+        current = json["inheritsFrom"].as_str()?.to_string();
+    }
+    None
+}
+
+/// 版本所需的 Java 运行时组件，旧版本没有 javaVersion 时使用 jre-legacy
+pub fn required_java_component(mc_dir: &str, version_id: &str) -> String {
+    find_java_version_info(mc_dir, version_id)
+        .and_then(|v| v["component"].as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| "jre-legacy".to_string())
+}
+
+/// Java 运行时安装目录: <mc_dir>/runtime/<component>/<os>/<component>
+/// 与官方启动器布局一致，可以复用官方启动器已下载的运行时
+pub fn java_runtime_dir(mc_dir: &str, component: &str) -> PathBuf {
+    Path::new(mc_dir)
+        .join("runtime")
+        .join(component)
+        .join(java_runtime_platform())
+        .join(component)
+}
+
+pub fn java_runtime_executable(runtime_dir: &Path) -> PathBuf {
+    #[cfg(target_os = "windows")]
+    return runtime_dir.join("bin").join("java.exe");
+    #[cfg(target_os = "macos")]
+    return runtime_dir.join("jre.bundle").join("Contents").join("Home").join("bin").join("java");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    return runtime_dir.join("bin").join("java");
+}
+
+// 手动下载版本所需的 Java 运行时
+#[tauri::command]
+pub async fn install_java_runtime(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    app_handle: tauri::AppHandle,
+    version_id: String,
+) -> Result<serde_json::Value, LauncherError> {
+    let app_state = state.lock().await;
+    let mc_dir = app_state.launcher_settings.minecraft_dir.clone()
+        .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string());
+    drop(app_state);
+    
+    let component = required_java_component(&mc_dir, &version_id);
+    let java_path = ensure_java_runtime(&mc_dir, &component, &app_handle).await
+        .map_err(install_failed(&component, &version_id, None))?;
+    
+    Ok(serde_json::json!({
+        "success": true,
+        "component": component,
+        "javaPath": java_path.to_string_lossy()
+    }))
+}
+
+/// 确保指定的 Java 运行时已安装，返回 java 可执行文件路径
+pub async fn ensure_java_runtime(
+    mc_dir: &str,
+    component: &str,
+    app_handle: &tauri::AppHandle,
+) -> Result<PathBuf, String> {
+    let runtime_dir = java_runtime_dir(mc_dir, component);
+    let java_exe = java_runtime_executable(&runtime_dir);
+    let version_marker = runtime_dir.parent()
+        .map(|p| p.join(".version"))
+        .unwrap_or_else(|| runtime_dir.join(".version"));
+    
+    if java_exe.exists() && version_marker.exists() {
+        return Ok(java_exe);
+    }
+    
+    println!("[JavaRuntime] Installing {} for {} into {:?}", component, java_runtime_platform(), runtime_dir);
+    send_progress(app_handle, 0, "progress.fetchingJavaRuntime", serde_json::json!({}), component);
+    
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(120))
+        .build()
+        .map_err(|e| e.to_string())?;
+    
+    let all_runtimes: serde_json::Value = client
+        .get(JAVA_RUNTIME_MANIFEST_URL)
+        .send()
+        .await
+        .map_err(|e| install_error("fetchJavaRuntimes", serde_json::json!({ "message": e.to_string() })))?
+        .json()
+        .await
+        .map_err(|e| install_error("parseJavaRuntimes", serde_json::json!({ "message": e.to_string() })))?;
+    
+    let runtime = all_runtimes[java_runtime_platform()][component]
+        .as_array()
+        .and_then(|arr| arr.first())
+        .ok_or_else(|| install_error("noJavaRuntime", serde_json::json!({ "component": component })))?;
+    
+    let manifest_url = runtime.pointer("/manifest/url").and_then(|v| v.as_str())
+        .ok_or_else(|| install_error("invalidJavaRuntime", serde_json::json!({})))?;
+    let manifest_sha1 = runtime.pointer("/manifest/sha1").and_then(|v| v.as_str());
+    let version_name = runtime.pointer("/version/name").and_then(|v| v.as_str()).unwrap_or(component);
+    
+    let manifest_bytes = client
+        .get(manifest_url)
+        .send()
+        .await
+        .map_err(|e| install_error("fetchJavaManifest", serde_json::json!({ "message": e.to_string() })))?
+        .bytes()
+        .await
+        .map_err(|e| e.to_string())?;
+    
+    if let Some(expected) = manifest_sha1 {
+        if sha1_hex(&manifest_bytes) != expected.to_lowercase() {
+            return Err(install_error("javaManifestChecksum", serde_json::json!({})));
+        }
+    }
+    
+    let manifest: serde_json::Value = serde_json::from_slice(&manifest_bytes)
+        .map_err(|e| install_error("parseJavaManifest", serde_json::json!({ "message": e.to_string() })))?;
+    let files = manifest["files"].as_object()
+        .ok_or_else(|| install_error("invalidJavaManifest", serde_json::json!({})))?;
+    
+    std::fs::create_dir_all(&runtime_dir).map_err(|e| e.to_string())?;
+    
+    // 先创建目录，再下载文件，最后创建链接（链接目标必须已存在）
+    let mut links = Vec::new();
+    let mut downloads = Vec::new();
+    
+    for (name, entry) in files {
+        let target = runtime_dir.join(name);
+        match entry["type"].as_str().unwrap_or("") {
+            "directory" => {
+                std::fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+            }
+            "file" => downloads.push((name, entry, target)),
+            "link" => {
+                if let Some(link_target) = entry["target"].as_str() {
+                    links.push((target, link_target.to_string()));
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    let total = downloads.len();
+    for (i, (name, entry, target)) in downloads.into_iter().enumerate() {
+        let raw = &entry["downloads"]["raw"];
+        let url = raw["url"].as_str().ok_or_else(|| install_error("missingDownloadUrl", serde_json::json!({ "file": name })))?;
+        let sha1 = raw["sha1"].as_str();
+        
+        let valid = target.exists() && sha1.map(|h| verify_sha1(&target, h)).unwrap_or(true);
+        if !valid {
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent).ok();
+            }
+            if i % 20 == 0 {
+                let progress = 5 + (i as f32 / total.max(1) as f32 * 90.0) as u32;
+                send_progress(app_handle, progress, "progress.downloadingJavaRuntime", serde_json::json!({ "current": i, "total": total }), name);
+            }
+            download_file(&client, url, &target).await?;
+            if let Some(expected) = sha1 {
+                if !verify_sha1(&target, expected) {
+                    let _ = std::fs::remove_file(&target);
+                    return Err(install_error("fileChecksum", serde_json::json!({ "file": name })));
+                }
+            }
+        }
+        
+        #[cfg(unix)]
+        if entry["executable"].as_bool().unwrap_or(false) {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o755))
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    
+    #[cfg(unix)]
+    for (link_path, link_target) in &links {
+        if let Some(parent) = link_path.parent() {
+            std::fs::create_dir_all(parent).ok();
+        }
+        let _ = std::fs::remove_file(link_path);
+        std::os::unix::fs::symlink(link_target, link_path)
+            .map_err(|e| install_error("linkFailed", serde_json::json!({ "path": link_path.display().to_string(), "message": e.to_string() })))?;
+    }
+    #[cfg(not(unix))]
+    if !links.is_empty() {
+        println!("[JavaRuntime] Skipping {} symlinks on this platform", links.len());
+    }
+    
+    if !java_exe.exists() {
+        return Err(install_error("javaRuntimeIncomplete", serde_json::json!({ "path": java_exe.display().to_string() })));
+    }
+    
+    std::fs::write(&version_marker, version_name).map_err(|e| e.to_string())?;
+    send_progress(app_handle, 100, "progress.javaRuntimeInstalled", serde_json::json!({}), component);
+    println!("[JavaRuntime] Installed {} ({})", component, version_name);
+    
+    Ok(java_exe)
+}
+
+fn sha1_hex(data: &[u8]) -> String {
+    use sha1::{Sha1, Digest};
+    
+    let mut hasher = Sha1::new();
+    hasher.update(data);
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, version_type: &str, release_time: &str) -> VersionEntry {
+        VersionEntry {
+            id: id.to_string(),
+            version_type: version_type.to_string(),
+            url: format!("https://example.com/{}.json", id),
+            time: String::new(),
+            release_time: release_time.to_string(),
+            sha1: None,
+            compliance_level: None,
+            installed: false,
+        }
+    }
+
+    fn manifest(versions: Vec<VersionEntry>) -> VersionManifest {
+        VersionManifest {
+            latest: LatestVersions { release: "1.20.1".to_string(), snapshot: "1.20.1".to_string() },
+            versions,
+        }
+    }
+
+    fn types(manifest: &VersionManifest) -> Vec<(&str, &str)> {
+        manifest.versions.iter().map(|v| (v.id.as_str(), v.version_type.as_str())).collect()
+    }
+
+    #[test]
+    fn extras_are_added_and_sorted_by_release_time() {
+        let mut manifest = manifest(vec![
+            entry("1.19.4", "release", "2023-03-14T12:56:18+00:00"),
+            entry("1.20.1", "release", "2023-06-12T13:25:51+00:00"),
+        ]);
+        merge_extra_versions(&mut manifest, vec![entry("1.19_deep_dark_experimental_snapshot-1", "snapshot", "2022-02-16T10:41:13+00:00")]);
+        assert_eq!(types(&manifest), [
+            ("1.20.1", "release"),
+            ("1.19.4", "release"),
+            ("1.19_deep_dark_experimental_snapshot-1", "experimental"),
+        ]);
+    }
+
+    #[test]
+    fn extras_override_type_of_existing_versions_only() {
+        let mut manifest = manifest(vec![entry("23w13a_or_b", "snapshot", "2023-04-01T12:00:00+00:00")]);
+        merge_extra_versions(&mut manifest, vec![entry("23w13a_or_b", "april_fools", "1970-01-01T00:00:00+00:00")]);
+        assert_eq!(manifest.versions.len(), 1);
+        assert_eq!(manifest.versions[0].version_type, "april_fools");
+        // 已有条目的其余字段保持官方清单的值
+        assert_eq!(manifest.versions[0].release_time, "2023-04-01T12:00:00+00:00");
+    }
+
+    #[test]
+    fn extras_without_url_are_skipped() {
+        let mut manifest = manifest(vec![entry("1.20.1", "release", "2023-06-12T13:25:51+00:00")]);
+        let mut broken = entry("custom", "experimental", "2024-01-01T00:00:00+00:00");
+        broken.url.clear();
+        merge_extra_versions(&mut manifest, vec![broken]);
+        assert_eq!(types(&manifest), [("1.20.1", "release")]);
+    }
+
+    #[test]
+    fn known_april_fools_and_experimental_ids_are_retyped() {
+        let mut manifest = manifest(vec![
+            entry("20w14infinite", "snapshot", "2020-04-01T12:00:00+00:00"),
+            entry("1.14_combat-212796", "snapshot", "2019-11-29T12:00:00+00:00"),
+            entry("1.15", "release", "2019-12-09T12:00:00+00:00"),
+        ]);
+        merge_extra_versions(&mut manifest, Vec::new());
+        assert_eq!(types(&manifest), [
+            ("20w14infinite", "april_fools"),
+            ("1.15", "release"),
+            ("1.14_combat-212796", "experimental"),
+        ]);
+    }
+}
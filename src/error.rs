@@ -0,0 +1,203 @@
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+// 命令统一使用的错误类型
+// 序列化为 { success: false, code, messageKey, error, context }，前端按 code 区分错误，
+// messageKey 对应语言文件中的 errors.* 条目，error 为用 context 填充后按当前语言渲染的文本
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{}", crate::i18n::t(&self.message_key(), &self.context()))]
+pub enum LauncherError {
+    AccountNotFound { account_id: String },
+    NoAccountSelected,
+    UnsupportedAccountType { account_id: String, expected: &'static str },
+    InvalidUsername { username: String, reason: String },
+    DuplicateUsername { username: String, existing_account_id: String, existing_type: String },
+    InvalidUuid { value: String },
+    LoginCancelled,
+    ReloginRequired { account_id: Option<String> },
+    NoLicense,
+    NoProfile { game_pass: bool },
+    Auth { message: String },
+    ProfileNameRejected { name: String, status: Option<String>, message: String },
+    Network { message: String },
+    Io { message: String },
+    DuplicateInstance { version_id: String, pid: u32 },
+    JavaNotFound { required_major: Option<u32>, message: String },
+    JavaCheckFailed { java_path: Option<String>, message: String },
+    GameDirMissing { path: String },
+    AgentUnavailable { message: String },
+    LaunchFailed { version_id: String, message: String },
+    InstallFailed { component: String, mc_version: String, loader_version: Option<String>, message: String },
+    BisectInProgress { version_id: String },
+    NoModsToBisect { mods_dir: String },
+    BisectRestoreFailed { files: String },
+    InvalidSkin { message: String },
+    SkinNotFound { hash: String },
+    ProfileApi { status: u16, message: String },
+    FileNotFound { path: String },
+    ChecksumMismatch { file: String, expected: String, actual: String },
+    NoCrashData,
+    UnsupportedShell { shell: String },
+    Internal { message: String },
+}
+
+impl LauncherError {
+    /// 稳定的错误代码，前端据此判断错误类型
+    pub fn code(&self) -> &'static str {
+        match self {
+            LauncherError::AccountNotFound { .. } => "account_not_found",
+            LauncherError::NoAccountSelected => "no_account_selected",
+            LauncherError::UnsupportedAccountType { .. } => "unsupported_account_type",
+            LauncherError::InvalidUsername { .. } => "invalid_username",
+            LauncherError::DuplicateUsername { .. } => "duplicate_username",
+            LauncherError::InvalidUuid { .. } => "invalid_uuid",
+            LauncherError::LoginCancelled => "login_cancelled",
+            LauncherError::ReloginRequired { .. } => "relogin_required",
+            LauncherError::NoLicense => "no_license",
+            LauncherError::NoProfile { .. } => "no_profile",
+            LauncherError::Auth { .. } => "auth_failed",
+            LauncherError::ProfileNameRejected { .. } => "profile_name_rejected",
+            LauncherError::Network { .. } => "network",
+            LauncherError::Io { .. } => "io",
+            LauncherError::DuplicateInstance { .. } => "duplicate_instance",
+            LauncherError::JavaNotFound { .. } => "java_not_found",
+            LauncherError::JavaCheckFailed { .. } => "java_check_failed",
+            LauncherError::GameDirMissing { .. } => "game_dir_missing",
+            LauncherError::AgentUnavailable { .. } => "agent_unavailable",
+            LauncherError::LaunchFailed { .. } => "launch_failed",
+            LauncherError::InstallFailed { .. } => "install_failed",
+            LauncherError::BisectInProgress { .. } => "bisect_in_progress",
+            LauncherError::NoModsToBisect { .. } => "no_mods_to_bisect",
+            LauncherError::BisectRestoreFailed { .. } => "bisect_restore_failed",
+            LauncherError::InvalidSkin { .. } => "invalid_skin",
+            LauncherError::SkinNotFound { .. } => "skin_not_found",
+            LauncherError::ProfileApi { .. } => "profile_api",
+            LauncherError::FileNotFound { .. } => "file_not_found",
+            LauncherError::ChecksumMismatch { .. } => "checksum_mismatch",
+            LauncherError::NoCrashData => "no_crash_data",
+            LauncherError::UnsupportedShell { .. } => "unsupported_shell",
+            LauncherError::Internal { .. } => "internal",
+        }
+    }
+
+    /// 语言文件中的翻译键，例如 account_not_found -> errors.accountNotFound
+    pub fn message_key(&self) -> String {
+        if let LauncherError::NoProfile { game_pass: true } = self {
+            return "errors.noProfileGamePass".to_string();
+        }
+        let mut key = String::from("errors.");
+        let mut upper = false;
+        for c in self.code().chars() {
+            if c == '_' {
+                upper = true;
+            } else if upper {
+                key.push(c.to_ascii_uppercase());
+                upper = false;
+            } else {
+                key.push(c);
+            }
+        }
+        key
+    }
+
+    /// 错误相关的结构化字段
+    pub fn context(&self) -> serde_json::Value {
+        use serde_json::json;
+        match self {
+            LauncherError::AccountNotFound { account_id } => json!({ "accountId": account_id }),
+            LauncherError::UnsupportedAccountType { account_id, expected } => json!({
+                "accountId": account_id,
+                "expected": expected
+            }),
+            LauncherError::InvalidUsername { username, reason } => json!({
+                "username": username,
+                "reason": reason
+            }),
+            LauncherError::DuplicateUsername { username, existing_account_id, existing_type } => json!({
+                "username": username,
+                "existingAccountId": existing_account_id,
+                "existingType": existing_type
+            }),
+            LauncherError::InvalidUuid { value } => json!({ "value": value }),
+            LauncherError::ReloginRequired { account_id } => json!({ "accountId": account_id }),
+            LauncherError::NoProfile { game_pass } => json!({ "gamePass": game_pass }),
+            LauncherError::Auth { message }
+            | LauncherError::Network { message }
+            | LauncherError::Io { message }
+            | LauncherError::AgentUnavailable { message }
+            | LauncherError::InvalidSkin { message }
+            | LauncherError::Internal { message } => json!({ "message": message }),
+            LauncherError::SkinNotFound { hash } => json!({ "hash": hash }),
+            LauncherError::FileNotFound { path } => json!({ "path": path }),
+            LauncherError::UnsupportedShell { shell } => json!({ "shell": shell }),
+            LauncherError::ChecksumMismatch { file, expected, actual } => json!({
+                "file": file,
+                "expected": expected,
+                "actual": actual
+            }),
+            LauncherError::ProfileApi { status, message } => json!({
+                "status": status,
+                "message": message
+            }),
+            LauncherError::ProfileNameRejected { name, status, message } => json!({
+                "name": name,
+                "status": status,
+                "message": message
+            }),
+            LauncherError::DuplicateInstance { version_id, pid } => json!({
+                "versionId": version_id,
+                "pid": pid
+            }),
+            LauncherError::JavaNotFound { required_major, message } => json!({
+                "requiredMajor": required_major,
+                "message": message
+            }),
+            LauncherError::JavaCheckFailed { java_path, message } => json!({
+                "javaPath": java_path,
+                "message": message
+            }),
+            LauncherError::GameDirMissing { path } => json!({ "path": path }),
+            LauncherError::BisectInProgress { version_id } => json!({ "versionId": version_id }),
+            LauncherError::NoModsToBisect { mods_dir } => json!({ "modsDir": mods_dir }),
+            LauncherError::BisectRestoreFailed { files } => json!({ "files": files }),
+            LauncherError::LaunchFailed { version_id, message } => json!({
+                "versionId": version_id,
+                "message": message
+            }),
+            LauncherError::InstallFailed { component, mc_version, loader_version, message } => json!({
+                "component": component,
+                "mcVersion": mc_version,
+                "loaderVersion": loader_version,
+                "message": message
+            }),
+            LauncherError::NoAccountSelected
+            | LauncherError::LoginCancelled
+            | LauncherError::NoLicense
+            | LauncherError::NoCrashData => json!({}),
+        }
+    }
+}
+
+impl Serialize for LauncherError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(5))?;
+        map.serialize_entry("success", &false)?;
+        map.serialize_entry("code", self.code())?;
+        map.serialize_entry("messageKey", &self.message_key())?;
+        map.serialize_entry("error", &self.to_string())?;
+        map.serialize_entry("context", &self.context())?;
+        map.end()
+    }
+}
+
+impl From<reqwest::Error> for LauncherError {
+    fn from(e: reqwest::Error) -> Self {
+        LauncherError::Network { message: e.to_string() }
+    }
+}
+
+impl From<std::io::Error> for LauncherError {
+    fn from(e: std::io::Error) -> Self {
+        LauncherError::Io { message: e.to_string() }
+    }
+}
@@ -0,0 +1,166 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+
+// 后端消息目录，与前端共用 src/locales 下的语言文件
+// 消息以稳定的键标识，{name} 占位符由参数填充；当前语言来自 AppConfig.app.language
+
+const DEFAULT_LANGUAGE: &str = "zh-CN";
+
+static CATALOGUES: LazyLock<HashMap<&'static str, Value>> = LazyLock::new(|| {
+    [
+        ("zh-CN", include_str!("locales/zh-CN.json")),
+        ("en-US", include_str!("locales/en-US.json")),
+    ]
+    .into_iter()
+    .filter_map(|(language, content)| match serde_json::from_str(content) {
+        Ok(catalogue) => Some((language, catalogue)),
+        Err(e) => {
+            println!("[I18n] Failed to parse {} catalogue: {}", language, e);
+            None
+        }
+    })
+    .collect()
+});
+
+static LANGUAGE: RwLock<String> = RwLock::new(String::new());
+
+/// 切换后端消息语言，不支持的语言回退到默认语言
+pub fn set_language(language: &str) {
+    let language = if CATALOGUES.contains_key(language) { language } else { DEFAULT_LANGUAGE };
+    println!("[I18n] Backend language: {}", language);
+    if let Ok(mut current) = LANGUAGE.write() {
+        *current = language.to_string();
+    }
+}
+
+pub fn language() -> String {
+    LANGUAGE
+        .read()
+        .ok()
+        .filter(|language| !language.is_empty())
+        .map(|language| language.clone())
+        .unwrap_or_else(|| DEFAULT_LANGUAGE.to_string())
+}
+
+// 按当前语言查找，缺失时回退到默认语言
+fn lookup(key: &str) -> Option<&'static Value> {
+    let find = |language: &str| {
+        key.split('.')
+            .try_fold(CATALOGUES.get(language)?, |node, part| node.get(part))
+    };
+    find(&language()).or_else(|| find(DEFAULT_LANGUAGE))
+}
+
+pub fn interpolate(template: &str, params: &Value) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            let name = &after[..end];
+            let value = match params.get(name)? {
+                Value::String(s) => s.clone(),
+                Value::Null => return None,
+                other => other.to_string(),
+            };
+            Some((value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                result.push_str(&value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                result.push('{');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// 渲染单条消息，找不到时返回键本身
+pub fn t(key: &str, params: &Value) -> String {
+    match lookup(key) {
+        Some(Value::String(template)) => interpolate(template, params),
+        _ => key.to_string(),
+    }
+}
+
+/// 渲染字符串数组形式的消息（如崩溃建议列表）
+pub fn t_list(key: &str, params: &Value) -> Vec<String> {
+    lookup(key)
+        .and_then(|value| value.as_array())
+        .map(|items| {
+            items.iter()
+                .filter_map(|item| item.as_str())
+                .map(|template| interpolate(template, params))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 事件中携带的消息：{ messageKey, params, message }
+pub fn message(key: &str, params: Value) -> Value {
+    json!({
+        "messageKey": key,
+        "message": t(key, &params),
+        "params": params
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolates_named_params() {
+        let params = json!({ "name": "Steve", "count": 3, "ok": true });
+        assert_eq!(interpolate("{name} 有 {count} 个 ({ok})", &params), "Steve 有 3 个 (true)");
+        assert_eq!(interpolate("{name}{name}", &params), "SteveSteve");
+    }
+
+    #[test]
+    fn keeps_unknown_and_unclosed_placeholders() {
+        let params = json!({ "name": "Steve", "empty": null });
+        assert_eq!(interpolate("{missing} {empty}", &params), "{missing} {empty}");
+        assert_eq!(interpolate("{name} {", &params), "Steve {");
+        assert_eq!(interpolate("{{name}}", &params), "{Steve}");
+        assert_eq!(interpolate("没有占位符", &params), "没有占位符");
+    }
+
+    #[test]
+    fn missing_key_renders_as_key() {
+        assert_eq!(t("no.such.key", &json!({})), "no.such.key");
+        assert!(t_list("no.such.key", &json!({})).is_empty());
+    }
+
+    // 收集所有叶子节点的键
+    fn leaf_keys(value: &Value, prefix: &str, keys: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                for (name, child) in map {
+                    let key = if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) };
+                    leaf_keys(child, &key, keys);
+                }
+            }
+            _ => keys.push(prefix.to_string()),
+        }
+    }
+
+    #[test]
+    fn catalogues_have_the_same_keys() {
+        let mut zh = Vec::new();
+        let mut en = Vec::new();
+        leaf_keys(&CATALOGUES["zh-CN"], "", &mut zh);
+        leaf_keys(&CATALOGUES["en-US"], "", &mut en);
+        zh.sort();
+        en.sort();
+        let only_zh: Vec<_> = zh.iter().filter(|k| en.binary_search(k).is_err()).collect();
+        let only_en: Vec<_> = en.iter().filter(|k| zh.binary_search(k).is_err()).collect();
+        assert!(only_zh.is_empty() && only_en.is_empty(), "zh-CN only: {:?}\nen-US only: {:?}", only_zh, only_en);
+    }
+}
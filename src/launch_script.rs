@@ -0,0 +1,549 @@
+use crate::minecraft::LaunchCommand;
+use std::path::Path;
+
+// 启动脚本生成
+// 由 minecraft::resolve_launch_command 得到与实际启动完全相同的参数，按目标 shell 的规则转义后写成脚本，
+// 脚本会先创建 natives 目录并解压本地库，再切换到游戏目录启动。
+// Java 9 起参数写入脚本旁的 @argfile，避免 cmd 单行 8191 字符的限制（Forge 等版本的 classpath 很容易超出）。
+// 访问令牌默认不写入脚本，运行时从环境变量 MC_ACCESS_TOKEN 读取，未设置时提示输入
+
+pub const TOKEN_VARIABLE: &str = "MC_ACCESS_TOKEN";
+
+// 参数中的令牌先替换为只含字母数字的占位符，转义完成后再换成各 shell 的变量引用
+const TOKEN_SENTINEL: &str = "IMAGINELAUNCHERACCESSTOKEN";
+
+/// 生成的脚本；argfile 为参数文件内容，需与脚本保存在同一目录
+pub struct LaunchScript {
+    pub script: String,
+    pub argfile: Option<String>,
+}
+
+/// Java 8 不支持 @argfile
+pub fn supports_argfile(java_major: u32) -> bool {
+    java_major >= 9
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScriptShell {
+    Sh,
+    Bat,
+    PowerShell,
+}
+
+impl ScriptShell {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim_start_matches('.').to_lowercase().as_str() {
+            "sh" | "bash" | "command" => Some(ScriptShell::Sh),
+            "bat" | "cmd" => Some(ScriptShell::Bat),
+            "ps1" | "powershell" => Some(ScriptShell::PowerShell),
+            _ => None,
+        }
+    }
+
+    /// 按保存路径的扩展名判断脚本类型
+    pub fn for_path(path: &str) -> Option<Self> {
+        Path::new(path).extension().and_then(|ext| Self::from_name(&ext.to_string_lossy()))
+    }
+
+    /// 当前系统默认的脚本类型
+    pub fn native() -> Self {
+        if cfg!(windows) { ScriptShell::Bat } else { ScriptShell::Sh }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ScriptShell::Sh => "sh",
+            ScriptShell::Bat => "bat",
+            ScriptShell::PowerShell => "ps1",
+        }
+    }
+
+    fn quote(&self, arg: &str) -> String {
+        match self {
+            ScriptShell::Sh => quote_sh(arg),
+            ScriptShell::Bat => quote_bat(arg),
+            ScriptShell::PowerShell => quote_ps(arg),
+        }
+    }
+
+    // 转义后的参数中把令牌占位符换成变量引用
+    fn quote_arg(&self, arg: &str, token: Option<&str>) -> String {
+        let Some(token) = token.filter(|t| arg.contains(*t)) else {
+            return self.quote(arg);
+        };
+        let quoted = self.quote(&arg.replace(token, TOKEN_SENTINEL));
+        let reference = match self {
+            // 占位符可能位于单引号内，先闭合单引号
+            ScriptShell::Sh if quoted.starts_with('\'') => format!("'\"${}\"'", TOKEN_VARIABLE),
+            ScriptShell::Sh => format!("\"${}\"", TOKEN_VARIABLE),
+            ScriptShell::Bat => format!("%{}%", TOKEN_VARIABLE),
+            ScriptShell::PowerShell => format!("${{env:{}}}", TOKEN_VARIABLE),
+        };
+        quoted.replace(TOKEN_SENTINEL, &reference)
+    }
+
+    // 引用脚本所在目录下的参数文件，脚本移动到其他位置后仍然可用
+    fn argfile_reference(&self, name: &str) -> String {
+        match self {
+            ScriptShell::Sh => format!("\"@$SCRIPT_DIR\"/{}", quote_sh(name)),
+            ScriptShell::Bat => format!("\"@%~dp0{}\"", name.replace('%', "%%")),
+            ScriptShell::PowerShell => format!("(\"@\" + (Join-Path $PSScriptRoot {}))", quote_ps(name)),
+        }
+    }
+}
+
+// Java 参数文件中引号内的反斜杠是转义符，路径中的反斜杠需要加倍
+fn quote_argfile(arg: &str) -> String {
+    format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn quote_sh(arg: &str) -> String {
+    let safe = !arg.is_empty()
+        && arg.chars().all(|c| c.is_ascii_alphanumeric() || "_-+=.,:/@%".contains(c));
+    if safe {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+// Windows 命令行按 CommandLineToArgvW 规则加引号，批处理中的 % 还需写成 %%
+fn quote_bat(arg: &str) -> String {
+    let needs_quotes = arg.is_empty()
+        || arg.chars().any(|c| c.is_whitespace() || "\"&|<>^()!,;=%".contains(c));
+    if !needs_quotes {
+        return arg.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                // 引号前的反斜杠需要加倍，引号本身转义为 \"
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+                continue;
+            }
+            _ => {}
+        }
+        if c != '\\' {
+            quoted.push_str(&"\\".repeat(backslashes));
+            backslashes = 0;
+            if c == '%' {
+                quoted.push_str("%%");
+            } else {
+                quoted.push(c);
+            }
+        }
+    }
+    // 结尾的反斜杠后紧跟闭合引号，同样需要加倍
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+fn quote_ps(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        if matches!(c, '`' | '$' | '"') {
+            quoted.push('`');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// 生成启动脚本。token 为需要替换成 MC_ACCESS_TOKEN 变量的访问令牌，None 表示参数原样写入；
+/// argfile_name 为参数文件名，None 时所有参数直接写在脚本中；notes 作为注释写在脚本开头
+pub fn render(
+    shell: ScriptShell,
+    command: &LaunchCommand,
+    version_id: &str,
+    token: Option<&str>,
+    argfile_name: Option<&str>,
+    notes: &[String],
+) -> LaunchScript {
+    let generated_at = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+    let natives_dir = command.natives_dir.to_string_lossy().to_string();
+    let native_jars: Vec<String> = command.native_jars.iter()
+        .map(|jar| jar.to_string_lossy().to_string())
+        .collect();
+    let all_args: Vec<&str> = command.jvm_args.iter().map(String::as_str)
+        .chain(std::iter::once(command.main_class.as_str()))
+        .chain(command.game_args.iter().map(String::as_str))
+        .collect();
+    // 参数文件无法引用环境变量，令牌所在参数及其后的参数保留在命令行上，顺序不变
+    let split = match argfile_name {
+        Some(_) => token
+            .and_then(|token| all_args.iter().position(|arg| arg.contains(token)))
+            .unwrap_or(all_args.len()),
+        None => 0,
+    };
+    let (file_args, inline_args) = all_args.split_at(split);
+    let args: Vec<String> = argfile_name.map(|name| shell.argfile_reference(name)).into_iter()
+        .chain(inline_args.iter().map(|arg| shell.quote_arg(arg, token)))
+        .collect();
+    let q = |s: &str| shell.quote(s);
+
+    let mut lines: Vec<String> = Vec::new();
+    match shell {
+        ScriptShell::Sh => {
+            lines.push("#!/bin/sh".to_string());
+            lines.push("# ImagineLauncher 生成的启动脚本".to_string());
+            lines.push(format!("# 版本: {}", version_id));
+            lines.push(format!("# 生成时间: {}", generated_at));
+            lines.extend(notes.iter().map(|note| format!("# {}", note)));
+            lines.push("set -e".to_string());
+            lines.push(String::new());
+            if argfile_name.is_some() {
+                lines.push("SCRIPT_DIR=$(cd \"$(dirname \"$0\")\" && pwd)".to_string());
+            }
+            lines.push(format!("cd {}", q(&command.working_dir)));
+            if token.is_some() {
+                lines.push(String::new());
+                lines.push(format!("# 访问令牌未写入脚本，可通过环境变量 {} 提供", TOKEN_VARIABLE));
+                lines.push(format!("if [ -z \"${{{}:-}}\" ]; then", TOKEN_VARIABLE));
+                lines.push("  printf 'Access token: '".to_string());
+                lines.push("  stty -echo 2>/dev/null || true".to_string());
+                lines.push(format!("  read -r {}", TOKEN_VARIABLE));
+                lines.push("  stty echo 2>/dev/null || true".to_string());
+                lines.push("  echo".to_string());
+                lines.push("fi".to_string());
+            }
+            lines.push(String::new());
+            lines.push("# 解压 natives".to_string());
+            lines.push(format!("mkdir -p {}", q(&natives_dir)));
+            for jar in &native_jars {
+                lines.push(format!(
+                    "unzip -n -j -q {} '*.so' '*.dylib' '*.jnilib' '*.dll' -x 'META-INF/*' -d {} 2>/dev/null || true",
+                    q(jar), q(&natives_dir)
+                ));
+            }
+            lines.push(String::new());
+            lines.push(format!("exec {} \\", q(&command.java_path)));
+            let last = args.len().saturating_sub(1);
+            for (i, arg) in args.iter().enumerate() {
+                lines.push(format!("  {}{}", arg, if i == last { "" } else { " \\" }));
+            }
+        }
+        ScriptShell::Bat => {
+            lines.push("@echo off".to_string());
+            lines.push("chcp 65001 >nul".to_string());
+            lines.push("REM ImagineLauncher 生成的启动脚本".to_string());
+            lines.push(format!("REM 版本: {}", version_id));
+            lines.push(format!("REM 生成时间: {}", generated_at));
+            lines.extend(notes.iter().map(|note| format!("REM {}", note)));
+            lines.push(String::new());
+            lines.push(format!("cd /d {}", q(&command.working_dir)));
+            if token.is_some() {
+                lines.push(String::new());
+                lines.push(format!("REM 访问令牌未写入脚本，可通过环境变量 {} 提供", TOKEN_VARIABLE));
+                lines.push(format!("if not defined {0} set /p \"{0}=Access token: \"", TOKEN_VARIABLE));
+            }
+            lines.push(String::new());
+            // Windows 10 起自带 bsdtar：-k 不覆盖已有文件，-s 去掉目录层级
+            lines.push("REM 解压 natives".to_string());
+            lines.push(format!("if not exist {0} mkdir {0}", q(&natives_dir)));
+            for jar in &native_jars {
+                lines.push(format!(
+                    "tar -xkf {} -C {} -s \",.*/,,\" \"*.dll\" 2>nul",
+                    q(jar), q(&natives_dir)
+                ));
+            }
+            lines.push(String::new());
+            lines.push(format!("{} ^", q(&command.java_path)));
+            let last = args.len().saturating_sub(1);
+            for (i, arg) in args.iter().enumerate() {
+                lines.push(format!("  {}{}", arg, if i == last { "" } else { " ^" }));
+            }
+            lines.push(String::new());
+            lines.push("pause".to_string());
+        }
+        ScriptShell::PowerShell => {
+            lines.push("# ImagineLauncher 生成的启动脚本".to_string());
+            lines.push(format!("# 版本: {}", version_id));
+            lines.push(format!("# 生成时间: {}", generated_at));
+            lines.extend(notes.iter().map(|note| format!("# {}", note)));
+            lines.push("$ErrorActionPreference = 'Stop'".to_string());
+            lines.push(String::new());
+            lines.push(format!("Set-Location -LiteralPath {}", q(&command.working_dir)));
+            if token.is_some() {
+                lines.push(String::new());
+                lines.push(format!("# 访问令牌未写入脚本，可通过环境变量 {} 提供", TOKEN_VARIABLE));
+                lines.push(format!("if (-not $env:{}) {{", TOKEN_VARIABLE));
+                lines.push("  $secure = Read-Host 'Access token' -AsSecureString".to_string());
+                lines.push(format!(
+                    "  $env:{} = [Runtime.InteropServices.Marshal]::PtrToStringAuto([Runtime.InteropServices.Marshal]::SecureStringToBSTR($secure))",
+                    TOKEN_VARIABLE
+                ));
+                lines.push("}".to_string());
+            }
+            lines.push(String::new());
+            lines.push("# 解压 natives".to_string());
+            lines.push(format!("$NativesDir = {}", q(&natives_dir)));
+            lines.push("New-Item -ItemType Directory -Force -Path $NativesDir | Out-Null".to_string());
+            if !native_jars.is_empty() {
+                lines.push("Add-Type -AssemblyName System.IO.Compression.FileSystem".to_string());
+                lines.push("$NativeJars = @(".to_string());
+                for jar in &native_jars {
+                    lines.push(format!("  {}", q(jar)));
+                }
+                lines.push(")".to_string());
+                lines.push("foreach ($jar in $NativeJars) {".to_string());
+                lines.push("  $zip = [IO.Compression.ZipFile]::OpenRead($jar)".to_string());
+                lines.push("  foreach ($entry in $zip.Entries) {".to_string());
+                lines.push("    if ($entry.FullName -like 'META-INF/*' -or $entry.Name -notmatch '\\.(dll|so|dylib|jnilib)$') { continue }".to_string());
+                lines.push("    $dest = Join-Path $NativesDir $entry.Name".to_string());
+                lines.push("    if (-not (Test-Path -LiteralPath $dest)) { [IO.Compression.ZipFileExtensions]::ExtractToFile($entry, $dest) }".to_string());
+                lines.push("  }".to_string());
+                lines.push("  $zip.Dispose()".to_string());
+                lines.push("}".to_string());
+            }
+            lines.push(String::new());
+            lines.push("$Arguments = @(".to_string());
+            for arg in &args {
+                lines.push(format!("  {}", arg));
+            }
+            lines.push(")".to_string());
+            lines.push(format!("& {} @Arguments", q(&command.java_path)));
+        }
+    }
+
+    let line_ending = if shell == ScriptShell::Sh { "\n" } else { "\r\n" };
+    let mut script = lines.join(line_ending);
+    script.push_str(line_ending);
+    // Windows PowerShell 5.1 只在有 BOM 时按 UTF-8 读取脚本
+    if shell == ScriptShell::PowerShell {
+        script.insert(0, '\u{feff}');
+    }
+    let argfile = argfile_name.map(|_| {
+        let mut content = file_args.iter().map(|arg| quote_argfile(arg)).collect::<Vec<_>>().join(line_ending);
+        content.push_str(line_ending);
+        content
+    });
+    LaunchScript { script, argfile }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const TOKEN: &str = "eyJsecret.token.value";
+
+    #[test]
+    fn quotes_sh() {
+        let cases = [
+            ("plain-arg_1.0", "plain-arg_1.0"),
+            ("-Dkey=value:/path@host,100%", "-Dkey=value:/path@host,100%"),
+            ("", "''"),
+            ("with space", "'with space'"),
+            ("it's", r"'it'\''s'"),
+            (r#"say "hi""#, r#"'say "hi"'"#),
+            ("wow!", "'wow!'"),
+            ("a^b", "'a^b'"),
+            ("$HOME", "'$HOME'"),
+            ("`id`", "'`id`'"),
+            (r"C:\dir\", r"'C:\dir\'"),
+        ];
+        for (arg, expected) in cases {
+            assert_eq!(quote_sh(arg), expected, "arg: {:?}", arg);
+        }
+    }
+
+    #[test]
+    fn quotes_bat() {
+        let cases = [
+            ("plain-arg_1.0", "plain-arg_1.0"),
+            ("", r#""""#),
+            ("with space", r#""with space""#),
+            ("it's", "it's"),
+            (r#"say "hi""#, r#""say \"hi\"""#),
+            ("100%", r#""100%%""#),
+            ("wow!", r#""wow!""#),
+            ("a^b", r#""a^b""#),
+            ("a&b|c", r#""a&b|c""#),
+            ("$HOME", "$HOME"),
+            ("`id`", "`id`"),
+            // 不需要引号时反斜杠原样保留
+            (r"C:\dir\", r"C:\dir\"),
+            // 结尾的反斜杠在闭合引号前加倍
+            (r"C:\my dir\", r#""C:\my dir\\""#),
+            (r"C:\my dir\\", r#""C:\my dir\\\\""#),
+            // 引号前的反斜杠加倍后再转义引号
+            (r#"a\"b c"#, r#""a\\\"b c""#),
+            // 其他位置的反斜杠不变
+            (r"C:\a b\c", r#""C:\a b\c""#),
+        ];
+        for (arg, expected) in cases {
+            assert_eq!(quote_bat(arg), expected, "arg: {:?}", arg);
+        }
+    }
+
+    #[test]
+    fn quotes_powershell() {
+        let cases = [
+            ("plain", r#""plain""#),
+            ("", r#""""#),
+            ("with space", r#""with space""#),
+            ("it's", r#""it's""#),
+            (r#"say "hi""#, r#""say `"hi`"""#),
+            ("100%", r#""100%""#),
+            ("wow!", r#""wow!""#),
+            ("a^b", r#""a^b""#),
+            ("$HOME", r#""`$HOME""#),
+            ("`id`", r#""``id``""#),
+            (r"C:\dir\", r#""C:\dir\""#),
+        ];
+        for (arg, expected) in cases {
+            assert_eq!(quote_ps(arg), expected, "arg: {:?}", arg);
+        }
+    }
+
+    #[test]
+    fn quotes_argfile() {
+        let cases = [
+            ("plain", r#""plain""#),
+            ("", r#""""#),
+            ("with space", r#""with space""#),
+            ("it's", r#""it's""#),
+            (r#"say "hi""#, r#""say \"hi\"""#),
+            ("# not a comment", r##""# not a comment""##),
+            ("100% $HOME `id` !^", r#""100% $HOME `id` !^""#),
+            (r"C:\my dir\", r#""C:\\my dir\\""#),
+        ];
+        for (arg, expected) in cases {
+            assert_eq!(quote_argfile(arg), expected, "arg: {:?}", arg);
+        }
+    }
+
+    #[test]
+    fn token_becomes_variable_reference() {
+        let cases = [
+            (ScriptShell::Sh, TOKEN, r#""$MC_ACCESS_TOKEN""#),
+            (ScriptShell::Sh, "Bearer eyJsecret.token.value", r#"'Bearer '"$MC_ACCESS_TOKEN"''"#),
+            (ScriptShell::Sh, "token:eyJsecret.token.value", r#"token:"$MC_ACCESS_TOKEN""#),
+            (ScriptShell::Bat, TOKEN, "%MC_ACCESS_TOKEN%"),
+            (ScriptShell::Bat, "Bearer eyJsecret.token.value", r#""Bearer %MC_ACCESS_TOKEN%""#),
+            (ScriptShell::PowerShell, TOKEN, r#""${env:MC_ACCESS_TOKEN}""#),
+            (ScriptShell::PowerShell, "Bearer eyJsecret.token.value", r#""Bearer ${env:MC_ACCESS_TOKEN}""#),
+        ];
+        for (shell, arg, expected) in cases {
+            assert_eq!(shell.quote_arg(arg, Some(TOKEN)), expected, "{:?} arg: {:?}", shell, arg);
+        }
+        // 没有令牌时按普通参数转义
+        assert_eq!(ScriptShell::Sh.quote_arg(TOKEN, None), TOKEN);
+        assert_eq!(ScriptShell::Sh.quote_arg("--username", Some(TOKEN)), "--username");
+    }
+
+    #[test]
+    fn shell_from_name_and_path() {
+        assert_eq!(ScriptShell::from_name("BASH"), Some(ScriptShell::Sh));
+        assert_eq!(ScriptShell::from_name(".cmd"), Some(ScriptShell::Bat));
+        assert_eq!(ScriptShell::from_name("powershell"), Some(ScriptShell::PowerShell));
+        assert_eq!(ScriptShell::from_name("fish"), None);
+        assert_eq!(ScriptShell::for_path("/tmp/launch.ps1"), Some(ScriptShell::PowerShell));
+        assert_eq!(ScriptShell::for_path("launch"), None);
+    }
+
+    fn command() -> LaunchCommand {
+        LaunchCommand {
+            java_path: "/opt/java 21/bin/java".to_string(),
+            jvm_args: vec![
+                "-Xmx4G".to_string(),
+                "-Djava.library.path=/home/alex/.minecraft/versions/1.20.1/natives".to_string(),
+                "-cp".to_string(),
+                "/home/alex/.minecraft/libraries/a.jar:/home/alex/My Mods/b.jar".to_string(),
+            ],
+            main_class: "net.minecraft.client.main.Main".to_string(),
+            game_args: vec![
+                "--username".to_string(),
+                "Alex".to_string(),
+                "--accessToken".to_string(),
+                TOKEN.to_string(),
+                "--userType".to_string(),
+                "msa".to_string(),
+            ],
+            working_dir: "/home/alex/.minecraft".to_string(),
+            natives_dir: PathBuf::from("/home/alex/.minecraft/versions/1.20.1/natives"),
+            native_jars: vec![PathBuf::from("/home/alex/.minecraft/libraries/lwjgl-natives.jar")],
+        }
+    }
+
+    #[test]
+    fn argfile_holds_arguments_before_token() {
+        let cases = [
+            (ScriptShell::Sh, r#""@$SCRIPT_DIR"/launch.args"#, r#""$MC_ACCESS_TOKEN""#),
+            (ScriptShell::Bat, r#""@%~dp0launch.args""#, "%MC_ACCESS_TOKEN%"),
+            (ScriptShell::PowerShell, r#"("@" + (Join-Path $PSScriptRoot "launch.args"))"#, r#""${env:MC_ACCESS_TOKEN}""#),
+        ];
+        for (shell, reference, variable) in cases {
+            let rendered = render(shell, &command(), "1.20.1", Some(TOKEN), Some("launch.args"), &[]);
+            let argfile = rendered.argfile.expect("argfile");
+
+            assert!(!rendered.script.contains(TOKEN), "{:?} script leaks token", shell);
+            assert!(!argfile.contains(TOKEN), "{:?} argfile leaks token", shell);
+            assert!(rendered.script.contains(reference), "{:?} script: {}", shell, rendered.script);
+            assert!(rendered.script.contains(variable), "{:?} script: {}", shell, rendered.script);
+
+            // 令牌之前的参数（含主类）都在参数文件中，之后的留在脚本里且顺序不变
+            let line_ending = if shell == ScriptShell::Sh { "\n" } else { "\r\n" };
+            let lines: Vec<&str> = argfile.split(line_ending).filter(|l| !l.is_empty()).collect();
+            assert_eq!(lines.len(), 8, "{:?} argfile: {}", shell, argfile);
+            assert_eq!(lines[0], r#""-Xmx4G""#);
+            assert_eq!(lines[3], r#""/home/alex/.minecraft/libraries/a.jar:/home/alex/My Mods/b.jar""#);
+            assert_eq!(lines[4], r#""net.minecraft.client.main.Main""#);
+            assert_eq!(lines[7], r#""--accessToken""#);
+            assert!(!rendered.script.contains("net.minecraft.client.main.Main"));
+            let token_at = rendered.script.find(variable).unwrap();
+            let user_type_at = rendered.script.find("--userType").unwrap();
+            assert!(rendered.script.find(reference).unwrap() < token_at && token_at < user_type_at);
+        }
+    }
+
+    #[test]
+    fn sh_argfile_is_resolved_before_cd() {
+        let rendered = render(ScriptShell::Sh, &command(), "1.20.1", Some(TOKEN), Some("launch.args"), &[]);
+        let script_dir = rendered.script.find("SCRIPT_DIR=$(cd \"$(dirname \"$0\")\" && pwd)").unwrap();
+        let cd = rendered.script.find("cd /home/alex/.minecraft").unwrap();
+        assert!(script_dir < cd);
+    }
+
+    #[test]
+    fn inline_arguments_without_argfile() {
+        for shell in [ScriptShell::Sh, ScriptShell::Bat, ScriptShell::PowerShell] {
+            let rendered = render(shell, &command(), "1.20.1", Some(TOKEN), None, &[]);
+
+            assert!(rendered.argfile.is_none());
+            assert!(!rendered.script.contains(TOKEN), "{:?} script leaks token", shell);
+            assert!(rendered.script.contains("net.minecraft.client.main.Main"));
+            assert!(!rendered.script.contains("launch.args"));
+        }
+    }
+
+    #[test]
+    fn included_token_is_written_as_is() {
+        let rendered = render(ScriptShell::Sh, &command(), "1.20.1", None, Some("launch.args"), &[]);
+
+        assert!(rendered.argfile.unwrap().contains(TOKEN));
+        assert!(!rendered.script.contains("MC_ACCESS_TOKEN"));
+    }
+
+    #[test]
+    fn notes_are_written_as_comments() {
+        let notes = vec!["missing agent".to_string()];
+        let cases = [
+            (ScriptShell::Sh, "# missing agent"),
+            (ScriptShell::Bat, "REM missing agent"),
+            (ScriptShell::PowerShell, "# missing agent"),
+        ];
+        for (shell, comment) in cases {
+            let rendered = render(shell, &command(), "1.20.1", Some(TOKEN), None, &notes);
+            assert!(rendered.script.contains(comment), "{:?} script: {}", shell, rendered.script);
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::error::LauncherError;
 use crate::state::AppState;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -24,6 +25,10 @@ pub struct LauncherSettings {
     pub launcher_behavior: String,
     #[serde(default = "default_instances_layout")]
     pub instances_layout: String,
+    #[serde(default)]
+    pub crash_share: CrashShareSettings,
+    #[serde(default)]
+    pub redaction: RedactionSettings,
 }
 
 fn default_launcher_behavior() -> String { "keep-front".to_string() }
@@ -41,6 +46,51 @@ impl Default for MemorySettings {
     }
 }
 
+// 崩溃报告分享使用的粘贴服务
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashShareSettings {
+    // "mclogs"：mclo.gs 兼容的 API（可以是自建实例）；"raw"：以纯文本 POST，响应为链接或 { "url": ... }
+    pub backend: String,
+    pub api_url: String,
+}
+
+impl Default for CrashShareSettings {
+    fn default() -> Self {
+        Self {
+            backend: "mclogs".to_string(),
+            api_url: "https://api.mclo.gs".to_string(),
+        }
+    }
+}
+
+// 导出和分享崩溃报告时的脱敏规则
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RedactionSettings {
+    pub tokens: bool,
+    // UUID 常用于排查存档和服务器问题，默认保留
+    pub uuids: bool,
+    pub usernames: bool,
+    pub home_dir: bool,
+    pub ip_addresses: bool,
+    // 额外的正则表达式，匹配内容替换为 <redacted>
+    pub custom_patterns: Vec<String>,
+}
+
+impl Default for RedactionSettings {
+    fn default() -> Self {
+        Self {
+            tokens: true,
+            uuids: false,
+            usernames: true,
+            home_dir: true,
+            ip_addresses: true,
+            custom_patterns: Vec::new(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct WindowSize {
     pub width: u32,
@@ -82,7 +132,7 @@ pub struct VersionSettings {
 #[tauri::command]
 pub async fn get_launcher_settings(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
-) -> Result<LauncherSettings, String> {
+) -> Result<LauncherSettings, LauncherError> {
     let state = state.lock().await;
     let settings = state.launcher_settings.clone();
     println!("[Launcher] get_launcher_settings: minecraft_dir={:?}, java_path={:?}", 
@@ -95,7 +145,7 @@ pub async fn get_launcher_settings(
 pub async fn save_launcher_settings(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     settings: serde_json::Value,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     println!("[Launcher] ========== save_launcher_settings ==========");
     println!("[Launcher] Received settings: {}", serde_json::to_string_pretty(&settings).unwrap_or_default());
     
@@ -162,14 +212,28 @@ pub async fn save_launcher_settings(
         state.launcher_settings.instances_layout = layout.to_string();
     }
     
+    if let Some(crash_share) = settings.get("crashShare").or(settings.get("crash_share")) {
+        match serde_json::from_value::<CrashShareSettings>(crash_share.clone()) {
+            Ok(crash_share) => state.launcher_settings.crash_share = crash_share,
+            Err(e) => println!("[Launcher] Failed to parse crashShare: {}", e),
+        }
+    }
+    
+    if let Some(redaction) = settings.get("redaction") {
+        match serde_json::from_value::<RedactionSettings>(redaction.clone()) {
+            Ok(redaction) => state.launcher_settings.redaction = redaction,
+            Err(e) => println!("[Launcher] Failed to parse redaction: {}", e),
+        }
+    }
+    
     println!("[Launcher] Final java_path after merge: {:?}", state.launcher_settings.java_path);
     
-    state.save_launcher_settings().map_err(|e| e.to_string())?;
+    state.save_launcher_settings().map_err(|e| LauncherError::Io { message: e.to_string() })?;
     Ok(serde_json::json!({ "success": true }))
 }
 
 #[tauri::command]
-pub async fn get_default_minecraft_dir() -> Result<String, String> {
+pub async fn get_default_minecraft_dir() -> Result<String, LauncherError> {
     let dir = get_default_mc_dir();
     Ok(dir.to_string_lossy().to_string())
 }
@@ -197,7 +261,7 @@ pub fn get_default_mc_dir() -> std::path::PathBuf {
 }
 
 #[tauri::command]
-pub async fn validate_minecraft_dir(dir_path: String) -> Result<bool, String> {
+pub async fn validate_minecraft_dir(dir_path: String) -> Result<bool, LauncherError> {
     let path = std::path::Path::new(&dir_path);
     Ok(path.exists() && path.join("versions").exists())
 }
@@ -205,7 +269,7 @@ pub async fn validate_minecraft_dir(dir_path: String) -> Result<bool, String> {
 #[tauri::command]
 pub async fn select_minecraft_dir(
     _state: tauri::State<'_, Arc<Mutex<AppState>>>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     // 前端使用 dialog plugin 选择目录后调用 set_minecraft_dir
     Ok(serde_json::json!({
         "success": false,
@@ -217,7 +281,7 @@ pub async fn select_minecraft_dir(
 pub async fn set_minecraft_dir(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     dir_path: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let path = std::path::Path::new(&dir_path);
     if !path.exists() || !path.join("versions").exists() {
         return Ok(serde_json::json!({
@@ -228,7 +292,7 @@ pub async fn set_minecraft_dir(
     
     let mut state = state.lock().await;
     state.launcher_settings.minecraft_dir = Some(dir_path.clone());
-    state.save_launcher_settings().map_err(|e| e.to_string())?;
+    state.save_launcher_settings().map_err(|e| LauncherError::Io { message: e.to_string() })?;
     
     Ok(serde_json::json!({
         "success": true,
@@ -239,7 +303,7 @@ pub async fn set_minecraft_dir(
 #[tauri::command]
 pub async fn get_minecraft_dir(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
-) -> Result<String, String> {
+) -> Result<String, LauncherError> {
     let state = state.lock().await;
     Ok(state.launcher_settings.minecraft_dir.clone()
         .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string()))
@@ -247,7 +311,7 @@ pub async fn get_minecraft_dir(
 
 
 #[tauri::command]
-pub async fn get_system_memory() -> Result<serde_json::Value, String> {
+pub async fn get_system_memory() -> Result<serde_json::Value, LauncherError> {
     use sysinfo::System;
     let sys = System::new_all();
     
@@ -264,32 +328,257 @@ pub async fn get_system_memory() -> Result<serde_json::Value, String> {
 
 // Java 检测
 #[tauri::command]
-pub async fn detect_java() -> Result<serde_json::Value, String> {
-    let java_list = find_java_installations().await;
+pub async fn detect_java(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+) -> Result<serde_json::Value, LauncherError> {
+    let app_state = state.lock().await;
+    let cache_dir = app_state.get_cache_dir();
+    let mc_dir = app_state.launcher_settings.minecraft_dir.clone()
+        .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string());
+    let memory_max = app_state.launcher_settings.memory.max;
+    drop(app_state);
+    
+    let mut java_list = find_java_installations(&cache_dir, &mc_dir).await;
+    for java in &mut java_list {
+        java.heap_limited = is_heap_limited(java, memory_max);
+    }
+    
     Ok(serde_json::json!({
         "success": true,
         "javaList": java_list
     }))
 }
 
-#[derive(Debug, Serialize)]
-struct JavaInfo {
-    path: String,
-    version: String,
-    name: String,
+// 32 位 JVM 可用地址空间有限，超过这个值的 -Xmx 通常无法启动
+pub(crate) const MAX_32BIT_HEAP_MB: u32 = 1536;
+
+pub(crate) fn is_heap_limited(java: &JavaInfo, memory_max: u32) -> bool {
+    !java.is_64bit && memory_max > MAX_32BIT_HEAP_MB
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct JavaInfo {
+    pub path: String,
+    pub version: String,
+    pub name: String,
+    pub major_version: u32,
+    pub is_64bit: bool,
+    pub vendor: String,
+    #[serde(default)]
+    pub full_version: String,
+    #[serde(default)]
+    pub arch: String,
+    #[serde(default)]
+    pub heap_limited: bool,
+}
+
+// 检测结果缓存，以可执行文件修改时间为键，避免每次都启动 JVM
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct JavaCache {
+    #[serde(default)]
+    entries: HashMap<String, CachedJava>,
+    #[serde(skip)]
+    used: std::collections::HashSet<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedJava {
+    mtime: u64,
+    info: JavaInfo,
+}
+
+impl JavaCache {
+    fn load(cache_dir: &Path) -> Self {
+        std::fs::read_to_string(cache_dir.join("java_cache.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+    
+    fn save(&mut self, cache_dir: &Path) {
+        // 清理本次扫描未找到的 Java
+        let used = &self.used;
+        self.entries.retain(|key, _| used.contains(key));
+        if let Ok(content) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::create_dir_all(cache_dir);
+            let _ = std::fs::write(cache_dir.join("java_cache.json"), content);
+        }
+    }
+}
+
+fn executable_mtime(path: &Path) -> Option<u64> {
+    std::fs::metadata(path).ok()?
+        .modified().ok()?
+        .duration_since(std::time::UNIX_EPOCH).ok()
+        .map(|d| d.as_secs())
+}
+
+// 在 PATH 中查找可执行文件的真实路径
+fn find_in_path(name: &str) -> Option<std::path::PathBuf> {
+    let name = if cfg!(target_os = "windows") {
+        format!("{}.exe", name)
+    } else {
+        name.to_string()
+    };
+    
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(&name))
+            .find(|p| p.is_file())
+    })
+}
+
+// 带缓存的检测：可执行文件未变化时直接使用上次的结果
+async fn probe_java_cached(java_path: &str, name: &str, cache: &mut JavaCache) -> Option<JavaInfo> {
+    let real_path = if Path::new(java_path).is_absolute() {
+        Path::new(java_path).to_path_buf()
+    } else {
+        find_in_path(java_path)?
+    };
+    let key = real_path.to_string_lossy().to_string();
+    let mtime = executable_mtime(&real_path);
+    cache.used.insert(key.clone());
+    
+    if let (Some(mtime), Some(cached)) = (mtime, cache.entries.get(&key)) {
+        if cached.mtime == mtime {
+            let mut info = cached.info.clone();
+            info.path = java_path.to_string();
+            info.name = name.to_string();
+            return Some(info);
+        }
+    }
+    
+    let info = probe_java(java_path, name).await?;
+    if let Some(mtime) = mtime {
+        cache.entries.insert(key, CachedJava { mtime, info: info.clone() });
+    }
+    Some(info)
+}
+
+/// 检测单个 Java，复用 java_cache.json 中的结果；不清理缓存中的其他条目
+pub(crate) async fn probe_java_with_cache(cache_dir: &Path, java_path: &str, name: &str) -> Option<JavaInfo> {
+    let mut cache = JavaCache::load(cache_dir);
+    let info = probe_java_cached(java_path, name, &mut cache).await;
+    cache.used.extend(cache.entries.keys().cloned());
+    cache.save(cache_dir);
+    info
+}
+
+/// 运行 java -XshowSettings:properties -version，解析版本、架构、位数和发行商
+pub(crate) async fn probe_java(java_path: &str, name: &str) -> Option<JavaInfo> {
+    let output = get_java_output(java_path, &["-XshowSettings:properties", "-version"]).await.ok()?;
+    let props = parse_java_properties(&output);
+    
+    let version = props.get("java.version").cloned()
+        .or_else(|| parse_version_string(&output))?;
+    let arch = props.get("os.arch").cloned().unwrap_or_default();
+    let is_64bit = match props.get("sun.arch.data.model").map(|s| s.as_str()) {
+        Some("64") => true,
+        Some("32") => false,
+        _ => output.contains("64-Bit") || arch.contains("64"),
+    };
+    // 优先使用 java.vendor 属性，旧版本 JVM 没有时退回整个输出
+    let vendor_text = match props.get("java.vendor") {
+        Some(vendor) => format!("{} {}", vendor, props.get("java.vm.name").cloned().unwrap_or_default()),
+        None => output.clone(),
+    };
+    
+    Some(JavaInfo {
+        path: java_path.to_string(),
+        major_version: parse_java_version(&version),
+        full_version: props.get("java.runtime.version").cloned().unwrap_or_else(|| version.clone()),
+        version,
+        name: name.to_string(),
+        is_64bit,
+        vendor: detect_java_vendor(java_path, &vendor_text),
+        arch,
+        heap_limited: false,
+    })
+}
+
+// 解析 -XshowSettings:properties 输出中的 "key = value" 行
+fn parse_java_properties(output: &str) -> HashMap<String, String> {
+    output.lines()
+        .filter_map(|line| {
+            let (key, value) = line.trim().split_once(" = ")?;
+            Some((key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+fn detect_java_vendor(java_path: &str, output: &str) -> String {
+    let lower = output.to_lowercase();
+    let path_lower = java_path.to_lowercase().replace('\\', "/");
+    
+    // 官方启动器/本启动器下载的运行时
+    if path_lower.contains("/runtime/java-runtime") || path_lower.contains("/runtime/jre-") {
+        return "Mojang".to_string();
+    }
+    
+    let vendors = [
+        ("microsoft", "Microsoft"),
+        ("temurin", "Eclipse Temurin"),
+        ("adoptium", "Eclipse Temurin"),
+        ("adoptopenjdk", "AdoptOpenJDK"),
+        ("zulu", "Azul Zulu"),
+        ("azul", "Azul Zulu"),
+        ("corretto", "Amazon Corretto"),
+        ("amazon", "Amazon Corretto"),
+        ("liberica", "BellSoft Liberica"),
+        ("bellsoft", "BellSoft Liberica"),
+        ("graalvm", "GraalVM"),
+        ("openj9", "IBM Semeru"),
+        ("ibm", "IBM Semeru"),
+        ("international business machines", "IBM Semeru"),
+        ("java(tm)", "Oracle"),
+        ("oracle", "Oracle"),
+    ];
+    
+    vendors.iter()
+        .find(|(key, _)| lower.contains(key))
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| "OpenJDK".to_string())
+}
+
+// 发行商优先级，数值越小越优先
+fn vendor_priority(vendor: &str) -> u32 {
+    match vendor {
+        "Mojang" => 0,
+        "Microsoft" => 1,
+        "Eclipse Temurin" | "AdoptOpenJDK" => 2,
+        "Azul Zulu" => 3,
+        "Amazon Corretto" | "BellSoft Liberica" => 4,
+        "Oracle" | "OpenJDK" => 5,
+        _ => 6,
+    }
+}
+
+/// 从检测结果中为指定主版本号挑选最合适的 Java
+/// 优先主版本号完全匹配，其次 64 位，最后按发行商排序；
+/// 没有完全匹配时选择不低于要求的最小版本
+pub(crate) fn select_best_java(java_list: &[JavaInfo], required_major: u32) -> Option<&JavaInfo> {
+    java_list.iter().min_by_key(|java| {
+        let major_rank = if java.major_version == required_major {
+            0
+        } else if java.major_version > required_major {
+            java.major_version - required_major
+        } else {
+            // 低于要求的版本基本无法运行
+            1000 + required_major - java.major_version
+        };
+        (major_rank, !java.is_64bit, vendor_priority(&java.vendor))
+    })
 }
 
-async fn find_java_installations() -> Vec<JavaInfo> {
+pub(crate) async fn find_java_installations(cache_dir: &Path, mc_dir: &str) -> Vec<JavaInfo> {
     let mut results = Vec::new();
     let mut seen_paths = std::collections::HashSet::new();
+    let mut cache = JavaCache::load(cache_dir);
     
     // 检查 PATH 中的 java
-    if let Ok(version) = get_java_version_cmd("java").await {
-        results.push(JavaInfo {
-            path: "java".to_string(),
-            version,
-            name: "System Java".to_string(),
-        });
+    if let Some(info) = probe_java_cached("java", "System Java", &mut cache).await {
+        results.push(info);
         seen_paths.insert("java".to_string());
     }
     
@@ -303,12 +592,8 @@ async fn find_java_installations() -> Vec<JavaInfo> {
         if java_bin.exists() {
             let path_str = java_bin.to_string_lossy().to_string();
             if !seen_paths.contains(&path_str) {
-                if let Ok(version) = get_java_version_cmd(&path_str).await {
-                    results.push(JavaInfo {
-                        path: path_str.clone(),
-                        version,
-                        name: "JAVA_HOME".to_string(),
-                    });
+                if let Some(info) = probe_java_cached(&path_str, "JAVA_HOME", &mut cache).await {
+                    results.push(info);
                     seen_paths.insert(path_str);
                 }
             }
@@ -336,7 +621,7 @@ async fn find_java_installations() -> Vec<JavaInfo> {
             for dir in &java_dirs {
                 let search_dir = base_path.join(dir);
                 if search_dir.exists() {
-                    scan_java_dir_recursive(&search_dir, &mut results, &mut seen_paths, 2).await;
+                    scan_java_dir_recursive(&search_dir, &mut results, &mut seen_paths, &mut cache, 2).await;
                 }
             }
         }
@@ -349,7 +634,7 @@ async fn find_java_installations() -> Vec<JavaInfo> {
         
         for launcher_path in mc_launchers {
             if launcher_path.exists() {
-                scan_java_dir_recursive(&launcher_path, &mut results, &mut seen_paths, 4).await;
+                scan_java_dir_recursive(&launcher_path, &mut results, &mut seen_paths, &mut cache, 4).await;
             }
         }
     }
@@ -364,7 +649,7 @@ async fn find_java_installations() -> Vec<JavaInfo> {
         for path in search_paths {
             let p = Path::new(&path);
             if p.exists() {
-                scan_java_dir_recursive(p, &mut results, &mut seen_paths, 3).await;
+                scan_java_dir_recursive(p, &mut results, &mut seen_paths, &mut cache, 3).await;
             }
         }
     }
@@ -375,21 +660,105 @@ async fn find_java_installations() -> Vec<JavaInfo> {
         for path in search_paths {
             let p = Path::new(path);
             if p.exists() {
-                scan_java_dir_recursive(p, &mut results, &mut seen_paths, 2).await;
+                scan_java_dir_recursive(p, &mut results, &mut seen_paths, &mut cache, 2).await;
             }
         }
+        
+        // Flatpak 和 Snap 打包的 JDK
+        let home = dirs::home_dir().unwrap_or_default();
+        let flatpak_roots = [Path::new("/var/lib/flatpak/runtime").to_path_buf(), home.join(".local/share/flatpak/runtime")];
+        for jvm_dir in package_jvm_dirs(&flatpak_roots, Path::new("/snap")) {
+            scan_java_dir_recursive(&jvm_dir, &mut results, &mut seen_paths, &mut cache, 1).await;
+        }
     }
     
+    // 版本管理工具和其他启动器的 Java
+    for (path, depth) in extra_java_search_paths(mc_dir) {
+        if path.exists() {
+            scan_java_dir_recursive(&path, &mut results, &mut seen_paths, &mut cache, depth).await;
+        }
+    }
+    
+    cache.save(cache_dir);
+    
     // 按版本排序（新版本在前）
-    results.sort_by(|a, b| {
-        let va = parse_java_version(&a.version);
-        let vb = parse_java_version(&b.version);
-        vb.cmp(&va)
-    });
+    results.sort_by_key(|j| std::cmp::Reverse(j.major_version));
     
     results
 }
 
+// Flatpak 和 Snap 中 JDK 所在的 jvm 目录，只展开已知的布局而不遍历整个目录树:
+// Flatpak: runtime/<扩展>/<架构>/<分支>/active/files/jvm/<jdk>
+// Snap: /snap/<名称>/current/usr/lib/jvm/<jdk>
+#[cfg(target_os = "linux")]
+fn package_jvm_dirs(flatpak_roots: &[std::path::PathBuf], snap_root: &Path) -> Vec<std::path::PathBuf> {
+    let child_dirs = |dir: &Path| -> Vec<std::path::PathBuf> {
+        std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect()
+    };
+    
+    let mut dirs = Vec::new();
+    for root in flatpak_roots {
+        for extension in child_dirs(root) {
+            for arch in child_dirs(&extension) {
+                for branch in child_dirs(&arch) {
+                    dirs.push(branch.join("active").join("files").join("jvm"));
+                }
+            }
+        }
+    }
+    for snap in child_dirs(snap_root) {
+        dirs.push(snap.join("current").join("usr").join("lib").join("jvm"));
+    }
+    dirs.retain(|dir| dir.is_dir());
+    dirs
+}
+
+// 跨平台的额外搜索位置: (目录, 搜索深度)
+fn extra_java_search_paths(mc_dir: &str) -> Vec<(std::path::PathBuf, u32)> {
+    let home = dirs::home_dir().unwrap_or_default();
+    let mut paths = vec![
+        // 版本管理工具
+        (home.join(".sdkman").join("candidates").join("java"), 1),
+        (home.join(".asdf").join("installs").join("java"), 1),
+        (home.join(".jenv").join("versions"), 1),
+        (home.join(".jdks"), 1),
+        // Minecraft 运行时: runtime/<component>/<os>/<component>/bin/java
+        (Path::new(mc_dir).join("runtime"), 4),
+        // 其他启动器
+        (home.join(".hmcl").join("java"), 3),
+        (home.join(".lunarclient").join("jre"), 3),
+    ];
+    
+    #[cfg(target_os = "windows")]
+    {
+        let appdata = Path::new(&std::env::var("APPDATA").unwrap_or_default()).to_path_buf();
+        paths.push((appdata.join("PrismLauncher").join("java"), 3));
+        paths.push((appdata.join(".hmcl").join("java"), 3));
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        let support = home.join("Library").join("Application Support");
+        paths.push((support.join("minecraft").join("runtime"), 4));
+        paths.push((support.join("PrismLauncher").join("java"), 3));
+    }
+    
+    #[cfg(target_os = "linux")]
+    {
+        paths.push((home.join(".minecraft").join("runtime"), 4));
+        paths.push((home.join(".local/share/PrismLauncher/java"), 3));
+        paths.push((home.join(".local/share/multimc/java"), 3));
+    }
+    
+    paths
+}
+
 fn parse_java_version(version: &str) -> u32 {
     // 解析 Java 版本号，如 "1.8.0_301" -> 8, "17.0.1" -> 17, "21" -> 21
     let v = version.trim_start_matches("1.");
@@ -403,6 +772,7 @@ async fn scan_java_dir_recursive(
     dir: &Path, 
     results: &mut Vec<JavaInfo>, 
     seen: &mut std::collections::HashSet<String>,
+    cache: &mut JavaCache,
     max_depth: u32
 ) {
     if max_depth == 0 {
@@ -431,49 +801,50 @@ async fn scan_java_dir_recursive(
                 if java_bin.exists() {
                     let path_str = java_bin.to_string_lossy().to_string();
                     if !seen.contains(&path_str) {
-                        if let Ok(version) = get_java_version_cmd(&path_str).await {
-                            let name = path.file_name()
-                                .map(|n| n.to_string_lossy().to_string())
-                                .unwrap_or_else(|| "Unknown".to_string());
-                            results.push(JavaInfo {
-                                path: path_str.clone(),
-                                version,
-                                name,
-                            });
+                        let name = path.file_name()
+                            .map(|n| n.to_string_lossy().to_string())
+                            .unwrap_or_else(|| "Unknown".to_string());
+                        if let Some(info) = probe_java_cached(&path_str, &name, cache).await {
+                            results.push(info);
                             seen.insert(path_str);
                         }
                     }
                 } else {
                     // 递归搜索子目录
-                    Box::pin(scan_java_dir_recursive(&path, results, seen, max_depth - 1)).await;
+                    Box::pin(scan_java_dir_recursive(&path, results, seen, cache, max_depth - 1)).await;
                 }
             }
         }
     }
 }
 
-async fn get_java_version_cmd(java_path: &str) -> Result<String, String> {
+async fn get_java_output(java_path: &str, args: &[&str]) -> Result<String, String> {
     let output = tokio::process::Command::new(java_path)
-        .arg("-version")
+        .args(args)
         .output()
         .await
         .map_err(|e| e.to_string())?;
     
     let stderr = String::from_utf8_lossy(&output.stderr);
     let stdout = String::from_utf8_lossy(&output.stdout);
-    let combined = format!("{}{}", stderr, stdout);
-    
+    Ok(format!("{}{}", stderr, stdout))
+}
+
+fn parse_version_string(output: &str) -> Option<String> {
     let re = regex::Regex::new(r#"version "([^"]+)""#).unwrap();
-    if let Some(caps) = re.captures(&combined) {
-        return Ok(caps.get(1).map(|m| m.as_str().to_string()).unwrap_or_default());
-    }
-    
-    Err("无法解析Java版本".to_string())
+    re.captures(output)
+        .and_then(|caps| caps.get(1))
+        .map(|m| m.as_str().to_string())
+}
+
+async fn get_java_version_cmd(java_path: &str) -> Result<String, String> {
+    let output = get_java_output(java_path, &["-version"]).await?;
+    parse_version_string(&output).ok_or_else(|| "无法解析Java版本".to_string())
 }
 
 
 #[tauri::command]
-pub async fn select_java_path() -> Result<serde_json::Value, String> {
+pub async fn select_java_path() -> Result<serde_json::Value, LauncherError> {
     // 前端使用 dialog plugin 选择文件
     Ok(serde_json::json!({
         "success": false,
@@ -485,7 +856,7 @@ pub async fn select_java_path() -> Result<serde_json::Value, String> {
 pub async fn set_java_path(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     java_path: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     println!("[Java] Setting Java path: {}", java_path);
     
     let version = get_java_version_cmd(&java_path).await;
@@ -507,7 +878,7 @@ pub async fn set_java_path(
     println!("[Java] Saving settings...");
     state.save_launcher_settings().map_err(|e| {
         println!("[Java] Save error: {}", e);
-        e.to_string()
+        LauncherError::Io { message: e.to_string() }
     })?;
     
     println!("[Java] Java path saved successfully");
@@ -522,7 +893,7 @@ pub async fn set_java_path(
 #[tauri::command]
 pub async fn get_java_path(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
-) -> Result<Option<String>, String> {
+) -> Result<Option<String>, LauncherError> {
     let state = state.lock().await;
     let java_path = state.launcher_settings.java_path.clone();
     println!("[Java] get_java_path: {:?}", java_path);
@@ -533,47 +904,47 @@ pub async fn get_java_path(
 pub async fn get_required_java_version(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     version_id: String,
-) -> Result<Option<u32>, String> {
+) -> Result<Option<u32>, LauncherError> {
     let state = state.lock().await;
     let mc_dir = state.launcher_settings.minecraft_dir.clone()
         .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string());
     
-    let json_path = std::path::Path::new(&mc_dir)
-        .join("versions")
-        .join(&version_id)
-        .join(format!("{}.json", version_id));
-    
-    if !json_path.exists() {
-        return Ok(None);
-    }
-    
-    let content = std::fs::read_to_string(&json_path).map_err(|e| e.to_string())?;
-    let json: serde_json::Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
-    
-    if let Some(java_version) = json.get("javaVersion").and_then(|v| v.get("majorVersion")) {
-        return Ok(java_version.as_u64().map(|v| v as u32));
+    Ok(resolve_required_java_major(&mc_dir, &version_id))
+}
+
+// 按发布时间推断所需 Java，用于没有 javaVersion 字段的旧版本 JSON
+// (起始时间, 主版本号)，按时间倒序
+const JAVA_RELEASE_TABLE: &[(&str, u32)] = &[
+    ("2024-04-03", 21), // 24w14a
+    ("2021-11-16", 17), // 1.18-pre2
+    ("2021-05-12", 16), // 21w19a
+];
+
+/// 解析版本所需的 Java 主版本号
+/// 沿继承链读取 javaVersion.majorVersion，没有时按 releaseTime 查表
+pub fn resolve_required_java_major(mc_dir: &str, version_id: &str) -> Option<u32> {
+    if let Some(major) = crate::downloads::find_java_version_info(mc_dir, version_id)
+        .and_then(|v| v["majorVersion"].as_u64())
+    {
+        return Some(major as u32);
     }
     
-    // 根据版本推断
-    let id_lower = version_id.to_lowercase();
-    if id_lower.contains("1.20") || id_lower.contains("1.21") {
-        return Ok(Some(21));
-    }
-    if id_lower.contains("1.18") || id_lower.contains("1.19") {
-        return Ok(Some(17));
-    }
-    if id_lower.contains("1.17") {
-        return Ok(Some(16));
-    }
+    let release_time = crate::downloads::find_inherited_field(mc_dir, version_id, "releaseTime")?;
+    let date = release_time.as_str()?.get(..10)?;
     
-    Ok(Some(8))
+    Some(
+        JAVA_RELEASE_TABLE.iter()
+            .find(|(since, _)| date >= *since)
+            .map(|(_, major)| *major)
+            .unwrap_or(8)
+    )
 }
 
 // 启动器行为
 #[tauri::command]
 pub async fn get_launcher_behavior(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
-) -> Result<String, String> {
+) -> Result<String, LauncherError> {
     let state = state.lock().await;
     Ok(state.launcher_settings.launcher_behavior.clone())
 }
@@ -582,10 +953,10 @@ pub async fn get_launcher_behavior(
 pub async fn set_launcher_behavior(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     behavior: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let mut state = state.lock().await;
     state.launcher_settings.launcher_behavior = behavior;
-    state.save_launcher_settings().map_err(|e| e.to_string())?;
+    state.save_launcher_settings().map_err(|e| LauncherError::Io { message: e.to_string() })?;
     Ok(serde_json::json!({ "success": true }))
 }
 
@@ -593,7 +964,7 @@ pub async fn set_launcher_behavior(
 pub async fn execute_launcher_behavior(
     window: tauri::Window,
     behavior: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     println!("[Launcher] Executing behavior: {}", behavior);
     match behavior.as_str() {
         "auto-hide" => {
@@ -620,7 +991,7 @@ pub async fn execute_launcher_behavior(
 #[tauri::command]
 pub async fn get_instances_layout(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
-) -> Result<String, String> {
+) -> Result<String, LauncherError> {
     let state = state.lock().await;
     Ok(state.launcher_settings.instances_layout.clone())
 }
@@ -629,9 +1000,104 @@ pub async fn get_instances_layout(
 pub async fn set_instances_layout(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     layout: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let mut state = state.lock().await;
     state.launcher_settings.instances_layout = layout;
-    state.save_launcher_settings().map_err(|e| e.to_string())?;
+    state.save_launcher_settings().map_err(|e| LauncherError::Io { message: e.to_string() })?;
     Ok(serde_json::json!({ "success": true }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn java(major_version: u32, is_64bit: bool, vendor: &str) -> JavaInfo {
+        JavaInfo {
+            path: format!("/jvm/{}-{}-{}/bin/java", vendor, major_version, is_64bit),
+            version: major_version.to_string(),
+            name: String::new(),
+            major_version,
+            is_64bit,
+            vendor: vendor.to_string(),
+            full_version: String::new(),
+            arch: String::new(),
+            heap_limited: false,
+        }
+    }
+
+    #[test]
+    fn select_prefers_exact_major() {
+        let list = [java(8, true, "Oracle"), java(21, true, "Mojang"), java(17, true, "OpenJDK")];
+        assert_eq!(select_best_java(&list, 17).unwrap().major_version, 17);
+        assert_eq!(select_best_java(&list, 8).unwrap().major_version, 8);
+    }
+
+    #[test]
+    fn select_prefers_64bit_then_vendor() {
+        let list = [java(17, false, "Mojang"), java(17, true, "OpenJDK"), java(17, true, "Eclipse Temurin")];
+        let best = select_best_java(&list, 17).unwrap();
+        assert!(best.is_64bit);
+        assert_eq!(best.vendor, "Eclipse Temurin");
+    }
+
+    #[test]
+    fn select_falls_back_to_nearest_newer_major() {
+        let list = [java(8, true, "Oracle"), java(21, true, "OpenJDK"), java(25, true, "Mojang")];
+        assert_eq!(select_best_java(&list, 17).unwrap().major_version, 21);
+        // 只有更低的版本时选择最接近的
+        let list = [java(8, true, "Oracle"), java(11, true, "OpenJDK")];
+        assert_eq!(select_best_java(&list, 17).unwrap().major_version, 11);
+        assert!(select_best_java(&[], 17).is_none());
+    }
+
+    #[test]
+    fn parses_java_properties() {
+        let output = "Property settings:\n    \
+            java.version = 17.0.8\n    \
+            java.vendor = Eclipse Adoptium\n    \
+            os.arch = amd64\n    \
+            sun.boot.library.path = C:\\Program Files\\Java\\bin\n    \
+            line.separator = \\n \n\n\
+            openjdk version \"17.0.8\" 2023-07-18\n";
+        let props = parse_java_properties(output);
+        assert_eq!(props.get("java.version").map(String::as_str), Some("17.0.8"));
+        assert_eq!(props.get("java.vendor").map(String::as_str), Some("Eclipse Adoptium"));
+        assert_eq!(props.get("sun.boot.library.path").map(String::as_str), Some("C:\\Program Files\\Java\\bin"));
+        assert!(!props.contains_key("Property settings:"));
+        assert!(!props.keys().any(|k| k.starts_with("openjdk")));
+    }
+
+    #[test]
+    fn parses_java_versions() {
+        assert_eq!(parse_java_version("1.8.0_301"), 8);
+        assert_eq!(parse_java_version("17.0.1"), 17);
+        assert_eq!(parse_java_version("21"), 21);
+        assert_eq!(parse_version_string("java version \"1.8.0_391\"\nJava(TM) SE"), Some("1.8.0_391".to_string()));
+    }
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn package_jvm_dirs_follow_flatpak_and_snap_layouts() {
+        let root = std::env::temp_dir().join(format!("launcher-test-{}-packages", std::process::id()));
+        let flatpak = root.join("flatpak");
+        let snap = root.join("snap");
+        let dirs = [
+            flatpak.join("org.freedesktop.Sdk.Extension.openjdk17/x86_64/23.08/active/files/jvm/openjdk-17/bin"),
+            flatpak.join("org.gnome.Platform/x86_64/45/active/files/lib"),
+            snap.join("openjdk/current/usr/lib/jvm/java-21-openjdk/bin"),
+            snap.join("core22/current/usr/lib"),
+            // 不符合布局的深层目录不会被扫描
+            root.join("flatpak/org.example.Deep/x86_64/1/other/files/jvm"),
+        ];
+        for dir in &dirs {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        
+        let mut found = package_jvm_dirs(&[flatpak.clone(), root.join("missing")], &snap);
+        found.sort();
+        let _ = std::fs::remove_dir_all(&root);
+        assert_eq!(found, [
+            flatpak.join("org.freedesktop.Sdk.Extension.openjdk17/x86_64/23.08/active/files/jvm"),
+            snap.join("openjdk/current/usr/lib/jvm"),
+        ]);
+    }
+}
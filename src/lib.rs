@@ -1,10 +1,23 @@
 mod accounts;
+mod bisect;
 mod config;
+mod crash_analyzer;
+mod crash_report;
+mod crash_share;
+mod credentials;
 mod downloads;
+mod error;
+mod i18n;
+mod launch_script;
 mod launcher;
 mod minecraft;
 mod mods;
+mod redaction;
+mod skin_render;
+mod skin_server;
+mod skins;
 mod state;
+mod yggdrasil;
 
 use state::AppState;
 use std::sync::Arc;
@@ -26,6 +39,7 @@ pub fn run() {
         .plugin(tauri_plugin_deep_link::init())
         .setup(|app| {
             let app_state = AppState::new(app.handle().clone())?;
+            let data_dir = app_state.data_dir.clone();
             let state = Arc::new(Mutex::new(app_state));
             app.manage(state.clone());
             
@@ -63,6 +77,20 @@ pub fn run() {
                 }
             });
             
+            // 恢复上次未正常结束的模组排查改动的模组状态
+            tauri::async_runtime::spawn(async move {
+                bisect::restore_interrupted(&data_dir).await;
+            });
+            
+            // 模组排查：进程监控记录一轮结果后，在这里启动下一轮
+            let app_handle = app.handle().clone();
+            app.listen(bisect::NEXT_ROUND_EVENT, move |_| {
+                let app_handle = app_handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    bisect::next_round(&app_handle).await;
+                });
+            });
+            
             // 启动游戏进程监控任务
             let app_handle = app.handle().clone();
             let monitor_state = state.clone();
@@ -125,15 +153,36 @@ pub fn run() {
             accounts::remove_account,
             accounts::switch_account,
             accounts::update_account_username,
+            accounts::migrate_offline_player_data,
             accounts::microsoft_login,
             accounts::manual_auth_callback,
+            accounts::microsoft_device_login,
+            accounts::cancel_microsoft_login,
             accounts::refresh_microsoft_account,
+            accounts::check_profile_name,
+            accounts::create_minecraft_profile,
+            accounts::get_name_change_info,
+            accounts::change_profile_name,
+            yggdrasil::resolve_auth_server,
+            yggdrasil::add_yggdrasil_account,
+            yggdrasil::select_yggdrasil_profile,
+            yggdrasil::refresh_yggdrasil_account,
             accounts::get_account_avatar,
             accounts::refresh_account_avatar,
             // 皮肤管理
             accounts::select_skin_file,
             accounts::set_offline_skin,
             accounts::get_skin_path,
+            skins::get_microsoft_textures,
+            skins::upload_microsoft_skin,
+            skins::reset_microsoft_skin,
+            skins::equip_microsoft_cape,
+            skins::get_skin_library,
+            skins::add_skin_to_library,
+            skins::remove_skin_from_library,
+            skins::apply_library_skin,
+            skins::set_offline_cape,
+            skin_render::render_skin,
             // 启动器设置
             launcher::get_launcher_settings,
             launcher::save_launcher_settings,
@@ -155,6 +204,7 @@ pub fn run() {
             minecraft::rename_version,
             // 游戏启动
             minecraft::launch_game,
+            minecraft::validate_java_config,
             minecraft::get_game_status,
             minecraft::kill_game,
             minecraft::get_running_instances,
@@ -170,8 +220,14 @@ pub fn run() {
             // 崩溃报告
             get_crash_report_data,
             minecraft::export_crash_report,
+            crash_report::get_crash_report,
+            crash_share::share_crash_report,
+            bisect::start_mod_bisect,
+            bisect::abort_mod_bisect,
+            bisect::get_mod_bisect_status,
             minecraft::generate_launch_script,
             // 下载管理
+            downloads::get_version_manifest,
             downloads::download_minecraft_version,
             downloads::install_fabric,
             downloads::install_forge,
@@ -180,6 +236,7 @@ pub fn run() {
             downloads::get_forge_mc_versions,
             downloads::get_forge_versions,
             downloads::complete_game_files,
+            downloads::install_java_runtime,
             // Mods 管理
             mods::scan_mods,
             mods::toggle_mod,
@@ -202,29 +259,42 @@ pub fn run() {
             mods::test_optifine_api,
             mods::download_mod,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                // 退出前写完排队中的账户凭据；其他命令仍持有状态锁时不等待，避免退出卡住
+                let state = app_handle.state::<Arc<Mutex<AppState>>>();
+                match state.try_lock() {
+                    Ok(state) => state.credentials.flush(),
+                    Err(_) => println!("[App] State is busy on exit, skipping credential flush"),
+                };
+            }
+        });
 }
 
 // 处理 deep link 认证回调
 async fn handle_auth_callback(
     state: &Arc<Mutex<AppState>>,
     callback_url: &str,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, error::LauncherError> {
     println!("[Auth Callback] Processing URL: {}", callback_url);
     
     // 解析 URL 获取 code
-    let url = url::Url::parse(callback_url).map_err(|e| e.to_string())?;
+    let url = url::Url::parse(callback_url)
+        .map_err(|e| error::LauncherError::Auth { message: e.to_string() })?;
     
     let code = url.query_pairs()
         .find(|(k, _)| k == "code")
         .map(|(_, v)| v.to_string())
-        .ok_or("未找到授权码")?;
+        .ok_or_else(|| error::LauncherError::Auth {
+            message: i18n::t("account.errors.missingAuthCode", &serde_json::json!({})),
+        })?;
     
     println!("[Auth Callback] Found code: {}...", &code[..code.len().min(20)]);
     
     // 调用 accounts 模块完成认证
-    let account = accounts::complete_auth_with_code(&code).await?;
+    let account = accounts::complete_auth_with_code(state, &code).await?;
     
     let mut app_state = state.lock().await;
     
@@ -239,7 +309,7 @@ async fn handle_auth_callback(
     }
     
     app_state.accounts_data.current_account = Some(account.id.clone());
-    app_state.save_accounts().map_err(|e| e.to_string())?;
+    app_state.save_accounts().map_err(|e| error::LauncherError::Io { message: e.to_string() })?;
     
     Ok(serde_json::json!({
         "success": true,
@@ -278,7 +348,7 @@ async fn window_close(window: tauri::Window) {
 #[tauri::command]
 async fn get_config(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
-) -> Result<config::AppConfig, String> {
+) -> Result<config::AppConfig, error::LauncherError> {
     let state = state.lock().await;
     let config = state.config.clone();
     println!("[Config] get_config called");
@@ -291,7 +361,7 @@ async fn get_config(
 async fn save_config(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     new_config: config::AppConfig,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, error::LauncherError> {
     println!("[Config] ========== save_config called ==========");
     println!("[Config] App name: {}", new_config.app.name);
     println!("[Config] App language: {}", new_config.app.language);
@@ -300,12 +370,13 @@ async fn save_config(
     println!("[Config] Theme gradient_colors: {:?}", new_config.theme.gradient_colors);
     
     let mut state = state.lock().await;
+    i18n::set_language(&new_config.app.language);
     state.config = new_config.clone();
     
     println!("[Config] Saving to disk...");
     state.save_config().map_err(|e| {
         println!("[Config] Save error: {}", e);
-        e.to_string()
+        error::LauncherError::Io { message: e.to_string() }
     })?;
     
     println!("[Config] Config saved successfully to disk");
@@ -319,7 +390,7 @@ async fn save_config(
 #[tauri::command]
 async fn get_crash_report_data(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
-) -> Result<Option<serde_json::Value>, String> {
+) -> Result<Option<serde_json::Value>, error::LauncherError> {
     let state = state.lock().await;
     Ok(state.last_crash_data.clone())
 }
@@ -119,6 +119,27 @@ async function initEventListeners() {
     });
   });
   
+  // 模组排查进度：提示并转发给页面
+  await listen('mod-bisect', (event) => {
+    console.log('[Main] Mod bisect:', event.payload);
+    const { messageKey, message, session, params } = event.payload;
+    // 有模组未能恢复时同样按错误提示
+    const failed = messageKey === 'bisect.launchFailed' || params?.failed?.length > 0;
+    showToast(message, failed ? 'error' : 'success');
+    updateBisectBar(message, session);
+    
+    document.querySelectorAll('iframe').forEach(iframe => {
+      if (iframe?.contentWindow) {
+        iframe.contentWindow.postMessage({
+          action: 'mod-bisect',
+          messageKey,
+          message,
+          session
+        }, '*');
+      }
+    });
+  });
+  
   // 监听配置更新事件（来自设置页面）
   await listen('config-updated', async (event) => {
     console.log('[Main] Config updated:', event.payload);
@@ -158,10 +179,19 @@ async function initEventListeners() {
     showToast('登录成功！', 'success');
   });
   
+  // 监听登录过期事件（启动前刷新令牌失败）
+  await listen('auth-relogin-required', (event) => {
+    console.log('[Main] Relogin required:', event.payload);
+    showToast(`${event.payload.username} 的登录已过期，请重新登录`, 'error');
+    showPage('account');
+    setActiveNav('account');
+  });
+  
   // 监听认证错误事件
   await listen('auth-error', (event) => {
     console.log('[Main] Auth error:', event.payload);
-    showToast('登录失败: ' + event.payload, 'error');
+    // 负载为后端 LauncherError 序列化后的对象
+    showToast('登录失败: ' + (event.payload?.error || event.payload), 'error');
   });
 }
 
@@ -530,6 +560,24 @@ function showToast(message, type = 'success') {
   }, 2500);
 }
 
+// 模组排查进行中时显示进度和中止按钮
+function updateBisectBar(message, session) {
+  let bar = document.getElementById('bisect-bar');
+  if (session?.status !== 'running') {
+    bar?.remove();
+    return;
+  }
+  if (!bar) {
+    bar = document.createElement('div');
+    bar.id = 'bisect-bar';
+    bar.className = 'bisect-bar';
+    bar.innerHTML = '<i class="ri-search-eye-line"></i><span></span><button>中止</button>';
+    bar.querySelector('button').addEventListener('click', () => invoke?.('abort_mod_bisect'));
+    document.body.appendChild(bar);
+  }
+  bar.querySelector('span').textContent = message;
+}
+
 // 导出给 iframe 使用
 window.tauriInvoke = (...args) => invoke?.(...args);
 window.showToast = showToast;
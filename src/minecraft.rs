@@ -1,3 +1,4 @@
+use crate::error::LauncherError;
 use crate::launcher::get_default_mc_dir;
 use crate::state::AppState;
 use serde::{Deserialize, Serialize};
@@ -41,7 +42,7 @@ pub struct LogEntry {
 #[tauri::command]
 pub async fn scan_versions(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let state = state.lock().await;
     let mc_dir = state.launcher_settings.minecraft_dir.clone()
         .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string());
@@ -50,7 +51,7 @@ pub async fn scan_versions(
     if !versions_dir.exists() {
         return Ok(serde_json::json!({
             "success": false,
-            "error": "versions 目录不存在"
+            "error": crate::i18n::t("instances.errors.versionsDirMissing", &serde_json::json!({}))
         }));
     }
     
@@ -138,7 +139,7 @@ fn detect_loader(json: &serde_json::Value, version_id: &str) -> String {
 pub async fn get_version_info(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     version_id: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let state = state.lock().await;
     let mc_dir = state.launcher_settings.minecraft_dir.clone()
         .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string());
@@ -151,12 +152,13 @@ pub async fn get_version_info(
     if !json_path.exists() {
         return Ok(serde_json::json!({
             "success": false,
-            "error": "版本不存在"
+            "error": crate::i18n::t("instances.errors.versionNotFound", &serde_json::json!({}))
         }));
     }
     
-    let content = std::fs::read_to_string(&json_path).map_err(|e| e.to_string())?;
-    let json: serde_json::Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    let content = std::fs::read_to_string(&json_path)?;
+    let json: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| LauncherError::Io { message: format!("{}: {}", json_path.display(), e) })?;
     
     Ok(serde_json::json!({
         "success": true,
@@ -169,7 +171,7 @@ pub async fn rename_version(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     old_id: String,
     new_id: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let state = state.lock().await;
     let mc_dir = state.launcher_settings.minecraft_dir.clone()
         .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string());
@@ -182,7 +184,7 @@ pub async fn rename_version(
     if !old_dir.exists() {
         return Ok(serde_json::json!({
             "success": false,
-            "error": "原版本不存在"
+            "error": crate::i18n::t("instances.errors.versionNotFound", &serde_json::json!({}))
         }));
     }
     
@@ -190,7 +192,7 @@ pub async fn rename_version(
     if new_dir.exists() {
         return Ok(serde_json::json!({
             "success": false,
-            "error": "目标版本名称已存在"
+            "error": crate::i18n::t("instances.errors.versionExists", &serde_json::json!({}))
         }));
     }
     
@@ -198,7 +200,7 @@ pub async fn rename_version(
     if new_id.is_empty() || new_id.contains('/') || new_id.contains('\\') || new_id.contains(':') {
         return Ok(serde_json::json!({
             "success": false,
-            "error": "无效的版本名称"
+            "error": crate::i18n::t("instances.errors.invalidVersionName", &serde_json::json!({}))
         }));
     }
     
@@ -206,7 +208,7 @@ pub async fn rename_version(
     if let Err(e) = std::fs::rename(&old_dir, &new_dir) {
         return Ok(serde_json::json!({
             "success": false,
-            "error": format!("重命名文件夹失败: {}", e)
+            "error": crate::i18n::t("instances.errors.renameFailed", &serde_json::json!({ "message": e.to_string() }))
         }));
     }
     
@@ -247,7 +249,7 @@ pub async fn rename_version(
 #[tauri::command]
 pub async fn get_running_instances(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
-) -> Result<Vec<serde_json::Value>, String> {
+) -> Result<Vec<serde_json::Value>, LauncherError> {
     let state = state.lock().await;
     let mut instances = state.running_instances.write().await;
     
@@ -277,8 +279,24 @@ pub async fn get_running_instances(
     Ok(result)
 }
 
+// 强制结束游戏进程（Windows 上连同子进程）
+pub(crate) fn terminate_process(pid: u32) {
+    #[cfg(target_os = "windows")]
+    {
+        let _ = std::process::Command::new("taskkill")
+            .args(["/pid", &pid.to_string(), "/f", "/t"])
+            .spawn();
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = std::process::Command::new("kill")
+            .args(["-9", &pid.to_string()])
+            .spawn();
+    }
+}
+
 // 检查进程是否存活
-fn is_process_alive(pid: u32) -> bool {
+pub(crate) fn is_process_alive(pid: u32) -> bool {
     #[cfg(target_os = "windows")]
     {
         use std::process::Command;
@@ -308,7 +326,7 @@ fn is_process_alive(pid: u32) -> bool {
 pub async fn get_instance_logs(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     pid: u32,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let state = state.lock().await;
     let instances = state.running_instances.read().await;
     
@@ -321,7 +339,7 @@ pub async fn get_instance_logs(
     
     Ok(serde_json::json!({
         "success": false,
-        "error": "实例不存在"
+        "error": crate::i18n::t("instances.errors.instanceNotFound", &serde_json::json!({}))
     }))
 }
 
@@ -329,26 +347,13 @@ pub async fn get_instance_logs(
 pub async fn kill_instance(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     pid: u32,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let state = state.lock().await;
     let mut instances = state.running_instances.write().await;
     
     if let Some(instance) = instances.get_mut(&pid) {
         if !instance.exited {
-            // 终止进程
-            #[cfg(target_os = "windows")]
-            {
-                let _ = std::process::Command::new("taskkill")
-                    .args(&["/pid", &pid.to_string(), "/f", "/t"])
-                    .spawn();
-            }
-            #[cfg(not(target_os = "windows"))]
-            {
-                let _ = std::process::Command::new("kill")
-                    .args(&["-9", &pid.to_string()])
-                    .spawn();
-            }
-            
+            terminate_process(pid);
             instance.exited = true;
             return Ok(serde_json::json!({ "success": true }));
         }
@@ -356,7 +361,7 @@ pub async fn kill_instance(
     
     Ok(serde_json::json!({
         "success": false,
-        "error": "实例不存在或已退出"
+        "error": crate::i18n::t("instances.errors.instanceNotRunning", &serde_json::json!({}))
     }))
 }
 
@@ -364,7 +369,7 @@ pub async fn kill_instance(
 pub async fn check_duplicate_instance(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     version_id: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let state = state.lock().await;
     let instances = state.running_instances.read().await;
     
@@ -383,7 +388,7 @@ pub async fn check_duplicate_instance(
 #[tauri::command]
 pub async fn get_game_status(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let state = state.lock().await;
     let mut instances = state.running_instances.write().await;
     
@@ -411,24 +416,13 @@ pub async fn get_game_status(
 #[tauri::command]
 pub async fn kill_game(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let state = state.lock().await;
     let mut instances = state.running_instances.write().await;
     
     for (pid, instance) in instances.iter_mut() {
         if !instance.exited {
-            #[cfg(target_os = "windows")]
-            {
-                let _ = std::process::Command::new("taskkill")
-                    .args(&["/pid", &pid.to_string(), "/f", "/t"])
-                    .spawn();
-            }
-            #[cfg(not(target_os = "windows"))]
-            {
-                let _ = std::process::Command::new("kill")
-                    .args(&["-9", &pid.to_string()])
-                    .spawn();
-            }
+            terminate_process(*pid);
             instance.exited = true;
         }
     }
@@ -445,7 +439,7 @@ pub async fn launch_game(
     version_id: String,
     account_id: Option<String>,
     force_new_instance: Option<bool>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     use tauri::Emitter;
     
     println!("[Launch] ========== Starting launch_game ==========");
@@ -453,12 +447,11 @@ pub async fn launch_game(
     println!("[Launch] account_id: {:?}", account_id);
     println!("[Launch] force_new_instance: {:?}", force_new_instance);
     
-    let send_step = |step: &str, status: &str, message: &str| {
-        let _ = app_handle.emit("launch-step", serde_json::json!({
-            "step": step,
-            "status": status,
-            "message": message
-        }));
+    let send_step = |step: &str, status: &str, key: &str, params: serde_json::Value| {
+        emit_launch_step(&app_handle, step, status, crate::i18n::message(key, params));
+    };
+    let send_error = |step: &str, error: &LauncherError| {
+        emit_launch_step(&app_handle, step, "error", crate::i18n::message(&error.message_key(), error.context()));
     };
     
     let app_state = state.lock().await;
@@ -471,18 +464,13 @@ pub async fn launch_game(
         let instances = app_state.running_instances.read().await;
         for (pid, inst) in instances.iter() {
             if inst.version_id == version_id && !inst.exited {
-                return Ok(serde_json::json!({
-                    "success": false,
-                    "error": "duplicate_instance",
-                    "runningPid": pid,
-                    "message": format!("{} 已经在运行中", version_id)
-                }));
+                return Err(LauncherError::DuplicateInstance { version_id, pid: *pid });
             }
         }
     }
     
     // 步骤1: 检测账户
-    send_step("check-account", "active", "正在验证账户...");
+    send_step("check-account", "active", "launch.steps.checkingAccount", serde_json::json!({}));
     
     let account = if let Some(aid) = &account_id {
         app_state.accounts_data.accounts.iter().find(|a| &a.id == aid).cloned()
@@ -495,76 +483,165 @@ pub async fn launch_game(
     let account = match account {
         Some(a) => a,
         None => {
-            send_step("check-account", "error", "未选择账户");
-            return Ok(serde_json::json!({
-                "success": false,
-                "error": "请先选择一个账户"
-            }));
+            let error = LauncherError::NoAccountSelected;
+            send_error("check-account", &error);
+            return Err(error);
         }
     };
-    send_step("check-account", "done", &format!("账户: {}", account.username));
-    
-    // 步骤2: 检测 Java
-    send_step("check-java", "active", "正在检测 Java...");
-    
-    // 优先使用版本独立设置的 Java 路径
-    let version_java_path = app_state.launcher_settings.version_settings
-        .get(&version_id)
-        .and_then(|vs| vs.java_path.clone());
     
-    println!("[Launch] Version-specific Java path: {:?}", version_java_path);
-    println!("[Launch] Global Java path: {:?}", app_state.launcher_settings.java_path);
-    
-    let java_path = version_java_path
-        .or_else(|| app_state.launcher_settings.java_path.clone())
-        .filter(|p| !p.is_empty());
+    // 微软账户令牌即将过期时静默刷新
+    let is_yggdrasil = account.account_type == "yggdrasil";
+    let (app_state, account) = if is_yggdrasil || crate::accounts::needs_token_refresh(&account) {
+        send_step("check-account", "active", "launch.steps.refreshingToken", serde_json::json!({}));
+        // 刷新期间释放锁
+        drop(app_state);
+        
+        // 外置登录令牌没有过期时间，每次启动前向认证服务器校验
+        let refresh_result = if is_yggdrasil {
+            crate::yggdrasil::ensure_valid_token(&account).await
+        } else {
+            crate::accounts::refresh_microsoft_tokens(&account).await
+        };
+        
+        match refresh_result {
+            Ok(refreshed) => {
+                let mut app_state = state.lock().await;
+                if let Some(acc) = app_state.accounts_data.accounts.iter_mut().find(|a| a.id == refreshed.id) {
+                    *acc = refreshed.clone();
+                }
+                if let Err(e) = app_state.save_accounts() {
+                    println!("[Launch] Failed to save refreshed account: {}", e);
+                }
+                (app_state, refreshed)
+            }
+            Err(crate::accounts::RefreshError::InvalidGrant(e)) => {
+                println!("[Launch] Refresh token rejected: {}", e);
+                let error = LauncherError::ReloginRequired { account_id: Some(account.id.clone()) };
+                send_error("check-account", &error);
+                let _ = app_handle.emit("auth-relogin-required", serde_json::json!({
+                    "accountId": account.id,
+                    "username": account.username,
+                    "reason": e
+                }));
+                return Err(error);
+            }
+            Err(crate::accounts::RefreshError::Other(e)) => {
+                // 网络问题时继续使用旧令牌，单人游戏不受影响
+                println!("[Launch] Token refresh failed, using existing token: {}", e);
+                (state.lock().await, account)
+            }
+        }
+    } else {
+        (app_state, account)
+    };
     
-    let java_path = match java_path {
-        Some(p) => {
-            println!("[Launch] Using Java path: {}", p);
-            p
-        },
-        None => {
-            println!("[Launch] Java path is None or empty!");
-            send_step("check-java", "error", "未设置 Java 路径");
-            return Ok(serde_json::json!({
-                "success": false,
-                "error": "请先选择 Java"
-            }));
+    let (app_state, agent_args) = if is_yggdrasil || crate::skin_server::needs_skin_server(&account) {
+        send_step("check-account", "active", "launch.steps.preparingAgent", serde_json::json!({}));
+        let data_dir = app_state.data_dir.clone();
+        drop(app_state);
+        
+        match build_agent_args(&data_dir, &account).await {
+            Ok(args) => (state.lock().await, args),
+            Err(error) => {
+                send_error("check-account", &error);
+                return Err(error);
+            }
         }
+    } else {
+        (app_state, Vec::new())
     };
-    send_step("check-java", "done", &format!("Java: {}", java_path));
+    send_step("check-account", "done", "launch.steps.accountReady", serde_json::json!({ "username": account.username }));
     
-    // 步骤3: 检查游戏目录
-    send_step("check-dir", "active", "正在检查游戏目录...");
+    // 步骤2: 检测 Java
+    send_step("check-java", "active", "launch.steps.checkingJava", serde_json::json!({}));
     
     let mc_dir = app_state.launcher_settings.minecraft_dir.clone()
         .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string());
+    let required_major = crate::launcher::resolve_required_java_major(&mc_dir, &version_id);
+    println!("[Launch] Required Java major version: {:?}", required_major);
+    
+    // 检测和下载 Java 期间释放锁，避免阻塞其他命令
+    let settings = app_state.launcher_settings.clone();
+    let cache_dir = app_state.get_cache_dir();
+    drop(app_state);
+    
+    let java_path = match select_java_for_version(&settings, &cache_dir, &mc_dir, &version_id, required_major).await {
+        Ok(path) => path,
+        Err(best) => {
+            // 没有匹配的 Java 时自动下载版本所需的官方运行时
+            println!("[Launch] No matching Java installed, using managed runtime");
+            
+            let component = crate::downloads::required_java_component(&mc_dir, &version_id);
+            send_step("check-java", "active", "launch.steps.downloadingJava", serde_json::json!({ "component": component }));
+            
+            match crate::downloads::ensure_java_runtime(&mc_dir, &component, &app_handle).await {
+                Ok(path) => path.to_string_lossy().to_string(),
+                Err(e) => {
+                    println!("[Launch] Failed to install Java runtime: {}", e);
+                    match best {
+                        // 下载失败时退回到不匹配的 Java，下方会给出警告
+                        Some(path) => path,
+                        None => {
+                            send_step("check-java", "error", "launch.steps.javaDownloadFailed", serde_json::json!({ "message": e }));
+                            return Err(LauncherError::JavaNotFound { required_major, message: e });
+                        }
+                    }
+                }
+            }
+        }
+    };
+    
+    // 启动前检查 Java 版本是否与要求一致，避免启动后才崩溃
+    let mut java_warning = None;
+    if let Some(info) = crate::launcher::probe_java_with_cache(&cache_dir, &java_path, "").await {
+        let (_, memory_max) = resolve_memory(&settings, &version_id);
+        
+        if let Some(required) = required_major.filter(|m| *m != info.major_version) {
+            println!("[Launch] Java version mismatch: required {}, got {}", required, info.major_version);
+            java_warning = Some(crate::i18n::message("launch.javaWarnings.versionMismatch", serde_json::json!({
+                "current": info.major_version,
+                "required": required
+            })));
+        } else if crate::launcher::is_heap_limited(&info, memory_max) {
+            println!("[Launch] 32-bit Java cannot use -Xmx{}M", memory_max);
+            java_warning = Some(crate::i18n::message("launch.javaWarnings.heapLimited", serde_json::json!({
+                "memory": memory_max,
+                "limit": crate::launcher::MAX_32BIT_HEAP_MB
+            })));
+        }
+    }
+    match &java_warning {
+        Some(warning) => emit_launch_step(&app_handle, "check-java", "done", warning.clone()),
+        None => send_step("check-java", "done", "launch.steps.javaReady", serde_json::json!({ "path": java_path })),
+    }
+    
+    let app_state = state.lock().await;
+    
+    // 步骤3: 检查游戏目录
+    send_step("check-dir", "active", "launch.steps.checkingDir", serde_json::json!({}));
     
     if !Path::new(&mc_dir).exists() {
-        send_step("check-dir", "error", "游戏目录不存在");
-        return Ok(serde_json::json!({
-            "success": false,
-            "error": "请先设置 .minecraft 目录"
-        }));
+        send_step("check-dir", "error", "launch.steps.gameDirMissing", serde_json::json!({ "path": mc_dir }));
+        return Err(LauncherError::GameDirMissing { path: mc_dir });
     }
-    send_step("check-dir", "done", "游戏目录已就绪");
+    send_step("check-dir", "done", "launch.steps.gameDirReady", serde_json::json!({}));
     
     // 步骤4-6: 构建启动参数
-    send_step("build-classpath", "active", "正在处理游戏依赖...");
+    send_step("build-classpath", "active", "launch.steps.resolvingLibraries", serde_json::json!({}));
     
     let launch_result = build_and_launch(
         &mc_dir,
         &version_id,
         &java_path,
         &account,
+        &agent_args,
         &app_state.launcher_settings,
         app_handle.clone(),
     ).await;
     
     match launch_result {
         Ok(pid) => {
-            send_step("launch", "done", "游戏已启动");
+            send_step("launch", "done", "launch.steps.launched", serde_json::json!({}));
             
             // 注册运行实例
             let mut instances = app_state.running_instances.write().await;
@@ -596,106 +673,259 @@ pub async fn launch_game(
             Ok(serde_json::json!({
                 "success": true,
                 "pid": pid,
-                "launcherBehavior": launcher_behavior
+                "launcherBehavior": launcher_behavior,
+                "javaWarning": java_warning.as_ref().map(|warning| warning["message"].clone())
             }))
         }
-        Err(e) => {
-            send_step("launch", "error", &e);
-            Ok(serde_json::json!({
-                "success": false,
-                "error": e
-            }))
+        Err(message) => {
+            let error = LauncherError::LaunchFailed { version_id, message };
+            send_error("launch", &error);
+            Err(error)
         }
     }
 }
 
+/// 启动所需的 javaagent 参数：外置登录需要 authlib-injector，离线账户的自定义皮肤通过本地皮肤服务器加载
+/// 皮肤服务器不可用不影响启动，此时返回空参数
+async fn build_agent_args(data_dir: &Path, account: &crate::accounts::Account) -> Result<Vec<String>, LauncherError> {
+    if account.account_type == "yggdrasil" {
+        return crate::yggdrasil::build_agent_args(data_dir, account).await
+            .map_err(|message| LauncherError::AgentUnavailable { message });
+    }
+    if !crate::skin_server::needs_skin_server(account) {
+        return Ok(Vec::new());
+    }
+    match crate::skin_server::build_agent_args(data_dir, account).await {
+        Ok(args) => Ok(args),
+        Err(e) => {
+            println!("[Launch] Skin server unavailable, launching without custom skin: {}", e);
+            Ok(Vec::new())
+        }
+    }
+}
 
-async fn build_and_launch(
-    mc_dir: &str,
-    version_id: &str,
-    java_path: &str,
-    account: &crate::accounts::Account,
-    settings: &crate::launcher::LauncherSettings,
-    app_handle: tauri::AppHandle,
-) -> Result<u32, String> {
+// 发送启动步骤事件，message 为 i18n::message 生成的 { messageKey, params, message }
+fn emit_launch_step(app_handle: &tauri::AppHandle, step: &str, status: &str, mut message: serde_json::Value) {
     use tauri::Emitter;
     
-    let mc_path = Path::new(mc_dir);
-    let versions_dir = mc_path.join("versions");
-    let version_dir = versions_dir.join(version_id);
-    let json_path = version_dir.join(format!("{}.json", version_id));
+    message["step"] = serde_json::json!(step);
+    message["status"] = serde_json::json!(status);
+    let _ = app_handle.emit("launch-step", message);
+}
+
+
+// 校验 Java 配置：用启动时完全相同的 JVM 参数（包括 javaagent）加 -version 试运行一次
+#[tauri::command]
+pub async fn validate_java_config(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    version_id: String,
+) -> Result<serde_json::Value, LauncherError> {
+    let app_state = state.lock().await;
+    let settings = app_state.launcher_settings.clone();
+    let cache_dir = app_state.get_cache_dir();
+    let data_dir = app_state.data_dir.clone();
+    let account = app_state.accounts_data.current_account.as_ref()
+        .and_then(|id| app_state.accounts_data.accounts.iter().find(|a| &a.id == id).cloned())
+        .ok_or(LauncherError::NoAccountSelected)?;
+    drop(app_state);
+    
+    let mc_dir = settings.minecraft_dir.clone()
+        .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string());
+    let required_major = crate::launcher::resolve_required_java_major(&mc_dir, &version_id);
+    
+    // 与启动时相同的选择逻辑，但不自动下载运行时
+    let java_path = match select_java_for_version(&settings, &cache_dir, &mc_dir, &version_id, required_major).await {
+        Ok(path) => Some(path),
+        Err(best) => {
+            let component = crate::downloads::required_java_component(&mc_dir, &version_id);
+            let runtime = crate::downloads::java_runtime_executable(
+                &crate::downloads::java_runtime_dir(&mc_dir, &component));
+            if runtime.exists() {
+                Some(runtime.to_string_lossy().to_string())
+            } else {
+                best
+            }
+        }
+    };
+    let java_path = java_path.ok_or_else(|| LauncherError::JavaCheckFailed {
+        java_path: None,
+        message: crate::i18n::t("javaCheck.noJava", &serde_json::json!({})),
+    })?;
+    
+    let agent_args = build_agent_args(&data_dir, &account).await?;
+    let merged = load_merged_version(&mc_dir, &version_id)
+        .map_err(|message| LauncherError::LaunchFailed { version_id: version_id.clone(), message })?;
+    let command = resolve_launch_command(&mc_dir, &version_id, &merged, &java_path, &account, &agent_args, &settings)
+        .map_err(|message| LauncherError::LaunchFailed { version_id: version_id.clone(), message })?;
+    let (_, memory_max) = resolve_memory(&settings, &version_id);
+    
+    println!("[JavaCheck] Validating {} with {} JVM args", java_path, command.jvm_args.len());
+    
+    let mut cmd = tokio::process::Command::new(&java_path);
+    cmd.args(&command.jvm_args)
+        .arg("-version")
+        .current_dir(&command.working_dir)
+        .kill_on_drop(true);
+    
+    let output = match tokio::time::timeout(std::time::Duration::from_secs(30), cmd.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => {
+            return Err(LauncherError::JavaCheckFailed { java_path: Some(java_path), message: e.to_string() });
+        }
+        Err(_) => {
+            return Err(LauncherError::JavaCheckFailed {
+                java_path: Some(java_path),
+                message: crate::i18n::t("javaCheck.timeout", &serde_json::json!({})),
+            });
+        }
+    };
     
-    // 读取版本 JSON
-    let content = std::fs::read_to_string(&json_path)
-        .map_err(|e| format!("无法读取版本文件: {}", e))?;
-    let version_json: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("无法解析版本文件: {}", e))?;
+    let combined = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stderr),
+        String::from_utf8_lossy(&output.stdout)
+    );
+    let (problems, warnings) = analyze_jvm_output(&combined, memory_max);
+    let valid = output.status.success() && problems.is_empty();
     
-    // 解析继承链并合并
-    let merged = merge_version_chain(mc_dir, &version_json)?;
+    println!("[JavaCheck] Valid: {}, problems: {}, warnings: {}", valid, problems.len(), warnings.len());
     
-    let _ = app_handle.emit("launch-step", serde_json::json!({
-        "step": "build-classpath",
-        "status": "done",
-        "message": "依赖处理完成"
-    }));
+    Ok(serde_json::json!({
+        "success": true,
+        "valid": valid,
+        "javaPath": java_path,
+        "requiredMajor": required_major,
+        // classpath 过长，不返回给前端
+        "jvmArgs": command.jvm_args.iter().take_while(|a| *a != "-cp").collect::<Vec<_>>(),
+        "problems": problems,
+        "warnings": warnings,
+        "exitCode": output.status.code(),
+        "output": combined
+    }))
+}
+
+// 分析 JVM 输出，返回 (致命问题, 警告)，问题说明为 i18n::message 格式
+fn analyze_jvm_output(output: &str, memory_max: u32) -> (Vec<serde_json::Value>, Vec<String>) {
+    let mut problems = Vec::new();
+    let mut warnings = Vec::new();
+    
+    let problem = |kind: &str, option: serde_json::Value, key: &str, params: serde_json::Value| {
+        let mut problem = crate::i18n::message(&format!("javaCheck.problems.{}", key), params);
+        problem["kind"] = serde_json::json!(kind);
+        problem["option"] = option;
+        problem
+    };
     
-    // 提取 natives
-    let _ = app_handle.emit("launch-step", serde_json::json!({
-        "step": "extract-natives",
-        "status": "active",
-        "message": "正在提取本地库..."
-    }));
-    
-    let natives_dir = version_dir.join("natives");
-    std::fs::create_dir_all(&natives_dir).ok();
-    extract_natives(mc_dir, &merged, &natives_dir)?;
-    
-    let _ = app_handle.emit("launch-step", serde_json::json!({
-        "step": "extract-natives",
-        "status": "done",
-        "message": "本地库已就绪"
-    }));
-    
-    // 构建 classpath
-    let _ = app_handle.emit("launch-step", serde_json::json!({
-        "step": "build-args",
-        "status": "active",
-        "message": "正在构建启动参数..."
-    }));
-    
-    let classpath = build_classpath(mc_dir, version_id, &merged)?;
+    let unknown_vm_option = regex::Regex::new(r"Unrecognized VM option '([^']+)'").unwrap();
+    let unknown_option = regex::Regex::new(r"Unrecognized option: (\S+)").unwrap();
+    let experimental = regex::Regex::new(r"VM option '([^']+)' is experimental").unwrap();
+    let ignored = regex::Regex::new(r"Ignoring option ([^;]+);").unwrap();
     
-    // 构建参数 - 优先使用版本独立设置
-    let version_settings = settings.version_settings.get(version_id);
+    for caps in unknown_vm_option.captures_iter(output).chain(unknown_option.captures_iter(output)) {
+        problems.push(problem("unknown_option", serde_json::json!(&caps[1]), "unknownOption",
+            serde_json::json!({ "option": &caps[1] })));
+    }
     
-    // 内存设置
+    for caps in experimental.captures_iter(output) {
+        problems.push(problem("experimental_option", serde_json::json!(&caps[1]), "experimentalOption",
+            serde_json::json!({ "option": &caps[1] })));
+    }
+    
+    if output.contains("Could not reserve enough space")
+        || output.contains("Invalid maximum heap size")
+        || output.contains("exceeds the maximum representable size")
+    {
+        problems.push(problem("heap_size", serde_json::json!(format!("-Xmx{}M", memory_max)), "heapTooLarge",
+            serde_json::json!({ "memory": memory_max })));
+    }
+    
+    if output.contains("Initial heap size set to a larger value than the maximum heap size") {
+        problems.push(problem("heap_size", serde_json::json!("-Xms"), "minAboveMax", serde_json::json!({})));
+    }
+    
+    for caps in ignored.captures_iter(output) {
+        warnings.push(crate::i18n::t("javaCheck.problems.ignoredOption", &serde_json::json!({ "option": caps[1].trim() })));
+    }
+    
+    // 无法识别具体原因时给出通用错误
+    if problems.is_empty() && output.contains("Could not create the Java Virtual Machine") {
+        problems.push(problem("unknown", serde_json::Value::Null, "unknown", serde_json::json!({})));
+    }
+    
+    (problems, warnings)
+}
+
+/// 为版本选择 Java：版本独立设置中指定的路径直接使用（版本不符由调用方给出警告），
+/// 全局设置的 Java 仅在主版本号符合要求时使用，否则从检测结果中挑选匹配版本要求的 Java，
+/// 没有匹配时返回 Err(最接近的候选)
+/// 检测 Java 需要逐个运行 java -version，调用前应先释放 AppState 锁
+async fn select_java_for_version(
+    settings: &crate::launcher::LauncherSettings,
+    cache_dir: &Path,
+    mc_dir: &str,
+    version_id: &str,
+    required_major: Option<u32>,
+) -> Result<String, Option<String>> {
+    let version_java_path = settings.version_settings
+        .get(version_id)
+        .and_then(|vs| vs.java_path.clone())
+        .filter(|p| !p.is_empty());
+    let global_java_path = settings.java_path.clone().filter(|p| !p.is_empty());
+    
+    println!("[Launch] Version-specific Java path: {:?}", version_java_path);
+    println!("[Launch] Global Java path: {:?}", global_java_path);
+    
+    if let Some(p) = version_java_path {
+        println!("[Launch] Using version-specific Java path: {}", p);
+        return Ok(p);
+    }
+    if let Some(p) = &global_java_path {
+        // 全局设置对所有版本生效，版本不符时改为自动选择，例如全局 Java 8 启动 1.20.5
+        let major = crate::launcher::probe_java_with_cache(cache_dir, p, "").await
+            .map(|java| java.major_version);
+        if major.is_some() && required_major.is_none_or(|m| major == Some(m)) {
+            println!("[Launch] Using global Java path: {}", p);
+            return Ok(p.clone());
+        }
+        println!("[Launch] Global Java {} is {:?}, required {:?}, selecting automatically", p, major, required_major);
+    }
+    
+    let candidates = crate::launcher::find_java_installations(cache_dir, mc_dir).await;
+    let best = crate::launcher::select_best_java(&candidates, required_major.unwrap_or(8));
+    match best {
+        Some(java) if required_major.is_none_or(|m| java.major_version == m) => {
+            println!("[Launch] Selected Java {} ({}) at {}", java.version, java.vendor, java.path);
+            Ok(java.path.clone())
+        }
+        // 没有更合适的候选时仍可退回全局设置的 Java
+        _ => Err(best.map(|java| java.path.clone()).or(global_java_path)),
+    }
+}
+
+// 内存设置 - 优先使用版本独立设置
+fn resolve_memory(settings: &crate::launcher::LauncherSettings, version_id: &str) -> (u32, u32) {
+    let version_settings = settings.version_settings.get(version_id);
     let memory_min = version_settings
         .and_then(|vs| vs.memory_min)
         .unwrap_or(settings.memory.min);
     let memory_max = version_settings
         .and_then(|vs| vs.memory_max)
         .unwrap_or(settings.memory.max);
-    
-    // 窗口大小
-    let window_width = version_settings
-        .and_then(|vs| vs.window_width)
-        .unwrap_or(settings.window_size.width);
-    let window_height = version_settings
-        .and_then(|vs| vs.window_height)
-        .unwrap_or(settings.window_size.height);
-    
-    let effective_window_size = crate::launcher::WindowSize {
-        width: window_width,
-        height: window_height,
-    };
-    
-    println!("[Launch] Memory: {}MB - {}MB (version override: {:?})", 
-        memory_min, memory_max, 
-        version_settings.and_then(|vs| vs.memory_max));
-    println!("[Launch] Window: {}x{} (version override: {:?})", 
-        window_width, window_height,
-        version_settings.and_then(|vs| vs.window_width));
+    (memory_min, memory_max)
+}
+
+/// 构建完整的 JVM 参数列表，启动和 Java 配置校验共用
+fn build_jvm_args(
+    mc_dir: &str,
+    version_id: &str,
+    merged: &serde_json::Value,
+    settings: &crate::launcher::LauncherSettings,
+    natives_dir: &Path,
+    classpath: Option<&str>,
+) -> Vec<String> {
+    let mc_path = Path::new(mc_dir);
+    let versions_dir = mc_path.join("versions");
+    let version_dir = versions_dir.join(version_id);
     
     // 构建 JVM 参数变量映射
     let version_jar = version_dir.join(format!("{}.jar", version_id));
@@ -711,7 +941,7 @@ async fn build_and_launch(
         ("natives_directory", natives_dir.to_string_lossy().to_string()),
         ("launcher_name", "ImagineLauncher".to_string()),
         ("launcher_version", "1.0.0".to_string()),
-        ("classpath", classpath.clone()),
+        ("classpath", classpath.unwrap_or_default().to_string()),
         ("classpath_separator", if cfg!(windows) { ";" } else { ":" }.to_string()),
         ("library_directory", mc_path.join("libraries").to_string_lossy().to_string()),
         ("version_name", version_id.to_string()),
@@ -725,6 +955,7 @@ async fn build_and_launch(
     
     println!("[Launch] Is old version (uses minecraftArguments): {}", is_old_version);
     
+    let (memory_min, memory_max) = resolve_memory(settings, version_id);
     let mut jvm_args = Vec::new();
     
     // 内存参数
@@ -835,38 +1066,152 @@ async fn build_and_launch(
     }
     
     // 添加 classpath（放在最后）
-    jvm_args.push("-cp".to_string());
-    jvm_args.push(classpath);
+    if let Some(classpath) = classpath {
+        jvm_args.push("-cp".to_string());
+        jvm_args.push(classpath.to_string());
+    }
+    
+    jvm_args
+}
+
+/// 解析完成的启动命令，游戏启动和启动脚本生成共用
+pub struct LaunchCommand {
+    pub java_path: String,
+    pub jvm_args: Vec<String>,
+    pub main_class: String,
+    pub game_args: Vec<String>,
+    pub working_dir: String,
+    pub natives_dir: PathBuf,
+    // 启动前需要解压到 natives_dir 的本地库
+    pub native_jars: Vec<PathBuf>,
+}
+
+// 读取版本 JSON 并合并继承链
+fn load_merged_version(mc_dir: &str, version_id: &str) -> Result<serde_json::Value, String> {
+    let json_path = Path::new(mc_dir)
+        .join("versions")
+        .join(version_id)
+        .join(format!("{}.json", version_id));
+    
+    let content = std::fs::read_to_string(&json_path)
+        .map_err(|e| crate::i18n::t("launch.errors.readVersion", &serde_json::json!({ "message": e.to_string() })))?;
+    let version_json: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| crate::i18n::t("launch.errors.parseVersion", &serde_json::json!({ "message": e.to_string() })))?;
+    
+    merge_version_chain(mc_dir, &version_json)
+}
+
+// 按版本 JSON 和启动器设置构建完整的 JVM 参数、主类和游戏参数
+fn resolve_launch_command(
+    mc_dir: &str,
+    version_id: &str,
+    merged: &serde_json::Value,
+    java_path: &str,
+    account: &crate::accounts::Account,
+    agent_args: &[String],
+    settings: &crate::launcher::LauncherSettings,
+) -> Result<LaunchCommand, String> {
+    let natives_dir = Path::new(mc_dir).join("versions").join(version_id).join("natives");
+    
+    let classpath = build_classpath(mc_dir, version_id, merged)?;
+    
+    // 构建参数 - 优先使用版本独立设置
+    let version_settings = settings.version_settings.get(version_id);
+    
+    // 内存设置
+    let (memory_min, memory_max) = resolve_memory(settings, version_id);
+    
+    // 窗口大小
+    let window_width = version_settings
+        .and_then(|vs| vs.window_width)
+        .unwrap_or(settings.window_size.width);
+    let window_height = version_settings
+        .and_then(|vs| vs.window_height)
+        .unwrap_or(settings.window_size.height);
+    
+    let effective_window_size = crate::launcher::WindowSize {
+        width: window_width,
+        height: window_height,
+    };
+    
+    println!("[Launch] Memory: {}MB - {}MB (version override: {:?})", 
+        memory_min, memory_max, 
+        version_settings.and_then(|vs| vs.memory_max));
+    println!("[Launch] Window: {}x{} (version override: {:?})", 
+        window_width, window_height,
+        version_settings.and_then(|vs| vs.window_width));
+    
+    // javaagent 需要在其他 JVM 参数之前
+    let jvm_args: Vec<String> = agent_args.iter().cloned()
+        .chain(build_jvm_args(mc_dir, version_id, merged, settings, &natives_dir, Some(&classpath)))
+        .collect();
     
     let main_class = merged["mainClass"].as_str()
-        .ok_or("无法确定主类")?
+        .ok_or_else(|| crate::i18n::t("launch.errors.noMainClass", &serde_json::json!({})))?
         .to_string();
     
-    let game_args = build_game_args(mc_dir, version_id, &merged, account, &effective_window_size)?;
+    let game_dir = crate::mods::game_dir_for_version(settings, mc_dir, version_id).to_string_lossy().to_string();
+    let game_args = build_game_args(mc_dir, &game_dir, version_id, merged, account, &effective_window_size)?;
+    
+    Ok(LaunchCommand {
+        java_path: java_path.to_string(),
+        jvm_args,
+        main_class,
+        game_args,
+        working_dir: game_dir,
+        native_jars: native_jars(mc_dir, merged),
+        natives_dir,
+    })
+}
+
+async fn build_and_launch(
+    mc_dir: &str,
+    version_id: &str,
+    java_path: &str,
+    account: &crate::accounts::Account,
+    agent_args: &[String],
+    settings: &crate::launcher::LauncherSettings,
+    app_handle: tauri::AppHandle,
+) -> Result<u32, String> {
+    use tauri::Emitter;
+    
+    // 读取版本 JSON，解析继承链并合并
+    let merged = load_merged_version(mc_dir, version_id)?;
+    
+    emit_launch_step(&app_handle, "build-classpath", "done", crate::i18n::message("launch.steps.librariesReady", serde_json::json!({})));
+    
+    // 构建 classpath 和启动参数
+    let command = resolve_launch_command(mc_dir, version_id, &merged, java_path, account, agent_args, settings)?;
+    
+    // 提取 natives
+    emit_launch_step(&app_handle, "extract-natives", "active", crate::i18n::message("launch.steps.extractingNatives", serde_json::json!({})));
+    
+    std::fs::create_dir_all(&command.natives_dir).ok();
+    extract_natives(&command)?;
+    
+    emit_launch_step(&app_handle, "extract-natives", "done", crate::i18n::message("launch.steps.nativesReady", serde_json::json!({})));
+    
+    emit_launch_step(&app_handle, "build-args", "active", crate::i18n::message("launch.steps.buildingArguments", serde_json::json!({})));
+    
+    let LaunchCommand { jvm_args, main_class, game_args, working_dir, .. } = command;
+    let (_, memory_max) = resolve_memory(settings, version_id);
     
     // 打印完整的启动命令用于调试
     println!("[Launch] JVM args: {:?}", jvm_args);
     println!("[Launch] Main class: {}", main_class);
-    println!("[Launch] Game args: {:?}", game_args);
+    let mut redactor = crate::redaction::Redactor::tokens_only(std::slice::from_ref(account));
+    println!("[Launch] Game args: {}", redactor.redact(&game_args.join(" ")));
     
-    let _ = app_handle.emit("launch-step", serde_json::json!({
-        "step": "build-args",
-        "status": "done",
-        "message": format!("内存: {}MB", memory_max)
-    }));
+    emit_launch_step(&app_handle, "build-args", "done", crate::i18n::message("launch.steps.argumentsReady", serde_json::json!({ "memory": memory_max })));
     
     // 启动游戏
-    let _ = app_handle.emit("launch-step", serde_json::json!({
-        "step": "launch",
-        "status": "active",
-        "message": "正在启动游戏..."
-    }));
+    emit_launch_step(&app_handle, "launch", "active", crate::i18n::message("launch.steps.starting", serde_json::json!({})));
     
     let mut cmd = std::process::Command::new(java_path);
     cmd.args(&jvm_args)
         .arg(&main_class)
         .args(&game_args)
-        .current_dir(mc_dir);
+        .current_dir(&working_dir);
     
     // 不要 pipe stdout/stderr，让游戏进程独立运行
     // 这样可以避免 GUI 窗口无法显示的问题
@@ -883,10 +1228,11 @@ async fn build_and_launch(
         java_path,
         jvm_args.join(" "),
         main_class,
-        game_args.join(" ")
+        redactor.redact(&game_args.join(" "))
     );
     
-    let child = cmd.spawn().map_err(|e| format!("启动失败: {}", e))?;
+    let child = cmd.spawn()
+        .map_err(|e| crate::i18n::t("launch.errors.spawnFailed", &serde_json::json!({ "message": e.to_string() })))?;
     let pid = child.id();
     
     println!("[Launch] Game process started with PID: {}", pid);
@@ -1059,10 +1405,10 @@ fn get_library_path(lib: &serde_json::Value, libraries_dir: &Path) -> Option<Pat
     None
 }
 
-fn extract_natives(mc_dir: &str, merged: &serde_json::Value, natives_dir: &Path) -> Result<(), String> {
+// 当前系统需要的 natives jar（只返回存在的文件）
+fn native_jars(mc_dir: &str, merged: &serde_json::Value) -> Vec<PathBuf> {
     let libraries_dir = Path::new(mc_dir).join("libraries");
-    
-    println!("[Natives] Extracting natives to: {:?}", natives_dir);
+    let mut jars = Vec::new();
     
     if let Some(libs) = merged["libraries"].as_array() {
         for lib in libs {
@@ -1078,16 +1424,11 @@ fn extract_natives(mc_dir: &str, merged: &serde_json::Value, natives_dir: &Path)
                     let key = key.replace("${arch}", if cfg!(target_pointer_width = "64") { "64" } else { "32" });
                     
                     // 方式1: 新版格式 - 使用 downloads/classifiers
-                    if let Some(path) = lib.pointer(&format!("/downloads/classifiers/{}/path", key))
+                    let native_path = if let Some(path) = lib.pointer(&format!("/downloads/classifiers/{}/path", key))
                         .and_then(|v| v.as_str()) 
                     {
-                        let native_path = libraries_dir.join(path);
-                        println!("[Natives] Found native (new format): {:?}", native_path);
-                        if native_path.exists() {
-                            extract_jar_natives(&native_path, natives_dir)?;
-                        } else {
-                            println!("[Natives] Native file not found: {:?}", native_path);
-                        }
+                        println!("[Natives] Found native (new format): {}", path);
+                        Some(libraries_dir.join(path))
                     }
                     // 方式2: 旧版格式 - 从 name 构建路径
                     else if let Some(name) = lib["name"].as_str() {
@@ -1099,21 +1440,35 @@ fn extract_natives(mc_dir: &str, merged: &serde_json::Value, natives_dir: &Path)
                             
                             // 构建带 classifier 的文件名
                             let filename = format!("{}-{}-{}.jar", artifact, version, key);
-                            let native_path = libraries_dir.join(&group).join(artifact).join(version).join(&filename);
-                            
-                            println!("[Natives] Found native (old format): {:?}", native_path);
-                            if native_path.exists() {
-                                extract_jar_natives(&native_path, natives_dir)?;
-                            } else {
-                                println!("[Natives] Native file not found: {:?}", native_path);
-                            }
+                            println!("[Natives] Found native (old format): {}", name);
+                            Some(libraries_dir.join(&group).join(artifact).join(version).join(&filename))
+                        } else {
+                            None
                         }
+                    } else {
+                        None
+                    };
+                    
+                    match native_path {
+                        Some(path) if path.exists() => jars.push(path),
+                        Some(path) => println!("[Natives] Native file not found: {:?}", path),
+                        None => {}
                     }
                 }
             }
         }
     }
     
+    jars
+}
+
+fn extract_natives(command: &LaunchCommand) -> Result<(), String> {
+    println!("[Natives] Extracting natives to: {:?}", command.natives_dir);
+    
+    for jar in &command.native_jars {
+        extract_jar_natives(jar, &command.natives_dir)?;
+    }
+    
     Ok(())
 }
 
@@ -1200,6 +1555,7 @@ fn check_rules(rules: Option<&serde_json::Value>) -> bool {
 
 fn build_game_args(
     mc_dir: &str,
+    game_dir: &str,
     version_id: &str,
     merged: &serde_json::Value,
     account: &crate::accounts::Account,
@@ -1209,14 +1565,18 @@ fn build_game_args(
     
     let uuid = account.uuid.replace("-", "");
     let access_token = account.access_token.as_deref().unwrap_or(&uuid);
-    let user_type = if account.account_type == "microsoft" { "msa" } else { "legacy" };
+    let user_type = match account.account_type.as_str() {
+        "microsoft" => "msa",
+        "yggdrasil" => "mojang",
+        _ => "legacy",
+    };
     
     let assets_index = merged["assets"].as_str()
         .or_else(|| merged.pointer("/assetIndex/id").and_then(|v| v.as_str()))
         .unwrap_or(version_id);
     
-    // 获取游戏目录（可能是版本隔离目录）
-    let game_dir = mc_dir.to_string();
+    // 游戏目录（可能是版本隔离目录）
+    let game_dir = game_dir.to_string();
     let assets_dir = Path::new(mc_dir).join("assets").to_string_lossy().to_string();
     
     let variables: HashMap<&str, String> = [
@@ -1293,7 +1653,7 @@ async fn monitor_game_process(
     app_handle: tauri::AppHandle,
 ) {
     use std::io::{BufRead, BufReader};
-    use tauri::Emitter;
+    use tauri::{Emitter, Manager};
     
     let pid = child.id();
     println!("[GameMonitor] Starting monitor for {} (PID: {})", version_id, pid);
@@ -1336,7 +1696,9 @@ async fn monitor_game_process(
             
             // 分析崩溃原因
             let crash_info = if exit_code != 0 {
-                Some(analyze_crash(&error_logs, &all_logs, exit_code))
+                let data_dir = app_handle.path().app_data_dir().ok();
+                let mods_dir = crash_mods_dir(&app_handle, &version_id).await;
+                Some(crate::crash_analyzer::analyze(&all_logs, exit_code, data_dir.as_deref(), mods_dir.as_deref()))
             } else {
                 None
             };
@@ -1404,7 +1766,12 @@ async fn monitor_game_process_simple(
             // 如果崩溃，尝试读取游戏日志文件来分析
             if exit_code != 0 {
                 // 尝试从游戏日志目录读取错误信息
-                let crash_info = analyze_crash_from_logs(&app_handle, &version_id, exit_code).await;
+                let crash_info = analyze_crash_from_logs(&app_handle, &version_id, pid, exit_code).await;
+                
+                // 模组排查中的崩溃只记录结果，不弹出崩溃窗口
+                if crate::bisect::record_exit(&app_handle, pid, &version_id, exit_code, crash_info["category"].as_str()).await {
+                    return;
+                }
                 
                 let crash_data = serde_json::json!({
                     "versionId": version_id,
@@ -1414,6 +1781,8 @@ async fn monitor_game_process_simple(
                 });
                 
                 open_crash_report_window(app_handle, crash_data).await;
+            } else {
+                crate::bisect::record_exit(&app_handle, pid, &version_id, exit_code, None).await;
             }
         }
         Err(e) => {
@@ -1432,20 +1801,23 @@ async fn monitor_game_process_simple(
 async fn analyze_crash_from_logs(
     app_handle: &tauri::AppHandle,
     version_id: &str,
+    pid: u32,
     exit_code: i32,
 ) -> serde_json::Value {
     use tauri::Manager;
     
-    // 获取 minecraft 目录
-    let mc_dir = if let Some(state) = app_handle.try_state::<std::sync::Arc<tokio::sync::Mutex<crate::state::AppState>>>() {
+    // 获取游戏目录（开启版本隔离时为 versions/<id>），日志和崩溃报告都在这里
+    let game_dir = if let Some(state) = app_handle.try_state::<std::sync::Arc<tokio::sync::Mutex<crate::state::AppState>>>() {
         let state = state.lock().await;
-        state.launcher_settings.minecraft_dir.clone()
-            .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string())
+        let settings = &state.launcher_settings;
+        let mc_dir = settings.minecraft_dir.clone()
+            .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string());
+        crate::mods::game_dir_for_version(settings, &mc_dir, version_id)
     } else {
-        get_default_mc_dir().to_string_lossy().to_string()
+        get_default_mc_dir()
     };
     
-    let logs_dir = Path::new(&mc_dir).join("logs");
+    let logs_dir = game_dir.join("logs");
     let latest_log = logs_dir.join("latest.log");
     
     let mut error_logs = Vec::new();
@@ -1461,44 +1833,36 @@ async fn analyze_crash_from_logs(
         }
     }
     
-    // 检查 crash-reports 目录
-    let crash_reports_dir = Path::new(&mc_dir).join("crash-reports");
-    if crash_reports_dir.exists() {
-        if let Ok(entries) = std::fs::read_dir(&crash_reports_dir) {
-            let mut crash_files: Vec<_> = entries
-                .filter_map(|e| e.ok())
-                .filter(|e| e.path().extension().map(|ext| ext == "txt").unwrap_or(false))
-                .collect();
-            
-            crash_files.sort_by(|a, b| {
-                let time_a = a.metadata().and_then(|m| m.modified()).ok();
-                let time_b = b.metadata().and_then(|m| m.modified()).ok();
-                time_b.cmp(&time_a)
-            });
-            
-            // 读取最新的崩溃报告
-            if let Some(latest_crash) = crash_files.first() {
-                if let Ok(content) = std::fs::read_to_string(latest_crash.path()) {
-                    // 检查是否是最近的崩溃报告（5分钟内）
-                    if let Ok(metadata) = latest_crash.metadata() {
-                        if let Ok(modified) = metadata.modified() {
-                            let now = std::time::SystemTime::now();
-                            if let Ok(duration) = now.duration_since(modified) {
-                                if duration.as_secs() < 300 {
-                                    // 添加崩溃报告内容
-                                    for line in content.lines().take(100) {
-                                        error_logs.push(line.to_string());
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    // 本次崩溃生成的崩溃报告和 JVM 错误日志，解析后把异常和堆栈交给分析
+    let crash_reports = crate::crash_report::collect_recent(&game_dir, pid);
+    for (_, report) in &crash_reports {
+        error_logs.extend(report.analysis_lines());
     }
     
-    analyze_crash(&error_logs, &error_logs, exit_code)
+    let data_dir = app_handle.path().app_data_dir().ok();
+    let mods_dir = crash_mods_dir(app_handle, version_id).await;
+    let mut crash_info = crate::crash_analyzer::analyze(&error_logs, exit_code, data_dir.as_deref(), mods_dir.as_deref());
+    crash_info["crashReports"] = crash_reports.iter()
+        .map(|(path, report)| serde_json::json!({
+            "path": path.to_string_lossy(),
+            "report": report
+        }))
+        .collect();
+    crash_info
+}
+
+// 崩溃版本实际加载的 mods 目录，用于把崩溃归因到具体模组
+async fn crash_mods_dir(app_handle: &tauri::AppHandle, version_id: &str) -> Option<std::path::PathBuf> {
+    use tauri::Manager;
+    
+    let state = app_handle.try_state::<std::sync::Arc<tokio::sync::Mutex<crate::state::AppState>>>()?;
+    let state = state.lock().await;
+    let settings = &state.launcher_settings;
+    let mc_dir = settings.minecraft_dir.clone()
+        .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string());
+    
+    let mods_dir = crate::mods::mods_dir_for_version(settings, &mc_dir, version_id);
+    mods_dir.is_dir().then_some(mods_dir)
 }
 
 // 打开崩溃报告窗口
@@ -1509,6 +1873,7 @@ async fn open_crash_report_window(app_handle: tauri::AppHandle, crash_data: serd
     if let Some(state) = app_handle.try_state::<std::sync::Arc<tokio::sync::Mutex<crate::state::AppState>>>() {
         let mut state = state.lock().await;
         state.last_crash_data = Some(crash_data.clone());
+        state.crash_share_preview = None;
     }
     
     // 创建崩溃报告窗口
@@ -1517,7 +1882,7 @@ async fn open_crash_report_window(app_handle: tauri::AppHandle, crash_data: serd
         "crash-report",
         tauri::WebviewUrl::App("crash-report.html".into())
     )
-    .title("游戏崩溃报告")
+    .title(crate::i18n::t("crashReport.windowTitle", &serde_json::json!({})))
     .inner_size(1021.0, 620.0)
     .min_inner_size(600.0, 400.0)
     .center()
@@ -1537,243 +1902,6 @@ async fn open_crash_report_window(app_handle: tauri::AppHandle, crash_data: serd
     }
 }
 
-// 分析崩溃原因 - 增强版
-fn analyze_crash(error_logs: &[String], all_logs: &[String], exit_code: i32) -> serde_json::Value {
-    let error_text = error_logs.join("\n");
-    let all_text = all_logs.join("\n");
-    let error_lower = error_text.to_lowercase();
-    let all_lower = all_text.to_lowercase();
-    
-    let mut reason = "游戏异常退出".to_string();
-    let mut details = format!("退出码: {}", exit_code);
-    let mut suggestions: Vec<&str> = vec![];
-    
-    // 1. Java 版本不兼容 - 最常见的问题
-    if error_lower.contains("unsupportedclassversionerror") 
-        || error_lower.contains("class file version")
-        || error_lower.contains("has been compiled by a more recent version")
-        || error_text.contains("UnsupportedClassVersionError")
-        || error_text.contains("class file version 61") // Java 17
-        || error_text.contains("class file version 65") // Java 21
-        || error_text.contains("class file version 52") // Java 8
-        || all_lower.contains("unsupportedclassversionerror")
-        // ClassCastException: URLClassLoader - Java 9+ 不再使用 URLClassLoader
-        || (error_text.contains("ClassCastException") && error_text.contains("URLClassLoader"))
-        || (error_lower.contains("classcastexception") && error_lower.contains("urlclassloader"))
-        || error_text.contains("cannot be cast to class java.net.URLClassLoader")
-        || error_text.contains("AppClassLoader cannot be cast")
-        // Java 模块系统相关错误
-        || error_text.contains("module java.base")
-        || (error_lower.contains("illegalaccesserror") && error_lower.contains("module")) {
-        reason = "Java 版本不兼容".to_string();
-        
-        // 尝试提取具体版本信息
-        if error_text.contains("class file version 65") || error_text.contains("Java 21") {
-            details = "游戏需要 Java 21，但当前使用的 Java 版本过低".to_string();
-        } else if error_text.contains("class file version 61") || error_text.contains("Java 17") {
-            details = "游戏需要 Java 17，但当前使用的 Java 版本过低".to_string();
-        } else if error_text.contains("class file version 52") {
-            details = "游戏需要 Java 8，但当前使用的 Java 版本可能不兼容".to_string();
-        } else if error_text.contains("URLClassLoader") || error_text.contains("AppClassLoader") {
-            details = "游戏/模组使用了旧版 Java API (URLClassLoader)，需要 Java 8 运行".to_string();
-        } else if error_text.contains("module java.base") {
-            details = "Java 模块系统冲突，通常是 Java 版本过高导致".to_string();
-        } else {
-            details = "游戏需要的 Java 版本与当前使用的不匹配".to_string();
-        }
-        
-        suggestions = vec![
-            "Minecraft 1.8-1.16 需要 Java 8",
-            "Minecraft 1.17 需要 Java 16+",
-            "Minecraft 1.18-1.20.4 需要 Java 17+",
-            "Minecraft 1.20.5+ 需要 Java 21+",
-            "如果使用旧版 Forge/模组，可能需要 Java 8",
-            "请在版本设置中选择正确的 Java 版本",
-        ];
-    }
-    // 2. Java 找不到或无法执行
-    else if error_lower.contains("cannot find java") 
-        || error_lower.contains("java not found")
-        || error_lower.contains("'java' is not recognized")
-        || error_lower.contains("error: could not find or load main class")
-        || error_text.contains("Error: Could not find or load main class") {
-        reason = "Java 无法启动".to_string();
-        details = "找不到 Java 或 Java 无法正常执行".to_string();
-        suggestions = vec![
-            "检查 Java 路径是否正确",
-            "确保 Java 已正确安装",
-            "尝试重新选择 Java 路径",
-        ];
-    }
-    // 3. 内存不足
-    else if error_lower.contains("outofmemoryerror") 
-        || error_lower.contains("out of memory")
-        || error_lower.contains("gc overhead limit exceeded")
-        || error_lower.contains("java heap space")
-        || error_text.contains("OutOfMemoryError") {
-        reason = "内存不足".to_string();
-        details = "游戏运行时内存耗尽 (OutOfMemoryError)".to_string();
-        suggestions = vec![
-            "增加分配给游戏的内存（建议 4GB 以上）",
-            "关闭其他占用内存的程序",
-            "减少游戏中的视距和渲染设置",
-            "如果使用了大量模组，考虑减少模组数量",
-        ];
-    }
-    // 4. OpenGL / 显卡错误
-    else if (error_lower.contains("opengl") || error_lower.contains("gl error"))
-        && (error_lower.contains("error") || error_lower.contains("failed") || error_lower.contains("not supported")) {
-        reason = "显卡/OpenGL 错误".to_string();
-        details = "图形渲染出现问题，可能是显卡驱动或 OpenGL 版本问题".to_string();
-        suggestions = vec![
-            "更新显卡驱动程序到最新版本",
-            "检查显卡是否支持 OpenGL 4.4+",
-            "尝试在游戏设置中降低图形质量",
-            "如果使用 OptiFine/光影，尝试禁用",
-        ];
-    }
-    // 5. LWJGL 错误
-    else if error_lower.contains("lwjgl") && (error_lower.contains("error") || error_lower.contains("failed") || error_lower.contains("exception")) {
-        reason = "LWJGL 库错误".to_string();
-        details = "游戏图形库 (LWJGL) 加载失败".to_string();
-        suggestions = vec![
-            "尝试重新下载游戏版本",
-            "检查 natives 文件是否完整",
-            "更新显卡驱动程序",
-            "检查是否有杀毒软件阻止了 DLL 文件",
-        ];
-    }
-    // 6. 模组问题 - Mixin 错误
-    else if error_lower.contains("mixin") && (error_lower.contains("error") || error_lower.contains("failed") || error_lower.contains("exception")) {
-        reason = "模组注入错误 (Mixin)".to_string();
-        details = "模组代码注入时发生错误，通常是模组不兼容导致".to_string();
-        suggestions = vec![
-            "检查模组是否与游戏版本兼容",
-            "尝试移除最近添加的模组",
-            "检查是否有多个模组修改了相同的游戏代码",
-            "更新所有模组到最新版本",
-        ];
-    }
-    // 7. 模组冲突
-    else if (error_lower.contains("mod") || error_lower.contains("fabric") || error_lower.contains("forge"))
-        && (error_lower.contains("conflict") || error_lower.contains("duplicate") || error_lower.contains("incompatible")) {
-        reason = "模组冲突".to_string();
-        details = "检测到模组之间存在冲突".to_string();
-        suggestions = vec![
-            "检查是否安装了重复的模组",
-            "查看哪些模组之间不兼容",
-            "尝试二分法排查问题模组",
-        ];
-    }
-    // 8. Forge/Fabric 加载错误
-    else if (error_lower.contains("forge") || error_lower.contains("fml")) 
-        && (error_lower.contains("error") || error_lower.contains("failed") || error_lower.contains("exception")) {
-        reason = "Forge 加载错误".to_string();
-        details = "Forge 模组加载器启动失败".to_string();
-        suggestions = vec![
-            "确保 Forge 版本与游戏版本匹配",
-            "尝试重新安装 Forge",
-            "检查是否有不兼容的模组",
-            "确保 Java 版本正确",
-        ];
-    }
-    else if error_lower.contains("fabric") && (error_lower.contains("error") || error_lower.contains("failed")) {
-        reason = "Fabric 加载错误".to_string();
-        details = "Fabric 模组加载器启动失败".to_string();
-        suggestions = vec![
-            "确保 Fabric 版本与游戏版本匹配",
-            "检查是否安装了 Fabric API",
-            "检查模组是否与 Fabric 版本兼容",
-        ];
-    }
-    // 9. 文件缺失
-    else if error_lower.contains("filenotfoundexception") 
-        || error_lower.contains("nosuchfileexception")
-        || error_lower.contains("file not found")
-        || error_text.contains("FileNotFoundException")
-        || error_text.contains("NoSuchFileException") {
-        reason = "游戏文件缺失".to_string();
-        
-        // 尝试提取缺失的文件名
-        for line in error_logs.iter() {
-            if line.contains("FileNotFoundException") || line.contains("NoSuchFileException") {
-                details = format!("文件缺失: {}", line);
-                break;
-            }
-        }
-        if details.starts_with("退出码") {
-            details = "部分游戏文件丢失或损坏".to_string();
-        }
-        
-        suggestions = vec![
-            "尝试重新下载游戏版本",
-            "检查游戏目录是否完整",
-            "确保杀毒软件没有删除游戏文件",
-            "检查磁盘空间是否充足",
-        ];
-    }
-    // 10. 权限问题
-    else if error_lower.contains("access denied") 
-        || error_lower.contains("permission denied")
-        || error_lower.contains("accessdeniedexception") {
-        reason = "权限不足".to_string();
-        details = "游戏没有足够的权限访问某些文件或目录".to_string();
-        suggestions = vec![
-            "尝试以管理员身份运行启动器",
-            "检查游戏目录的权限设置",
-            "确保杀毒软件没有阻止游戏运行",
-            "尝试将游戏安装到其他目录",
-        ];
-    }
-    // 11. 网络错误
-    else if error_lower.contains("connection") && (error_lower.contains("refused") || error_lower.contains("timeout") || error_lower.contains("reset")) {
-        reason = "网络连接问题".to_string();
-        details = "无法连接到游戏服务器或下载资源".to_string();
-        suggestions = vec![
-            "检查网络连接",
-            "如果使用正版登录，检查 Minecraft 服务器状态",
-            "尝试使用 VPN 或更换网络",
-        ];
-    }
-    // 12. 通用 Java 异常
-    else if error_text.contains("Exception") || error_text.contains("Error") {
-        reason = "游戏崩溃".to_string();
-        
-        // 尝试提取异常信息
-        for line in error_logs.iter().rev() {
-            if (line.contains("Exception") || line.contains("Error")) && !line.contains("at ") {
-                details = line.clone();
-                break;
-            }
-        }
-        
-        suggestions = vec![
-            "查看完整的错误日志了解详情",
-            "尝试重新启动游戏",
-            "如果问题持续，尝试重新安装游戏",
-            "检查是否有模组导致问题",
-        ];
-    }
-    // 13. 未知错误
-    else {
-        suggestions = vec![
-            "查看游戏日志了解详情",
-            "尝试重新启动游戏",
-            "检查游戏文件是否完整",
-            "尝试使用不同的 Java 版本",
-        ];
-    }
-    
-    serde_json::json!({
-        "reason": reason,
-        "details": details,
-        "suggestions": suggestions,
-        "exitCode": exit_code
-    })
-}
-
-
-
 // 进程监控任务 - 监控游戏进程状态，当所有游戏退出时恢复窗口
 pub async fn start_process_monitor(
     app_handle: tauri::AppHandle,
@@ -1859,133 +1987,87 @@ pub async fn start_process_monitor(
 pub async fn export_crash_report(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     save_path: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     use std::io::Write;
     use zip::write::SimpleFileOptions;
     
+    // 外置登录账户的脚本需要 authlib-injector 参数，获取时可能联网，不持有状态锁
+    let (data_dir, account) = {
+        let app_state = state.lock().await;
+        if app_state.last_crash_data.is_none() {
+            return Err(LauncherError::NoCrashData);
+        }
+        let account = app_state.accounts_data.current_account.as_ref()
+            .and_then(|id| app_state.accounts_data.accounts.iter().find(|a| &a.id == id))
+            .cloned();
+        (app_state.data_dir.clone(), account)
+    };
+    let agent_args = match &account {
+        Some(account) => script_agent_args(&data_dir, account).await,
+        None => Ok(Vec::new()),
+    };
+    
     let app_state = state.lock().await;
     
     // 获取崩溃数据
     let crash_data = app_state.last_crash_data.clone()
-        .ok_or("没有崩溃数据")?;
+        .ok_or(LauncherError::NoCrashData)?;
+    
+    // 导出的所有文本都经过脱敏，规则见 LauncherSettings.redaction
+    let mut redactor = crate::redaction::Redactor::from_state(&app_state);
     
     let version_id = crash_data["versionId"].as_str().unwrap_or("unknown");
-    let exit_code = crash_data["exitCode"].as_i64().unwrap_or(-1);
-    let crash_info = &crash_data["crashInfo"];
+    let crash_reports = crash_data["crashInfo"]["crashReports"].as_array().cloned().unwrap_or_default();
     let error_logs: Vec<String> = crash_data["errorLogs"]
         .as_array()
         .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
         .unwrap_or_default();
     
-    // 获取设置
-    let mc_dir = app_state.launcher_settings.minecraft_dir.clone()
-        .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string());
-    let java_path = app_state.launcher_settings.java_path.clone()
-        .unwrap_or_else(|| "java".to_string());
-    
-    // 获取版本特定设置
-    let version_settings = app_state.launcher_settings.version_settings.get(version_id);
-    let effective_java = version_settings
-        .and_then(|vs| vs.java_path.clone())
-        .unwrap_or_else(|| java_path.clone());
-    let memory_min = version_settings
-        .and_then(|vs| vs.memory_min)
-        .unwrap_or(app_state.launcher_settings.memory.min);
-    let memory_max = version_settings
-        .and_then(|vs| vs.memory_max)
-        .unwrap_or(app_state.launcher_settings.memory.max);
+    // 获取设置，日志、崩溃报告和模组都在版本实际使用的游戏目录中
+    let (mc_dir, effective_java, _, _) = crash_launch_settings(&app_state, version_id);
+    let game_dir = crate::mods::game_dir_for_version(&app_state.launcher_settings, &mc_dir, version_id);
     
     // 创建 ZIP 文件
-    let file = std::fs::File::create(&save_path)
-        .map_err(|e| format!("无法创建文件: {}", e))?;
+    let file = std::fs::File::create(&save_path)?;
     let mut zip = zip::ZipWriter::new(file);
     let options = SimpleFileOptions::default()
         .compression_method(zip::CompressionMethod::Deflated);
     
-    // 1. 生成启动脚本
-    let launch_script = generate_launch_script_content(
-        &mc_dir,
-        version_id,
-        &effective_java,
-        memory_min,
-        memory_max,
-    );
-    
-    zip.start_file("launch_script.bat", options)
-        .map_err(|e| e.to_string())?;
-    zip.write_all(launch_script.as_bytes())
-        .map_err(|e| e.to_string())?;
+    // 1. 生成启动脚本（使用当前账户，令牌以环境变量占位）
+    if let Some(account) = &account {
+        let shell = crate::launch_script::ScriptShell::native();
+        let notes: Vec<String> = match &agent_args {
+            Ok(_) => Vec::new(),
+            Err(e) => vec![format!("缺少 authlib-injector 参数，外置登录账户无法直接用此脚本进入游戏: {}", e)],
+        };
+        let agent_args = agent_args.as_deref().unwrap_or_default();
+        // 压缩包中的脚本用于排查，参数直接写在脚本中便于阅读
+        match generate_launch_script_content(&mc_dir, version_id, &effective_java, account, agent_args, &app_state.launcher_settings, shell, false, None, &notes) {
+            Ok(launch_script) => {
+                zip.start_file(format!("launch_script.{}", shell.extension()), options)
+                    .map_err(zip_error)?;
+                zip.write_all(redactor.redact(&launch_script.script).as_bytes())?;
+            }
+            Err(e) => println!("[Export] Failed to generate launch script: {}", e),
+        }
+    }
     
     // 2. 崩溃分析报告
-    let crash_report = format!(
-        r#"╔══════════════════════════════════════════════════════════════════╗
-║              ImagineLauncher 崩溃报告                            ║
-╚══════════════════════════════════════════════════════════════════╝
-
-导出时间: {}
-游戏版本: {}
-退出码: {}
-
-═══════════════════════════════════════════════════════════════════
-                           崩溃分析
-═══════════════════════════════════════════════════════════════════
-
-崩溃原因: {}
-详细信息: {}
-
-解决建议:
-{}
-
-═══════════════════════════════════════════════════════════════════
-                         启动器设置
-═══════════════════════════════════════════════════════════════════
-
-Minecraft 目录: {}
-Java 路径: {}
-内存设置: {}MB - {}MB
-
-═══════════════════════════════════════════════════════════════════
-                           系统信息
-═══════════════════════════════════════════════════════════════════
-
-{}
-
-"#,
-        chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
-        version_id,
-        exit_code,
-        crash_info["reason"].as_str().unwrap_or("未知"),
-        crash_info["details"].as_str().unwrap_or("-"),
-        crash_info["suggestions"]
-            .as_array()
-            .map(|arr| arr.iter()
-                .filter_map(|v| v.as_str())
-                .map(|s| format!("  • {}", s))
-                .collect::<Vec<_>>()
-                .join("\n"))
-            .unwrap_or_default(),
-        mc_dir,
-        effective_java,
-        memory_min,
-        memory_max,
-        get_system_info(),
-    );
+    let crash_report = render_crash_summary(&app_state, &crash_data);
     
     zip.start_file("crash_report.txt", options)
-        .map_err(|e| e.to_string())?;
-    zip.write_all(crash_report.as_bytes())
-        .map_err(|e| e.to_string())?;
+        .map_err(zip_error)?;
+    zip.write_all(redactor.redact(&crash_report).as_bytes())?;
     
     // 3. 游戏错误日志 (从崩溃数据)
     if !error_logs.is_empty() {
         zip.start_file("game_stderr.log", options)
-            .map_err(|e| e.to_string())?;
-        zip.write_all(error_logs.join("\n").as_bytes())
-            .map_err(|e| e.to_string())?;
+            .map_err(zip_error)?;
+        zip.write_all(redactor.redact(&error_logs.join("\n")).as_bytes())?;
     }
     
     // 4. 读取 MC 的 latest.log
-    let logs_dir = Path::new(&mc_dir).join("logs");
+    let logs_dir = game_dir.join("logs");
     println!("[Export] Logs dir: {:?}, exists: {}", logs_dir, logs_dir.exists());
     
     let latest_log_path = logs_dir.join("latest.log");
@@ -1996,9 +2078,8 @@ Java 路径: {}
             Ok(content) => {
                 println!("[Export] Read latest.log, size: {} bytes", content.len());
                 zip.start_file("logs/latest.log", options)
-                    .map_err(|e| e.to_string())?;
-                zip.write_all(content.as_bytes())
-                    .map_err(|e| e.to_string())?;
+                    .map_err(zip_error)?;
+                zip.write_all(redactor.redact(&content).as_bytes())?;
             }
             Err(e) => {
                 println!("[Export] Failed to read latest.log: {}", e);
@@ -2012,9 +2093,8 @@ Java 路径: {}
         if let Ok(content) = std::fs::read_to_string(&fml_log_path) {
             println!("[Export] Read fml-client-latest.log, size: {} bytes", content.len());
             zip.start_file("logs/fml-client-latest.log", options)
-                .map_err(|e| e.to_string())?;
-            zip.write_all(content.as_bytes())
-                .map_err(|e| e.to_string())?;
+                .map_err(zip_error)?;
+            zip.write_all(redactor.redact(&content).as_bytes())?;
         }
     }
     
@@ -2037,22 +2117,21 @@ Java 路径: {}
                 time_b.cmp(&time_a)
             });
             
-            // 取最新的 3 个压缩日志
+            // 取最新的 3 个压缩日志，解压后脱敏再写入
             for gz_file in gz_files.iter().take(3) {
-                let filename = gz_file.file_name().to_string_lossy().to_string();
-                if let Ok(content) = std::fs::read(gz_file.path()) {
+                let filename = gz_file.file_name().to_string_lossy().trim_end_matches(".gz").to_string();
+                if let Some(content) = read_gz_log(&gz_file.path()) {
                     println!("[Export] Adding compressed log: {}", filename);
                     zip.start_file(format!("logs/{}", filename), options)
-                        .map_err(|e| e.to_string())?;
-                    zip.write_all(&content)
-                        .map_err(|e| e.to_string())?;
+                        .map_err(zip_error)?;
+                    zip.write_all(redactor.redact(&content).as_bytes())?;
                 }
             }
         }
     }
     
     // 5. 读取 MC 的 crash-reports 目录下最新的崩溃报告
-    let crash_reports_dir = Path::new(&mc_dir).join("crash-reports");
+    let crash_reports_dir = game_dir.join("crash-reports");
     if crash_reports_dir.exists() {
         if let Ok(entries) = std::fs::read_dir(&crash_reports_dir) {
             let mut crash_files: Vec<_> = entries
@@ -2071,14 +2150,23 @@ Java 路径: {}
                 if let Ok(content) = std::fs::read_to_string(latest_crash.path()) {
                     let filename = latest_crash.file_name().to_string_lossy().to_string();
                     zip.start_file(format!("crash-reports/{}", filename), options)
-                        .map_err(|e| e.to_string())?;
-                    zip.write_all(content.as_bytes())
-                        .map_err(|e| e.to_string())?;
+                        .map_err(zip_error)?;
+                    zip.write_all(redactor.redact(&content).as_bytes())?;
                 }
             }
         }
     }
     
+    // 5.1 JVM 致命错误日志 (hs_err_pid*.log)
+    for entry in crash_reports.iter().filter(|entry| entry["report"]["kind"] == "jvm") {
+        let path = Path::new(entry["path"].as_str().unwrap_or_default());
+        if let (Some(filename), Ok(content)) = (path.file_name(), std::fs::read(path)) {
+            zip.start_file(format!("crash-reports/{}", filename.to_string_lossy()), options)
+                .map_err(zip_error)?;
+            zip.write_all(redactor.redact(&String::from_utf8_lossy(&content)).as_bytes())?;
+        }
+    }
+    
     // 6. 版本 JSON 文件
     let version_json_path = Path::new(&mc_dir)
         .join("versions")
@@ -2087,9 +2175,8 @@ Java 路径: {}
     if version_json_path.exists() {
         if let Ok(content) = std::fs::read_to_string(&version_json_path) {
             zip.start_file(format!("version_{}.json", version_id), options)
-                .map_err(|e| e.to_string())?;
-            zip.write_all(content.as_bytes())
-                .map_err(|e| e.to_string())?;
+                .map_err(zip_error)?;
+            zip.write_all(redactor.redact(&content).as_bytes())?;
         }
     }
     
@@ -2097,12 +2184,11 @@ Java 路径: {}
     let settings_json = serde_json::to_string_pretty(&app_state.launcher_settings)
         .unwrap_or_default();
     zip.start_file("launcher_settings.json", options)
-        .map_err(|e| e.to_string())?;
-    zip.write_all(settings_json.as_bytes())
-        .map_err(|e| e.to_string())?;
+        .map_err(zip_error)?;
+    zip.write_all(redactor.redact(&settings_json).as_bytes())?;
     
     // 8. Mods 列表 (如果存在)
-    let mods_dir = Path::new(&mc_dir).join("mods");
+    let mods_dir = game_dir.join("mods");
     if mods_dir.exists() {
         if let Ok(entries) = std::fs::read_dir(&mods_dir) {
             let mods_list: Vec<String> = entries
@@ -2113,163 +2199,325 @@ Java 路径: {}
             
             if !mods_list.is_empty() {
                 let mods_content = format!(
-                    "已安装的 Mods ({} 个):\n\n{}",
-                    mods_list.len(),
+                    "{}\n\n{}",
+                    crate::i18n::t("crashReport.modsList", &serde_json::json!({ "count": mods_list.len() })),
                     mods_list.join("\n")
                 );
                 zip.start_file("mods_list.txt", options)
-                    .map_err(|e| e.to_string())?;
-                zip.write_all(mods_content.as_bytes())
-                    .map_err(|e| e.to_string())?;
+                    .map_err(zip_error)?;
+                zip.write_all(redactor.redact(&mods_content).as_bytes())?;
             }
         }
     }
     
-    zip.finish().map_err(|e| e.to_string())?;
+    // 9. 脱敏说明
+    zip.start_file("redaction_summary.txt", options)
+        .map_err(zip_error)?;
+    zip.write_all(redactor.summary_text().as_bytes())?;
+    
+    zip.finish().map_err(zip_error)?;
     
     Ok(serde_json::json!({
         "success": true,
-        "path": save_path
+        "path": save_path,
+        "redaction": redactor.summary()
     }))
 }
 
+fn zip_error(e: zip::result::ZipError) -> LauncherError {
+    LauncherError::Io { message: e.to_string() }
+}
+
+fn read_gz_log(path: &Path) -> Option<String> {
+    use std::io::Read;
+    let mut decoder = flate2::read::GzDecoder::new(std::fs::File::open(path).ok()?);
+    let mut content = Vec::new();
+    decoder.read_to_end(&mut content).ok()?;
+    Some(String::from_utf8_lossy(&content).to_string())
+}
+
 // Tauri 命令：生成启动脚本
+// shell 为 sh / bat / ps1，未指定时按保存路径的扩展名判断；include_token 为 true 时才把访问令牌写入脚本
 #[tauri::command]
 pub async fn generate_launch_script(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     version_id: String,
     save_path: String,
-) -> Result<serde_json::Value, String> {
+    account_id: Option<String>,
+    shell: Option<String>,
+    include_token: Option<bool>,
+) -> Result<serde_json::Value, LauncherError> {
+    let shell = match shell {
+        Some(name) => crate::launch_script::ScriptShell::from_name(&name)
+            .ok_or(LauncherError::UnsupportedShell { shell: name })?,
+        None => crate::launch_script::ScriptShell::for_path(&save_path)
+            .unwrap_or_else(crate::launch_script::ScriptShell::native),
+    };
+    
     let app_state = state.lock().await;
     
     let mc_dir = app_state.launcher_settings.minecraft_dir.clone()
         .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string());
     
-    // 获取版本特定设置
-    let version_settings = app_state.launcher_settings.version_settings.get(&version_id);
+    let account = match account_id.or_else(|| app_state.accounts_data.current_account.clone()) {
+        Some(id) => app_state.accounts_data.accounts.iter().find(|a| a.id == id).cloned()
+            .ok_or(LauncherError::AccountNotFound { account_id: id })?,
+        None => return Err(LauncherError::NoAccountSelected),
+    };
     
-    let java_path = version_settings
-        .and_then(|vs| vs.java_path.clone())
-        .or_else(|| app_state.launcher_settings.java_path.clone())
-        .unwrap_or_else(|| "java".to_string());
+    let settings = app_state.launcher_settings.clone();
+    let cache_dir = app_state.get_cache_dir();
+    let data_dir = app_state.data_dir.clone();
+    drop(app_state);
     
-    let memory_min = version_settings
-        .and_then(|vs| vs.memory_min)
-        .unwrap_or(app_state.launcher_settings.memory.min);
-    let memory_max = version_settings
-        .and_then(|vs| vs.memory_max)
-        .unwrap_or(app_state.launcher_settings.memory.max);
+    // 与启动时相同的 Java 选择逻辑，没有匹配版本时使用最接近的候选
+    let required_major = crate::launcher::resolve_required_java_major(&mc_dir, &version_id);
+    let java_path = select_java_for_version(&settings, &cache_dir, &mc_dir, &version_id, required_major).await
+        .unwrap_or_else(|best| best.unwrap_or_else(|| "java".to_string()));
+    
+    // 参数写入脚本旁的 <脚本名>.args；Java 8 不支持参数文件，仍写在脚本中
+    let java_major = crate::launcher::probe_java_with_cache(&cache_dir, &java_path, &java_path).await
+        .map(|java| java.major_version)
+        .or(required_major);
+    let argfile_path = java_major.is_none_or(crate::launch_script::supports_argfile)
+        .then(|| Path::new(&save_path).with_extension("args"));
+    let argfile_name = argfile_path.as_ref()
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().to_string());
+    
+    let agent_args = script_agent_args(&data_dir, &account).await?;
     
     let script_content = generate_launch_script_content(
         &mc_dir,
         &version_id,
         &java_path,
-        memory_min,
-        memory_max,
-    );
+        &account,
+        &agent_args,
+        &settings,
+        shell,
+        include_token.unwrap_or(false),
+        argfile_name.as_deref(),
+        &[],
+    )
+    .map_err(|message| LauncherError::LaunchFailed { version_id: version_id.clone(), message })?;
     
     // 写入文件
-    std::fs::write(&save_path, script_content)
-        .map_err(|e| format!("无法写入文件: {}", e))?;
+    std::fs::write(&save_path, &script_content.script)?;
+    if let (Some(path), Some(argfile)) = (&argfile_path, &script_content.argfile) {
+        std::fs::write(path, argfile)?;
+    }
+    
+    #[cfg(unix)]
+    if shell == crate::launch_script::ScriptShell::Sh {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&save_path, std::fs::Permissions::from_mode(0o755));
+    }
     
     Ok(serde_json::json!({
         "success": true,
-        "path": save_path
+        "path": save_path,
+        "argfilePath": argfile_path.map(|path| path.to_string_lossy().to_string()),
+        "shell": shell.extension()
     }))
 }
 
-// 内部函数：生成启动脚本内容
+// 启动脚本使用的 javaagent 参数
+// 外置登录需要 authlib-injector；离线皮肤依赖启动器运行时的本地皮肤服务器，脚本中不包含
+async fn script_agent_args(data_dir: &Path, account: &crate::accounts::Account) -> Result<Vec<String>, LauncherError> {
+    if account.account_type != "yggdrasil" {
+        return Ok(Vec::new());
+    }
+    crate::yggdrasil::build_agent_args(data_dir, account).await
+        .map_err(|message| LauncherError::AgentUnavailable { message })
+}
+
+// 内部函数：生成启动脚本内容，参数与 build_and_launch 实际使用的完全一致
+#[allow(clippy::too_many_arguments)]
 fn generate_launch_script_content(
     mc_dir: &str,
     version_id: &str,
     java_path: &str,
-    memory_min: u32,
-    memory_max: u32,
-) -> String {
-    let mc_path = Path::new(mc_dir);
-    let versions_dir = mc_path.join("versions");
-    let version_dir = versions_dir.join(version_id);
-    let json_path = version_dir.join(format!("{}.json", version_id));
-    let natives_dir = version_dir.join("natives");
+    account: &crate::accounts::Account,
+    agent_args: &[String],
+    settings: &crate::launcher::LauncherSettings,
+    shell: crate::launch_script::ScriptShell,
+    include_token: bool,
+    argfile_name: Option<&str>,
+    notes: &[String],
+) -> Result<crate::launch_script::LaunchScript, String> {
+    let merged = load_merged_version(mc_dir, version_id)?;
+    let command = resolve_launch_command(mc_dir, version_id, &merged, java_path, account, agent_args, settings)?;
+    
+    // 离线账户没有令牌，参数中的 accessToken 只是 UUID
+    let token = account.access_token.as_deref()
+        .filter(|token| !include_token && !token.is_empty());
+    
+    Ok(crate::launch_script::render(shell, &command, version_id, token, argfile_name, notes))
+}
+
+// 获取系统信息
+// 崩溃报告摘要 (crash_report.txt)，按当前语言重新渲染（崩溃后可能切换过语言）
+pub fn render_crash_summary(app_state: &AppState, crash_data: &serde_json::Value) -> String {
+    let version_id = crash_data["versionId"].as_str().unwrap_or("unknown");
+    let exit_code = crash_data["exitCode"].as_i64().unwrap_or(-1);
+    let crash_info = &crash_data["crashInfo"];
+    let (mc_dir, effective_java, memory_min, memory_max) = crash_launch_settings(app_state, version_id);
     
-    // 读取版本 JSON
-    let version_json: serde_json::Value = std::fs::read_to_string(&json_path)
-        .ok()
-        .and_then(|content| serde_json::from_str(&content).ok())
+    let label = |key: &str| crate::i18n::t(&format!("crashReport.{}", key), &serde_json::json!({}));
+    let findings: Vec<(String, String, Vec<String>)> = crash_info["findings"]
+        .as_array()
+        .map(|arr| arr.iter().map(crate::crash_analyzer::localize_finding).collect())
         .unwrap_or_default();
+    let (reason, details, suggestions) = findings.first().cloned()
+        .unwrap_or_else(|| (label("unknown"), "-".to_string(), Vec::new()));
+    let bullet = |items: Vec<String>| items.iter().map(|s| format!("  • {}", s)).collect::<Vec<_>>().join("\n");
+    let others = bullet(findings.iter().skip(1).map(|(reason, details, _)| format!("{}: {}", reason, details)).collect());
+    let culprits = bullet(crash_info["culprits"]
+        .as_array()
+        .map(|arr| arr.iter()
+            .map(|c| format!("[{}] {}", c["kind"].as_str().unwrap_or_default(), c["name"].as_str().unwrap_or_default()))
+            .collect())
+        .unwrap_or_default());
+    let suspected_mods = bullet(crash_info["suspectedMods"]
+        .as_array()
+        .map(|arr| arr.iter()
+            .map(|m| {
+                let reasons: Vec<String> = m["reasons"].as_array()
+                    .map(|reasons| reasons.iter()
+                        .map(|r| crate::i18n::t(r["messageKey"].as_str().unwrap_or_default(), &r["params"]))
+                        .collect())
+                    .unwrap_or_default();
+                format!(
+                    "{} ({}) - {} {:.0}%\n    {}",
+                    m["name"].as_str().or(m["id"].as_str()).unwrap_or_default(),
+                    m["file"].as_str().unwrap_or_default(),
+                    label("confidence"),
+                    m["confidence"].as_f64().unwrap_or(0.0) * 100.0,
+                    reasons.join("; ")
+                )
+            })
+            .collect())
+        .unwrap_or_default());
+    let parsed_reports = crash_info["crashReports"].as_array().into_iter().flatten()
+        .map(|entry| {
+            let report = &entry["report"];
+            let field = |key: &str, value: Option<&str>| value.map(|v| format!("  {}: {}", label(key), v));
+            let fields = match report["kind"].as_str() {
+                Some("jvm") => vec![
+                    field("jvmError", report["error"].as_str()),
+                    field("problematicFrame", report["problematicFrame"].as_str()),
+                    field("javaVersion", report["jreVersion"].as_str()),
+                ],
+                _ => vec![
+                    field("description", report["description"].as_str()),
+                    field("exception", report["exception"]["text"].as_str().and_then(|t| t.lines().next())),
+                    field("rootCause", report["exception"]["rootCause"].as_str()),
+                    field("javaVersion", report["javaVersion"].as_str()),
+                    report["mods"].as_array()
+                        .filter(|mods| !mods.is_empty())
+                        .map(|mods| format!("  {}: {}", label("modCount"), mods.len())),
+                ],
+            };
+            let file = entry["path"].as_str()
+                .and_then(|p| Path::new(p).file_name())
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_default();
+            std::iter::once(file).chain(fields.into_iter().flatten()).collect::<Vec<_>>().join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
     
-    // 合并继承链
-    let merged = merge_version_chain(mc_dir, &version_json).unwrap_or(version_json);
-    
-    // 构建 classpath
-    let classpath = build_classpath(mc_dir, version_id, &merged).unwrap_or_default();
-    
-    let main_class = merged["mainClass"].as_str().unwrap_or("net.minecraft.client.main.Main");
-    
-    // 构建脚本
+    let rule = "═".repeat(67);
+    let section = |key: &str| format!("{}\n{}\n{}", rule, center_text(&label(key), 67), rule);
     format!(
-        r#"@echo off
-REM ═══════════════════════════════════════════════════════════════════
-REM ImagineLauncher 生成的启动脚本
-REM 版本: {}
-REM 生成时间: {}
-REM ═══════════════════════════════════════════════════════════════════
-
-REM 设置工作目录
-cd /d "{}"
-
-REM Java 路径
-set JAVA_PATH="{}"
-
-REM 内存设置
-set MIN_MEMORY={}M
-set MAX_MEMORY={}M
-
-REM Natives 目录
-set NATIVES_DIR="{}"
-
-REM Classpath
-set CLASSPATH={}
-
-REM 主类
-set MAIN_CLASS={}
-
-REM 启动命令
-"%JAVA_PATH%" ^
-    -Xms%MIN_MEMORY% ^
-    -Xmx%MAX_MEMORY% ^
-    -XX:+UnlockExperimentalVMOptions ^
-    -XX:+UseG1GC ^
-    -Djava.library.path="%NATIVES_DIR%" ^
-    -cp "%CLASSPATH%" ^
-    %MAIN_CLASS% ^
-    --username Player ^
-    --version {} ^
-    --gameDir "{}" ^
-    --assetsDir "{}\assets" ^
-    --assetIndex {} ^
-    --accessToken 0 ^
-    --userType legacy
-
-pause
+        r#"╔{border}╗
+║{title}║
+╚{border}╝
+
+{exported_at_label}: {exported_at}
+{version_label}: {version_id}
+{exit_code_label}: {exit_code}
+
+{analysis}
+
+{reason_label}: {reason}
+{details_label}: {details}
+
+{suggestions_label}:
+{suggestions}
+
+{others_label}:
+{others}
+
+{culprits_label}:
+{culprits}
+
+{suspected_mods_label}:
+{suspected_mods}
+
+{reports_section}
+
+{parsed_reports}
+
+{settings}
+
+{mc_dir_label}: {mc_dir}
+{java_label}: {java}
+{memory_label}: {memory_min}MB - {memory_max}MB
+
+{system}
+
+{system_info}
+
 "#,
-        version_id,
-        chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
-        mc_dir,
-        java_path,
-        memory_min,
-        memory_max,
-        natives_dir.display(),
-        classpath,
-        main_class,
-        version_id,
-        mc_dir,
-        mc_dir,
-        merged["assets"].as_str().unwrap_or(version_id),
+        border = "═".repeat(66),
+        title = center_text(&label("title"), 66),
+        exported_at_label = label("exportedAt"),
+        exported_at = chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+        version_label = label("gameVersion"),
+        exit_code_label = label("exitCode"),
+        analysis = section("analysis"),
+        reason_label = label("reason"),
+        details_label = label("details"),
+        suggestions_label = label("suggestions"),
+        suggestions = bullet(suggestions),
+        others_label = label("otherFindings"),
+        others = if others.is_empty() { "  -".to_string() } else { others },
+        culprits_label = label("culprits"),
+        culprits = if culprits.is_empty() { "  -".to_string() } else { culprits },
+        suspected_mods_label = label("suspectedMods"),
+        suspected_mods = if suspected_mods.is_empty() { "  -".to_string() } else { suspected_mods },
+        reports_section = section("crashReports"),
+        parsed_reports = if parsed_reports.is_empty() { "  -".to_string() } else { parsed_reports },
+        settings = section("launcherSettings"),
+        mc_dir_label = label("minecraftDir"),
+        java_label = label("javaPath"),
+        java = effective_java,
+        memory_label = label("memory"),
+        system = section("systemInfo"),
+        system_info = get_system_info(),
     )
 }
 
-// 获取系统信息
+// 崩溃版本的游戏目录、Java 路径和内存设置（版本独立设置优先）
+fn crash_launch_settings(app_state: &AppState, version_id: &str) -> (String, String, u32, u32) {
+    let settings = &app_state.launcher_settings;
+    let mc_dir = settings.minecraft_dir.clone()
+        .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string());
+    let version_settings = settings.version_settings.get(version_id);
+    let java = version_settings
+        .and_then(|vs| vs.java_path.clone())
+        .or_else(|| settings.java_path.clone())
+        .unwrap_or_else(|| "java".to_string());
+    let memory_min = version_settings
+        .and_then(|vs| vs.memory_min)
+        .unwrap_or(settings.memory.min);
+    let memory_max = version_settings
+        .and_then(|vs| vs.memory_max)
+        .unwrap_or(settings.memory.max);
+    (mc_dir, java, memory_min, memory_max)
+}
+
 fn get_system_info() -> String {
     use sysinfo::System;
     
@@ -2284,20 +2532,21 @@ fn get_system_info() -> String {
     let os_version = System::os_version().unwrap_or_else(|| "Unknown".to_string());
     let kernel_version = System::kernel_version().unwrap_or_else(|| "Unknown".to_string());
     
-    format!(
-        r#"操作系统: {} {}
-内核版本: {}
-CPU 核心数: {}
-总内存: {} MB
-已用内存: {} MB
-可用内存: {} MB"#,
-        os_name,
-        os_version,
-        kernel_version,
-        cpu_count,
-        total_memory,
-        used_memory,
-        total_memory - used_memory,
-    )
+    crate::i18n::t("crashReport.system", &serde_json::json!({
+        "os": format!("{} {}", os_name, os_version),
+        "kernel": kernel_version,
+        "cpus": cpu_count,
+        "totalMemory": total_memory,
+        "usedMemory": used_memory,
+        "freeMemory": total_memory - used_memory
+    }))
+}
+
+// 按显示宽度居中（中日韩字符占两列）
+fn center_text(text: &str, width: usize) -> String {
+    let text_width: usize = text.chars().map(|c| if c >= '\u{1100}' { 2 } else { 1 }).sum();
+    let left = width.saturating_sub(text_width) / 2;
+    let right = width.saturating_sub(text_width + left);
+    format!("{}{}{}", " ".repeat(left), text, " ".repeat(right))
 }
 
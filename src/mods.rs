@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
 use zip::ZipArchive;
+use crate::error::LauncherError;
 use crate::launcher::get_default_mc_dir;
 use crate::state::AppState;
 use std::sync::Arc;
@@ -51,7 +52,7 @@ struct BMCLOptiFineVersion {
 }
 
 #[tauri::command]
-pub async fn scan_mods(mods_dir: String) -> Result<serde_json::Value, String> {
+pub async fn scan_mods(mods_dir: String) -> Result<serde_json::Value, LauncherError> {
     let path = Path::new(&mods_dir);
     
     if !path.exists() {
@@ -63,7 +64,7 @@ pub async fn scan_mods(mods_dir: String) -> Result<serde_json::Value, String> {
     
     let mut mods = Vec::new();
     
-    let entries = fs::read_dir(path).map_err(|e| e.to_string())?;
+    let entries = fs::read_dir(path)?;
     
     for entry in entries.flatten() {
         let file_path = entry.path();
@@ -257,16 +258,308 @@ fn extract_toml_value(content: &str, key: &str) -> Option<String> {
     None
 }
 
+// 崩溃归因和模组排查用的模组索引：模组 ID、jar 内类的包前缀、声明的 Mixin 配置和必需依赖
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModIndexEntry {
+    pub file: String,
+    pub id: Option<String>,
+    pub name: String,
+    pub enabled: bool,
+    pub packages: Vec<String>,
+    pub mixin_configs: Vec<String>,
+    pub dependencies: Vec<String>,
+    // 该文件额外提供的模组 ID：provides 别名和 jar-in-jar 内嵌模组（如 fabric-api 中的 fabric-api-base）
+    pub provides: Vec<String>,
+}
+
+// 常被打包进模组的第三方库和游戏本体，不用于归因
+const SHARED_PACKAGES: [&str; 12] = [
+    "net.minecraft", "com.mojang", "java", "javax", "kotlin", "kotlinx",
+    "org.spongepowered", "com.google", "org.apache", "org.slf4j", "it.unimi", "com.llamalad7",
+];
+// 包前缀最多保留的层级
+const PACKAGE_PREFIX_DEPTH: usize = 4;
+// jar-in-jar 最多展开的层数
+const NESTED_JAR_DEPTH: usize = 3;
+
+/// 扫描 mods 目录并建立索引（包含已禁用的模组）
+pub fn build_mod_index(mods_dir: &Path) -> Vec<ModIndexEntry> {
+    let Ok(entries) = fs::read_dir(mods_dir) else {
+        return Vec::new();
+    };
+    
+    let mut index = Vec::new();
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let enabled = file_name.ends_with(".jar");
+        if !enabled && !file_name.ends_with(".jar.disabled") {
+            continue;
+        }
+        
+        let path = entry.path();
+        let info = read_mod_info(&path);
+        let (packages, mixin_configs, dependencies) = read_mod_classes(&path).unwrap_or_default();
+        index.push(ModIndexEntry {
+            name: info.as_ref().map(|i| i.name.clone()).unwrap_or_else(|| {
+                file_name.trim_end_matches(".disabled").trim_end_matches(".jar").to_string()
+            }),
+            id: info.and_then(|i| i.id),
+            file: file_name,
+            enabled,
+            packages,
+            mixin_configs,
+            dependencies,
+            provides: read_provided_ids(&path),
+        });
+    }
+    index
+}
+
+fn read_entry<R: std::io::Read + std::io::Seek>(archive: &mut ZipArchive<R>, name: &str) -> Option<String> {
+    let mut entry = archive.by_name(name).ok()?;
+    let mut content = String::new();
+    std::io::Read::read_to_string(&mut entry, &mut content).ok()?;
+    Some(content)
+}
+
+fn read_json<R: std::io::Read + std::io::Seek>(archive: &mut ZipArchive<R>, name: &str) -> Option<serde_json::Value> {
+    read_entry(archive, name).and_then(|content| serde_json::from_str(&content).ok())
+}
+
+// 读取模组 jar 额外提供的模组 ID，其他模组的依赖可能指向这些 ID 而不是 jar 本身的 ID
+fn read_provided_ids(path: &Path) -> Vec<String> {
+    let Some(mut archive) = fs::File::open(path).ok().and_then(|file| ZipArchive::new(file).ok()) else {
+        return Vec::new();
+    };
+    let mut ids = provided_ids(&mut archive, 0);
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+// provides 别名，以及 fabric.mod.json / quilt.mod.json 的 jars 和 Forge jarjar/metadata.json 中内嵌 jar 的模组 ID
+fn provided_ids<R: std::io::Read + std::io::Seek>(archive: &mut ZipArchive<R>, depth: usize) -> Vec<String> {
+    let mut ids = Vec::new();
+    let mut nested = Vec::new();
+    let strings = |value: &serde_json::Value, key: &str| -> Vec<String> {
+        value.as_array().into_iter().flatten()
+            .filter_map(|item| item.as_str().or_else(|| item[key].as_str()).map(|s| s.to_string()))
+            .collect()
+    };
+
+    if let Some(json) = read_json(archive, "fabric.mod.json") {
+        ids.extend(strings(&json["provides"], "id"));
+        nested.extend(strings(&json["jars"], "file"));
+    }
+    if let Some(json) = read_json(archive, "quilt.mod.json") {
+        ids.extend(strings(&json["quilt_loader"]["provides"], "id"));
+        nested.extend(strings(&json["quilt_loader"]["jars"], "file"));
+    }
+    if let Some(json) = read_json(archive, "META-INF/jarjar/metadata.json") {
+        nested.extend(strings(&json["jars"], "path"));
+    }
+
+    if depth >= NESTED_JAR_DEPTH {
+        return ids;
+    }
+    for jar in nested {
+        let Ok(mut entry) = archive.by_name(&jar) else { continue };
+        let mut bytes = Vec::new();
+        if std::io::Read::read_to_end(&mut entry, &mut bytes).is_err() {
+            continue;
+        }
+        drop(entry);
+        let Ok(mut inner) = ZipArchive::new(std::io::Cursor::new(bytes)) else { continue };
+        ids.extend(own_ids(&mut inner));
+        ids.extend(provided_ids(&mut inner, depth + 1));
+    }
+    ids
+}
+
+// 内嵌 jar 自身的模组 ID
+fn own_ids<R: std::io::Read + std::io::Seek>(archive: &mut ZipArchive<R>) -> Vec<String> {
+    let mut ids = Vec::new();
+    if let Some(id) = read_json(archive, "fabric.mod.json").and_then(|json| json["id"].as_str().map(|s| s.to_string())) {
+        ids.push(id);
+    }
+    if let Some(id) = read_json(archive, "quilt.mod.json").and_then(|json| json["quilt_loader"]["id"].as_str().map(|s| s.to_string())) {
+        ids.push(id);
+    }
+    for toml in ["META-INF/mods.toml", "META-INF/neoforge.mods.toml"] {
+        if let Some(content) = read_entry(archive, toml) {
+            ids.extend(read_toml_mod_ids(&content));
+        }
+    }
+    ids
+}
+
+// mods.toml 中 [[mods]] 块的 modId
+fn read_toml_mod_ids(content: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    let mut in_mods = false;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_mods = line == "[[mods]]";
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=').filter(|_| in_mods) else { continue };
+        if key.trim() == "modId" {
+            let value = value.split('#').next().unwrap_or_default().trim().trim_matches('"');
+            ids.push(value.to_string());
+        }
+    }
+    ids
+}
+
+// 读取 jar 中类文件的包前缀、Mixin 配置和必需依赖的模组 ID
+fn read_mod_classes(path: &Path) -> Option<(Vec<String>, Vec<String>, Vec<String>)> {
+    let file = fs::File::open(path).ok()?;
+    let mut archive = ZipArchive::new(file).ok()?;
+    
+    let mut packages = std::collections::BTreeSet::new();
+    for name in archive.file_names() {
+        let Some(class) = name.strip_suffix(".class") else { continue };
+        let segments: Vec<&str> = class.split('/').collect();
+        if segments.len() < 3 || name.starts_with("META-INF/") {
+            continue;
+        }
+        let package = segments[..(segments.len() - 1).min(PACKAGE_PREFIX_DEPTH)].join(".");
+        let shared = SHARED_PACKAGES.iter()
+            .any(|p| package == *p || package.starts_with(&format!("{}.", p)));
+        if !shared {
+            packages.insert(package);
+        }
+    }
+    
+    let mut mixin_configs = Vec::new();
+    let mut dependencies = Vec::new();
+    // Fabric: "mixins": ["a.mixins.json", { "config": "b.mixins.json" }]，"depends": { "modid": "版本" }
+    if let Some(json) = read_entry(&mut archive, "fabric.mod.json")
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+    {
+        for mixin in json["mixins"].as_array().into_iter().flatten() {
+            if let Some(config) = mixin.as_str().or_else(|| mixin["config"].as_str()) {
+                mixin_configs.push(config.to_string());
+            }
+        }
+        if let Some(depends) = json["depends"].as_object() {
+            dependencies.extend(depends.keys().cloned());
+        }
+    }
+    // Quilt: "mixin": "a.mixins.json" 或数组，"depends": ["modid", { "id": "modid", "optional": true }]
+    if let Some(json) = read_entry(&mut archive, "quilt.mod.json")
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+    {
+        match &json["mixin"] {
+            serde_json::Value::String(config) => mixin_configs.push(config.clone()),
+            serde_json::Value::Array(configs) => mixin_configs.extend(
+                configs.iter().filter_map(|c| c.as_str().map(|s| s.to_string()))
+            ),
+            _ => {}
+        }
+        for depend in json["quilt_loader"]["depends"].as_array().into_iter().flatten() {
+            if depend["optional"].as_bool().unwrap_or(false) {
+                continue;
+            }
+            if let Some(id) = depend.as_str().or_else(|| depend["id"].as_str()) {
+                dependencies.push(id.to_string());
+            }
+        }
+    }
+    // NeoForge: [[mixins]] config = "a.mixins.json"
+    for toml in ["META-INF/mods.toml", "META-INF/neoforge.mods.toml"] {
+        if let Some(content) = read_entry(&mut archive, toml) {
+            mixin_configs.extend(
+                content.lines()
+                    .filter(|line| line.trim_start().starts_with("config"))
+                    .filter_map(|line| line.split_once('=').map(|(_, v)| v.trim().trim_matches('"').to_string()))
+                    .filter(|config| config.ends_with(".json"))
+            );
+            dependencies.extend(read_toml_dependencies(&content));
+        }
+    }
+    // Forge: MANIFEST.MF 中的 MixinConfigs: a.mixins.json,b.mixins.json
+    if let Some(manifest) = read_entry(&mut archive, "META-INF/MANIFEST.MF") {
+        // 清单文件的长行会折行，续行以空格开头
+        let manifest = manifest.replace("\r\n ", "").replace("\n ", "");
+        if let Some(line) = manifest.lines().find(|l| l.starts_with("MixinConfigs:")) {
+            mixin_configs.extend(
+                line["MixinConfigs:".len()..].split(',')
+                    .map(|c| c.trim().to_string())
+                    .filter(|c| !c.is_empty())
+            );
+        }
+    }
+    mixin_configs.sort();
+    mixin_configs.dedup();
+    dependencies.sort();
+    dependencies.dedup();
+    
+    Some((packages.into_iter().collect(), mixin_configs, dependencies))
+}
+
+// mods.toml 中的 [[dependencies.<modid>]]：Forge 用 mandatory = true，NeoForge 用 type = "required"（默认）
+fn read_toml_dependencies(content: &str) -> Vec<String> {
+    let mut dependencies = Vec::new();
+    let mut current: Option<(Option<String>, Option<bool>, Option<String>)> = None;
+    let mut finish = |block: Option<(Option<String>, Option<bool>, Option<String>)>| {
+        if let Some((Some(id), mandatory, kind)) = block {
+            if mandatory.unwrap_or_else(|| kind.map(|k| k == "required").unwrap_or(true)) {
+                dependencies.push(id);
+            }
+        }
+    };
+    
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            finish(current.take());
+            if line.starts_with("[[dependencies") {
+                current = Some((None, None, None));
+            }
+            continue;
+        }
+        let Some((block, (key, value))) = current.as_mut().zip(line.split_once('=')) else { continue };
+        let value = value.split('#').next().unwrap_or_default().trim().trim_matches('"');
+        match key.trim() {
+            "modId" => block.0 = Some(value.to_string()),
+            "mandatory" => block.1 = Some(value == "true"),
+            "type" => block.2 = Some(value.to_lowercase()),
+            _ => {}
+        }
+    }
+    finish(current);
+    dependencies
+}
+
+/// 版本的游戏目录，开启版本隔离时为 versions/<id>；启动、模组排查和崩溃分析都以此为准
+pub fn game_dir_for_version(settings: &crate::launcher::LauncherSettings, mc_dir: &str, version_id: &str) -> std::path::PathBuf {
+    let version_isolation = settings.version_settings
+        .get(version_id)
+        .map(|vs| vs.version_isolation)
+        .unwrap_or(false);
+    if version_isolation {
+        Path::new(mc_dir).join("versions").join(version_id)
+    } else {
+        Path::new(mc_dir).to_path_buf()
+    }
+}
+
+/// 与模组页面一致：游戏目录下的 mods
+pub fn mods_dir_for_version(settings: &crate::launcher::LauncherSettings, mc_dir: &str, version_id: &str) -> std::path::PathBuf {
+    game_dir_for_version(settings, mc_dir, version_id).join("mods")
+}
+
 #[tauri::command]
 pub async fn toggle_mod(
     mods_dir: String,
     file: String,
     enabled: bool,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let path = Path::new(&mods_dir).join(&file);
     
     if !path.exists() {
-        return Err("文件不存在".to_string());
+        return Err(LauncherError::FileNotFound { path: path.to_string_lossy().to_string() });
     }
     
     let new_file = if enabled {
@@ -284,7 +577,7 @@ pub async fn toggle_mod(
     let new_path = Path::new(&mods_dir).join(&new_file);
     
     if path != new_path {
-        fs::rename(&path, &new_path).map_err(|e| e.to_string())?;
+        fs::rename(&path, &new_path)?;
     }
     
     Ok(serde_json::json!({
@@ -294,14 +587,14 @@ pub async fn toggle_mod(
 }
 
 #[tauri::command]
-pub async fn delete_mod(mods_dir: String, file: String) -> Result<serde_json::Value, String> {
+pub async fn delete_mod(mods_dir: String, file: String) -> Result<serde_json::Value, LauncherError> {
     let path = Path::new(&mods_dir).join(&file);
     
     if !path.exists() {
-        return Err("文件不存在".to_string());
+        return Err(LauncherError::FileNotFound { path: path.to_string_lossy().to_string() });
     }
     
-    fs::remove_file(&path).map_err(|e| e.to_string())?;
+    fs::remove_file(&path)?;
     
     Ok(serde_json::json!({
         "success": true
@@ -309,11 +602,11 @@ pub async fn delete_mod(mods_dir: String, file: String) -> Result<serde_json::Va
 }
 
 #[tauri::command]
-pub async fn add_mod(mods_dir: String) -> Result<serde_json::Value, String> {
+pub async fn add_mod(mods_dir: String) -> Result<serde_json::Value, LauncherError> {
     // 确保目录存在
     let path = Path::new(&mods_dir);
     if !path.exists() {
-        fs::create_dir_all(path).map_err(|e| e.to_string())?;
+        fs::create_dir_all(path)?;
     }
     
     // 这里需要通过前端调用文件选择对话框
@@ -329,25 +622,21 @@ pub async fn add_mod(mods_dir: String) -> Result<serde_json::Value, String> {
 pub async fn copy_mod_to_dir(
     source_path: String,
     mods_dir: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let source = Path::new(&source_path);
     
-    if !source.exists() {
-        return Err("源文件不存在".to_string());
-    }
-    
-    let file_name = source.file_name()
-        .ok_or("无效的文件名")?
-        .to_string_lossy()
-        .to_string();
+    let Some(file_name) = source.file_name().filter(|_| source.exists()) else {
+        return Err(LauncherError::FileNotFound { path: source_path });
+    };
+    let file_name = file_name.to_string_lossy().to_string();
     
     let dest_dir = Path::new(&mods_dir);
     if !dest_dir.exists() {
-        fs::create_dir_all(dest_dir).map_err(|e| e.to_string())?;
+        fs::create_dir_all(dest_dir)?;
     }
     
     let dest = dest_dir.join(&file_name);
-    fs::copy(source, &dest).map_err(|e| e.to_string())?;
+    fs::copy(source, &dest)?;
     
     Ok(serde_json::json!({
         "success": true,
@@ -356,15 +645,15 @@ pub async fn copy_mod_to_dir(
 }
 
 #[tauri::command]
-pub async fn open_folder(folder_path: String) -> Result<(), String> {
+pub async fn open_folder(folder_path: String) -> Result<(), LauncherError> {
     let path = Path::new(&folder_path);
     
     // 如果目录不存在，创建它
     if !path.exists() {
-        fs::create_dir_all(path).map_err(|e| e.to_string())?;
+        fs::create_dir_all(path)?;
     }
     
-    open::that(path).map_err(|e| e.to_string())
+    Ok(open::that(path)?)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -380,7 +669,7 @@ pub struct WorldInfo {
 }
 
 #[tauri::command]
-pub async fn scan_worlds(saves_dir: String) -> Result<serde_json::Value, String> {
+pub async fn scan_worlds(saves_dir: String) -> Result<serde_json::Value, LauncherError> {
     let path = Path::new(&saves_dir);
     
     if !path.exists() {
@@ -392,7 +681,7 @@ pub async fn scan_worlds(saves_dir: String) -> Result<serde_json::Value, String>
     
     let mut worlds = Vec::new();
     
-    let entries = fs::read_dir(path).map_err(|e| e.to_string())?;
+    let entries = fs::read_dir(path)?;
     
     for entry in entries.flatten() {
         let entry_path = entry.path();
@@ -476,14 +765,14 @@ fn calculate_dir_size(path: &Path) -> Result<u64, std::io::Error> {
 }
 
 #[tauri::command]
-pub async fn delete_world(saves_dir: String, folder: String) -> Result<serde_json::Value, String> {
+pub async fn delete_world(saves_dir: String, folder: String) -> Result<serde_json::Value, LauncherError> {
     let path = Path::new(&saves_dir).join(&folder);
     
     if !path.exists() {
-        return Err("存档不存在".to_string());
+        return Err(LauncherError::FileNotFound { path: path.to_string_lossy().to_string() });
     }
     
-    fs::remove_dir_all(&path).map_err(|e| e.to_string())?;
+    fs::remove_dir_all(&path)?;
     
     Ok(serde_json::json!({
         "success": true
@@ -500,7 +789,7 @@ pub struct ResourcepackInfo {
 }
 
 #[tauri::command]
-pub async fn scan_resourcepacks(resourcepacks_dir: String) -> Result<serde_json::Value, String> {
+pub async fn scan_resourcepacks(resourcepacks_dir: String) -> Result<serde_json::Value, LauncherError> {
     let path = Path::new(&resourcepacks_dir);
     
     if !path.exists() {
@@ -516,7 +805,7 @@ pub async fn scan_resourcepacks(resourcepacks_dir: String) -> Result<serde_json:
     let cache_dir = Path::new(&resourcepacks_dir).join(".pack_icons");
     let _ = fs::create_dir_all(&cache_dir);
     
-    let entries = fs::read_dir(path).map_err(|e| e.to_string())?;
+    let entries = fs::read_dir(path)?;
     
     for entry in entries.flatten() {
         let file_path = entry.path();
@@ -664,17 +953,17 @@ fn read_resourcepack_info(path: &Path) -> Option<(String, Option<String>)> {
 }
 
 #[tauri::command]
-pub async fn delete_file(file_path: String) -> Result<serde_json::Value, String> {
+pub async fn delete_file(file_path: String) -> Result<serde_json::Value, LauncherError> {
     let path = Path::new(&file_path);
     
     if !path.exists() {
-        return Err("文件不存在".to_string());
+        return Err(LauncherError::FileNotFound { path: path.to_string_lossy().to_string() });
     }
     
     if path.is_dir() {
-        fs::remove_dir_all(path).map_err(|e| e.to_string())?;
+        fs::remove_dir_all(path)?;
     } else {
-        fs::remove_file(path).map_err(|e| e.to_string())?;
+        fs::remove_file(path)?;
     }
     
     Ok(serde_json::json!({
@@ -686,25 +975,21 @@ pub async fn delete_file(file_path: String) -> Result<serde_json::Value, String>
 pub async fn copy_file_to_dir(
     source_path: String,
     dest_dir: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let source = Path::new(&source_path);
     
-    if !source.exists() {
-        return Err("源文件不存在".to_string());
-    }
-    
-    let file_name = source.file_name()
-        .ok_or("无效的文件名")?
-        .to_string_lossy()
-        .to_string();
+    let Some(file_name) = source.file_name().filter(|_| source.exists()) else {
+        return Err(LauncherError::FileNotFound { path: source_path });
+    };
+    let file_name = file_name.to_string_lossy().to_string();
     
     let dest_dir_path = Path::new(&dest_dir);
     if !dest_dir_path.exists() {
-        fs::create_dir_all(dest_dir_path).map_err(|e| e.to_string())?;
+        fs::create_dir_all(dest_dir_path)?;
     }
     
     let dest = dest_dir_path.join(&file_name);
-    fs::copy(source, &dest).map_err(|e| e.to_string())?;
+    fs::copy(source, &dest)?;
     
     Ok(serde_json::json!({
         "success": true,
@@ -717,14 +1002,14 @@ pub async fn copy_file_to_dir(
 #[tauri::command]
 pub async fn get_optifine_versions(
     game_version: Option<String>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     // 尝试多个 API 端点
     let api_urls = vec![
         "https://bmclapi2.bangbang93.com/optifine/versionlist",
         // 备用 API 可以在这里添加
     ];
     
-    let mut last_error = String::new();
+    let mut last_error = None;
     
     for api_url in api_urls {
         println!("[OptiFine] Trying API: {}", api_url);
@@ -733,19 +1018,21 @@ pub async fn get_optifine_versions(
             Ok(result) => return Ok(result),
             Err(e) => {
                 println!("[OptiFine] API {} failed: {}", api_url, e);
-                last_error = e;
+                last_error = Some(e);
                 continue;
             }
         }
     }
     
-    Err(format!("所有 OptiFine API 都失败了，最后一个错误: {}", last_error))
+    Err(last_error.unwrap_or_else(|| LauncherError::Network {
+        message: crate::i18n::t("mods.errors.optifineApiUnavailable", &serde_json::json!({})),
+    }))
 }
 
 async fn try_get_optifine_versions_from_api(
     api_url: &str,
     game_version: &Option<String>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     
     let client = reqwest::Client::new();
     let response = client
@@ -754,18 +1041,11 @@ async fn try_get_optifine_versions_from_api(
         .header("Accept", "application/json")
         .timeout(std::time::Duration::from_secs(30))
         .send()
-        .await
-        .map_err(|e| format!("请求失败: {}", e))?;
-    
-    if !response.status().is_success() {
-        return Err(format!("API 请求失败: {}", response.status()));
-    }
+        .await?
+        .error_for_status()?;
     
     // 先获取响应文本进行调试
-    let response_text = response
-        .text()
-        .await
-        .map_err(|e| format!("读取响应失败: {}", e))?;
+    let response_text = response.text().await?;
     
     println!("[OptiFine] API Response length: {}", response_text.len());
     if response_text.len() > 0 {
@@ -773,7 +1053,7 @@ async fn try_get_optifine_versions_from_api(
     }
     
     if response_text.trim().is_empty() {
-        return Err("API 返回空响应".to_string());
+        return Err(invalid_response("empty response"));
     }
     
     // 尝试解析 JSON
@@ -781,7 +1061,7 @@ async fn try_get_optifine_versions_from_api(
         .map_err(|e| {
             println!("[OptiFine] JSON parse error: {}", e);
             println!("[OptiFine] Raw response: {}", &response_text[..std::cmp::min(1000, response_text.len())]);
-            format!("解析响应失败: {} - Response preview: {}", e, &response_text[..std::cmp::min(200, response_text.len())])
+            invalid_response(e)
         })?;
     
     let mut versions: Vec<OptiFineVersion> = bmcl_versions
@@ -839,13 +1119,13 @@ pub async fn download_optifine(
     game_version: String,
     optifine_version: String,
     download_url: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let app_state = state.lock().await;
     drop(app_state); // 释放锁
     
     // 创建临时下载目录
     let temp_dir = std::env::temp_dir().join("imaginelauncher_optifine");
-    std::fs::create_dir_all(&temp_dir).map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&temp_dir)?;
     
     let installer_filename = format!("OptiFine_{}_{}.jar", 
         game_version, optifine_version);
@@ -858,20 +1138,12 @@ pub async fn download_optifine(
         .get(&download_url)
         .timeout(std::time::Duration::from_secs(300))
         .send()
-        .await
-        .map_err(|e| format!("下载失败: {}", e))?;
-    
-    if !response.status().is_success() {
-        return Err(format!("下载失败: HTTP {}", response.status()));
-    }
+        .await?
+        .error_for_status()?;
     
-    let content = response
-        .bytes()
-        .await
-        .map_err(|e| format!("读取响应失败: {}", e))?;
+    let content = response.bytes().await?;
     
-    std::fs::write(&installer_path, content)
-        .map_err(|e| format!("保存文件失败: {}", e))?;
+    std::fs::write(&installer_path, content)?;
     
     println!("[OptiFine] Downloaded to: {:?}", installer_path);
     
@@ -887,6 +1159,15 @@ pub async fn install_optifine(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     version_id: String,
     installer_path: String,
+) -> Result<serde_json::Value, LauncherError> {
+    let on_error = crate::downloads::install_failed("optifine", &version_id, None);
+    install_optifine_version(&state, version_id, installer_path).await.map_err(on_error)
+}
+
+async fn install_optifine_version(
+    state: &Arc<Mutex<AppState>>,
+    version_id: String,
+    installer_path: String,
 ) -> Result<serde_json::Value, String> {
     let app_state = state.lock().await;
     let mc_dir = app_state.launcher_settings.minecraft_dir.clone()
@@ -894,7 +1175,7 @@ pub async fn install_optifine(
     
     let installer = Path::new(&installer_path);
     if !installer.exists() {
-        return Err("安装器文件不存在".to_string());
+        return Err(crate::i18n::t("mods.errors.optifineInstallerMissing", &serde_json::json!({ "path": installer_path })));
     }
     
     // 验证 OptiFine 安装器并提取版本信息
@@ -905,14 +1186,14 @@ pub async fn install_optifine(
     let version_json_path = version_dir.join(format!("{}.json", version_id));
     
     if !version_json_path.exists() {
-        return Err("目标版本不存在".to_string());
+        return Err(crate::i18n::t("mods.errors.versionMissing", &serde_json::json!({ "versionId": version_id })));
     }
     
     // 读取版本 JSON 验证游戏版本
     let version_content = std::fs::read_to_string(&version_json_path)
-        .map_err(|e| format!("读取版本文件失败: {}", e))?;
+        .map_err(|e| e.to_string())?;
     let version_json: serde_json::Value = serde_json::from_str(&version_content)
-        .map_err(|e| format!("解析版本文件失败: {}", e))?;
+        .map_err(|e| e.to_string())?;
     
     // 获取实际的游戏版本
     let actual_game_version = if let Some(inherits) = version_json["inheritsFrom"].as_str() {
@@ -922,10 +1203,10 @@ pub async fn install_optifine(
     };
     
     if normalize_version(&mc_version) != normalize_version(&actual_game_version) {
-        return Err(format!(
-            "版本不匹配: OptiFine 需要 {} 但目标版本是 {}",
-            mc_version, actual_game_version
-        ));
+        return Err(crate::i18n::t("mods.errors.optifineVersionMismatch", &serde_json::json!({
+            "required": mc_version,
+            "actual": actual_game_version
+        })));
     }
     
     // 创建 OptiFine 版本
@@ -934,18 +1215,18 @@ pub async fn install_optifine(
     let optifine_version_dir = Path::new(&mc_dir).join("versions").join(&optifine_version_id);
     
     std::fs::create_dir_all(&optifine_version_dir)
-        .map_err(|e| format!("创建版本目录失败: {}", e))?;
+        .map_err(|e| e.to_string())?;
     
     // 复制 OptiFine 安装器到 libraries 目录
     let libraries_dir = Path::new(&mc_dir).join("libraries").join("optifine").join("OptiFine");
     let optifine_lib_version = format!("{}_{}_{}_{}", mc_version, of_edition, of_release, "installer");
     let optifine_lib_dir = libraries_dir.join(&optifine_lib_version);
     std::fs::create_dir_all(&optifine_lib_dir)
-        .map_err(|e| format!("创建库目录失败: {}", e))?;
+        .map_err(|e| e.to_string())?;
     
     let optifine_lib_path = optifine_lib_dir.join(format!("OptiFine-{}-installer.jar", optifine_lib_version));
     std::fs::copy(installer, &optifine_lib_path)
-        .map_err(|e| format!("复制 OptiFine 库失败: {}", e))?;
+        .map_err(|e| e.to_string())?;
     
     // 创建 OptiFine 版本 JSON
     let optifine_json = create_optifine_version_json(
@@ -958,7 +1239,7 @@ pub async fn install_optifine(
     
     let optifine_json_path = optifine_version_dir.join(format!("{}.json", optifine_version_id));
     std::fs::write(&optifine_json_path, serde_json::to_string_pretty(&optifine_json).unwrap())
-        .map_err(|e| format!("创建版本文件失败: {}", e))?;
+        .map_err(|e| e.to_string())?;
     
     // 清理临时文件
     let _ = std::fs::remove_file(installer);
@@ -968,7 +1249,7 @@ pub async fn install_optifine(
     Ok(serde_json::json!({
         "success": true,
         "version_id": optifine_version_id,
-        "message": format!("OptiFine {} 安装成功", optifine_version_id)
+        "message": crate::i18n::message("mods.optifineInstalled", serde_json::json!({ "versionId": optifine_version_id }))
     }))
 }
 
@@ -992,10 +1273,10 @@ fn lookup_version(version: &str) -> String {
 
 fn extract_optifine_info(installer_path: &Path) -> Result<(String, String, String), String> {
     let file = std::fs::File::open(installer_path)
-        .map_err(|e| format!("打开安装器失败: {}", e))?;
+        .map_err(|e| e.to_string())?;
     
     let mut archive = zip::ZipArchive::new(file)
-        .map_err(|e| format!("读取安装器失败: {}", e))?;
+        .map_err(|e| e.to_string())?;
     
     // 查找 Config.class 文件
     let config_paths = [
@@ -1008,23 +1289,33 @@ fn extract_optifine_info(installer_path: &Path) -> Result<(String, String, Strin
         if let Ok(mut config_file) = archive.by_name(config_path) {
             let mut content = Vec::new();
             std::io::Read::read_to_end(&mut config_file, &mut content)
-                .map_err(|e| format!("读取 Config.class 失败: {}", e))?;
+                .map_err(|e| e.to_string())?;
             
             // 简单的字符串搜索来提取版本信息
             let content_str = String::from_utf8_lossy(&content);
             
             let mc_version = extract_string_constant(&content_str, "MC_VERSION")
-                .ok_or("无法提取 MC_VERSION")?;
+                .ok_or_else(invalid_installer)?;
             let of_edition = extract_string_constant(&content_str, "OF_EDITION")
-                .ok_or("无法提取 OF_EDITION")?;
+                .ok_or_else(invalid_installer)?;
             let of_release = extract_string_constant(&content_str, "OF_RELEASE")
-                .ok_or("无法提取 OF_RELEASE")?;
+                .ok_or_else(invalid_installer)?;
             
             return Ok((mc_version, of_edition, of_release));
         }
     }
     
-    Err("无法找到 Config.class 文件".to_string())
+    Err(invalid_installer())
+}
+
+fn invalid_installer() -> String {
+    crate::i18n::t("mods.errors.invalidOptifineInstaller", &serde_json::json!({}))
+}
+
+fn invalid_response(e: impl std::fmt::Display) -> LauncherError {
+    LauncherError::Network {
+        message: crate::i18n::t("mods.errors.invalidResponse", &serde_json::json!({ "message": e.to_string() })),
+    }
 }
 
 fn extract_string_constant(content: &str, constant_name: &str) -> Option<String> {
@@ -1107,7 +1398,7 @@ fn create_optifine_version_json(
     })
 }
 #[tauri::command]
-pub async fn test_optifine_api() -> Result<serde_json::Value, String> {
+pub async fn test_optifine_api() -> Result<serde_json::Value, LauncherError> {
     let api_url = "https://bmclapi2.bangbang93.com/optifine/versionlist";
     
     println!("[OptiFine] Testing API: {}", api_url);
@@ -1119,25 +1410,17 @@ pub async fn test_optifine_api() -> Result<serde_json::Value, String> {
         .header("Accept", "application/json")
         .timeout(std::time::Duration::from_secs(30))
         .send()
-        .await
-        .map_err(|e| format!("请求失败: {}", e))?;
+        .await?;
     
     println!("[OptiFine] Response status: {}", response.status());
     
-    if !response.status().is_success() {
-        return Err(format!("API 请求失败: {}", response.status()));
-    }
-    
-    let response_text = response
-        .text()
-        .await
-        .map_err(|e| format!("读取响应失败: {}", e))?;
+    let response_text = response.error_for_status()?.text().await?;
     
     println!("[OptiFine] Response length: {}", response_text.len());
     
     // 尝试解析前几个条目
     let bmcl_versions: Vec<BMCLOptiFineVersion> = serde_json::from_str(&response_text)
-        .map_err(|e| format!("解析失败: {}", e))?;
+        .map_err(invalid_response)?;
     
     println!("[OptiFine] Parsed {} versions", bmcl_versions.len());
     
@@ -1190,7 +1473,7 @@ pub struct ModDownloadRequest {
 pub async fn download_mod(
     state: tauri::State<'_, Arc<Mutex<AppState>>>,
     request: ModDownloadRequest,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LauncherError> {
     let app_state = state.lock().await;
     let mc_dir = app_state.launcher_settings.minecraft_dir.clone()
         .unwrap_or_else(|| get_default_mc_dir().to_string_lossy().to_string());
@@ -1210,8 +1493,7 @@ pub async fn download_mod(
     };
     
     // 确保目录存在
-    std::fs::create_dir_all(&mods_dir)
-        .map_err(|e| format!("创建 mods 目录失败: {}", e))?;
+    std::fs::create_dir_all(&mods_dir)?;
     
     let file_path = mods_dir.join(&request.filename);
     
@@ -1224,17 +1506,10 @@ pub async fn download_mod(
         .header("User-Agent", "ImagineLauncher/1.0.0")
         .timeout(std::time::Duration::from_secs(300))
         .send()
-        .await
-        .map_err(|e| format!("下载失败: {}", e))?;
-    
-    if !response.status().is_success() {
-        return Err(format!("下载失败: HTTP {}", response.status()));
-    }
+        .await?
+        .error_for_status()?;
     
-    let content = response
-        .bytes()
-        .await
-        .map_err(|e| format!("读取响应失败: {}", e))?;
+    let content = response.bytes().await?;
     
     // 验证哈希（如果提供）
     if let Some(expected_hash) = &request.hash {
@@ -1246,15 +1521,18 @@ pub async fn download_mod(
             let actual_hash = format!("{:x}", hasher.finalize());
             
             if actual_hash != *expected_hash {
-                return Err(format!("文件校验失败: 期望 {} 实际 {}", expected_hash, actual_hash));
+                return Err(LauncherError::ChecksumMismatch {
+                    file: request.filename.clone(),
+                    expected: expected_hash.clone(),
+                    actual: actual_hash,
+                });
             }
         }
         // SHA-512 暂时跳过验证，因为没有 sha2 依赖
     }
     
     // 保存文件
-    std::fs::write(&file_path, content)
-        .map_err(|e| format!("保存文件失败: {}", e))?;
+    std::fs::write(&file_path, content)?;
     
     println!("[Mod] Downloaded successfully: {:?}", file_path);
     
@@ -1264,3 +1542,135 @@ pub async fn download_mod(
         "filename": request.filename
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn zip_bytes(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, content) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    // 写入临时目录，测试结束时删除
+    struct TempJar(std::path::PathBuf);
+
+    impl TempJar {
+        fn new(name: &str, entries: &[(&str, &[u8])]) -> Self {
+            let path = std::env::temp_dir().join(format!("mods-test-{}-{}", std::process::id(), name));
+            fs::write(&path, zip_bytes(entries)).unwrap();
+            TempJar(path)
+        }
+    }
+
+    impl Drop for TempJar {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn forge_dependencies_use_mandatory() {
+        let toml = r#"
+modLoader="javafml"
+[[mods]]
+modId="examplemod"
+[[dependencies.examplemod]]
+    modId="forge"
+    mandatory=true
+    versionRange="[47,)"
+[[dependencies.examplemod]]
+    modId="jei"
+    mandatory=false # 可选
+[[dependencies.examplemod]]
+    modId="geckolib"
+    mandatory=true
+"#;
+        assert_eq!(read_toml_dependencies(toml), ["forge", "geckolib"]);
+        assert_eq!(read_toml_mod_ids(toml), ["examplemod"]);
+    }
+
+    #[test]
+    fn neoforge_dependencies_use_type() {
+        let toml = r#"
+[[mods]]
+modId = "examplemod" # 注释
+[[dependencies.examplemod]]
+modId = "neoforge"
+type = "required"
+[[dependencies.examplemod]]
+modId = "jei"
+type = "optional"
+[[dependencies.examplemod]]
+modId = "curios"
+type = "INCOMPATIBLE"
+[[dependencies.examplemod]]
+modId = "minecraft"
+"#;
+        assert_eq!(read_toml_dependencies(toml), ["neoforge", "minecraft"]);
+        assert_eq!(read_toml_mod_ids(toml), ["examplemod"]);
+    }
+
+    #[test]
+    fn reads_fabric_classes_mixins_and_dependencies() {
+        let jar = TempJar::new("fabric.jar", &[
+            ("com/example/mod/Main.class", b""),
+            ("com/example/mod/client/render/deep/Renderer.class", b""),
+            ("net/minecraft/client/Patched.class", b""),
+            ("META-INF/versions/9/com/example/Module.class", b""),
+            ("Top.class", b""),
+            ("fabric.mod.json", br#"{
+                "id": "examplemod",
+                "mixins": ["example.mixins.json", { "config": "example.client.mixins.json", "environment": "client" }],
+                "depends": { "fabricloader": ">=0.15", "fabric-api": "*" }
+            }"#),
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\nMixinConfigs: example.mixins.json,example.compat.mi\r\n xins.json\r\n"),
+        ]);
+        let (packages, mixins, dependencies) = read_mod_classes(&jar.0).unwrap();
+        assert_eq!(packages, ["com.example.mod", "com.example.mod.client"]);
+        assert_eq!(mixins, ["example.client.mixins.json", "example.compat.mixins.json", "example.mixins.json"]);
+        assert_eq!(dependencies, ["fabric-api", "fabricloader"]);
+    }
+
+    #[test]
+    fn reads_neoforge_mixins_and_dependencies() {
+        let jar = TempJar::new("neoforge.jar", &[
+            ("META-INF/neoforge.mods.toml", br#"
+[[mods]]
+modId = "examplemod"
+[[mixins]]
+config = "examplemod.mixins.json"
+[[dependencies.examplemod]]
+modId = "neoforge"
+type = "required"
+"#),
+        ]);
+        let (packages, mixins, dependencies) = read_mod_classes(&jar.0).unwrap();
+        assert!(packages.is_empty());
+        assert_eq!(mixins, ["examplemod.mixins.json"]);
+        assert_eq!(dependencies, ["neoforge"]);
+    }
+
+    #[test]
+    fn reads_provided_and_nested_ids() {
+        let inner = zip_bytes(&[("fabric.mod.json", br#"{ "id": "fabric-api-base" }"#)]);
+        let forge_inner = zip_bytes(&[("META-INF/mods.toml", b"[[mods]]\nmodId=\"mixinextras\"\n")]);
+        let jar = TempJar::new("provides.jar", &[
+            ("fabric.mod.json", br#"{
+                "id": "fabric-api",
+                "provides": ["fabric"],
+                "jars": [{ "file": "META-INF/jars/base.jar" }]
+            }"#),
+            ("META-INF/jars/base.jar", &inner),
+            ("META-INF/jarjar/metadata.json", br#"{ "jars": [{ "path": "META-INF/jarjar/mixinextras.jar" }] }"#),
+            ("META-INF/jarjar/mixinextras.jar", &forge_inner),
+        ]);
+        assert_eq!(read_provided_ids(&jar.0), ["fabric", "fabric-api-base", "mixinextras"]);
+    }
+}
@@ -169,6 +169,9 @@ function updateUI() {
     if (accountType === 'microsoft') {
       // badge.innerHTML = '<i class="ri-microsoft-fill"></i><span>Microsoft</span>'; // Del Icons
       badge.className = 'account-badge microsoft';
+    } else if (accountType === 'yggdrasil') {
+      badge.innerHTML = '<i class="ri-server-line"></i><span>' + (account.auth_server_name || account.authServerName || '外置登录') + '</span>';
+      badge.className = 'account-badge offline';
     } else {
       badge.innerHTML = '<i class="ri-user-line"></i><span>' + (getI18n('account.offline') || '离线') + '</span>';
       badge.className = 'account-badge offline';
@@ -413,6 +416,16 @@ async function refreshAccount() {
     } else {
       showToast(result.error || getI18n('account.refreshFailed') || '刷新失败', 'error');
     }
+  } else if (accountType === 'yggdrasil') {
+    const result = await ipcRenderer.invoke('refresh-yggdrasil-account', account.id);
+    if (result.success) {
+      const index = accounts.findIndex(a => a.id === account.id);
+      if (index >= 0) accounts[index] = result.account;
+      updateUI();
+      showToast(getI18n('account.refreshSuccess') || '刷新成功', 'success');
+    } else {
+      showToast(result.error || getI18n('account.refreshFailed') || '刷新失败', 'error');
+    }
   } else {
     // 离线账户无需刷新
     showToast(getI18n('account.offlineNoRefresh') || '离线账户无需刷新', 'success');
@@ -432,6 +445,20 @@ async function showAddAccountDialog() {
             <span class="option-desc">${getI18n('account.microsoftDesc') || '正版账户'}</span>
           </div>
         </button>
+        <button class="dialog-option" data-type="microsoft-device">
+          <div class="option-icon microsoft"><i class="ri-qr-code-line"></i></div>
+          <div class="option-text">
+            <span class="option-title">Microsoft (设备代码)</span>
+            <span class="option-desc">在其他设备的浏览器中输入代码登录</span>
+          </div>
+        </button>
+        <button class="dialog-option" data-type="yggdrasil">
+          <div class="option-icon offline"><i class="ri-server-line"></i></div>
+          <div class="option-text">
+            <span class="option-title">外置登录</span>
+            <span class="option-desc">authlib-injector 认证服务器</span>
+          </div>
+        </button>
         <button class="dialog-option" data-type="offline">
           <div class="option-icon offline"><i class="ri-user-line"></i></div>
           <div class="option-text">
@@ -449,6 +476,10 @@ async function showAddAccountDialog() {
       closeDialog(dialog);
       if (opt.dataset.type === 'microsoft') {
         await loginMicrosoft();
+      } else if (opt.dataset.type === 'microsoft-device') {
+        await loginMicrosoftDevice();
+      } else if (opt.dataset.type === 'yggdrasil') {
+        await showAddYggdrasilDialog();
       } else {
         await showAddOfflineDialog();
       }
@@ -461,11 +492,29 @@ async function loginMicrosoft() {
   console.log('[Account] Starting Microsoft login...');
   showToast(getI18n('account.loggingIn') || '正在打开登录页面...', 'success');
   
+  // 等待浏览器回调期间允许取消
+  const waitDialog = createDialog({
+    title: '微软账户登录',
+    content: `<p style="color: var(--text-secondary);">请在浏览器中完成登录...</p>`,
+    buttons: [
+      { text: '取消', type: 'secondary', action: 'cancel' }
+    ]
+  });
+  const cancel = () => ipcRenderer.invoke('cancel-microsoft-login');
+  waitDialog.querySelector('[data-action="cancel"]')?.addEventListener('click', () => {
+    cancel();
+    closeDialog(waitDialog);
+  });
+  waitDialog.querySelector('.dialog-close')?.addEventListener('click', cancel);
+  
   try {
     const result = await ipcRenderer.invoke('microsoft-login');
     console.log('[Account] Microsoft login result:', result);
+    closeDialog(waitDialog);
     
-    if (result.success) {
+    if (result.code === 'login_cancelled') {
+      showToast('登录已取消', 'error');
+    } else if (result.success) {
       accounts = (await ipcRenderer.invoke('get-accounts')).accounts;
       currentAccount = result.currentAccount || result.account?.id;
       
@@ -484,11 +533,14 @@ async function loginMicrosoft() {
           accountId: currentAccount 
         }, '*');
       }
+    } else if (result.code === 'no_profile') {
+      await showCreateProfileDialog(result);
     } else {
       showToast(result.error || getI18n('account.loginFailed') || '登录失败', 'error');
     }
   } catch (error) {
     console.error('[Account] Microsoft login error:', error);
+    closeDialog(waitDialog);
     // 用户取消或超时
     if (error.toString().includes('超时') || error.toString().includes('取消')) {
       showToast('登录已取消', 'error');
@@ -498,6 +550,150 @@ async function loginMicrosoft() {
   }
 }
 
+// 设备代码登录
+async function loginMicrosoftDevice() {
+  console.log('[Account] Starting Microsoft device code login...');
+  
+  const dialog = createDialog({
+    title: '微软账户登录',
+    content: `
+      <div class="dialog-input-group">
+        <p style="margin-bottom: 12px; color: var(--text-secondary);" id="device-login-status">正在获取登录代码...</p>
+        <div id="device-code-box" style="display: none; text-align: center;">
+          <div id="device-user-code" style="font-size: 28px; font-weight: 600; letter-spacing: 4px; margin: 12px 0; user-select: all;"></div>
+          <button class="btn-outline-small" id="open-device-url">
+            <i class="ri-external-link-line"></i> <span id="device-verification-uri"></span>
+          </button>
+        </div>
+      </div>
+    `,
+    buttons: [
+      { text: '取消', type: 'secondary', action: 'cancel' }
+    ]
+  });
+  
+  const statusEl = dialog.querySelector('#device-login-status');
+  let verificationUri = '';
+  
+  ipcRenderer.on('device-code', (event, data) => {
+    verificationUri = data.verificationUri;
+    dialog.querySelector('#device-code-box').style.display = '';
+    dialog.querySelector('#device-user-code').textContent = data.userCode;
+    dialog.querySelector('#device-verification-uri').textContent = data.verificationUri;
+    statusEl.textContent = '请在任意设备的浏览器中打开以下地址并输入代码：';
+  });
+  
+  ipcRenderer.on('device-login-progress', (event, data) => {
+    if (data.stage !== 'pending') statusEl.textContent = data.message;
+  });
+  
+  dialog.querySelector('#open-device-url')?.addEventListener('click', () => {
+    if (window.shell?.openExternal) {
+      window.shell.openExternal(verificationUri);
+    } else {
+      window.open(verificationUri, '_blank');
+    }
+  });
+  
+  const cancel = () => ipcRenderer.invoke('cancel-microsoft-login');
+  dialog.querySelector('[data-action="cancel"]')?.addEventListener('click', () => {
+    cancel();
+    closeDialog(dialog);
+  });
+  dialog.querySelector('.dialog-close')?.addEventListener('click', cancel);
+  
+  try {
+    const result = await ipcRenderer.invoke('microsoft-device-login');
+    closeDialog(dialog);
+    
+    if (result.success) {
+      accounts = (await ipcRenderer.invoke('get-accounts')).accounts;
+      currentAccount = result.currentAccount || result.account?.id;
+      if (currentAccount) {
+        await ipcRenderer.invoke('refresh-account-avatar', currentAccount);
+      }
+      updateUI();
+      showToast('登录成功！欢迎 ' + (result.account?.username || ''), 'success');
+      
+      if (window.parent && window.parent !== window) {
+        window.parent.postMessage({ 
+          action: 'avatar-updated', 
+          accountId: currentAccount 
+        }, '*');
+      }
+    } else if (result.code === 'no_profile') {
+      await showCreateProfileDialog(result);
+    } else if (result.code !== 'login_cancelled') {
+      showToast(result.error || '登录失败', 'error');
+    }
+  } catch (error) {
+    console.error('[Account] Device login error:', error);
+    closeDialog(dialog);
+    showToast('登录失败: ' + error, 'error');
+  }
+}
+
+// 新账户（或 Game Pass 账户）尚未创建角色时设置角色名
+async function showCreateProfileDialog(loginResult) {
+  const dialog = createDialog({
+    title: '创建游戏角色',
+    content: `
+      <div class="dialog-input-group">
+        <p style="margin-bottom: 12px; color: var(--text-secondary);">${loginResult.error}</p>
+        <label>角色名</label>
+        <input type="text" id="profile-name" maxlength="16" placeholder="3-16 位英文字母、数字或下划线">
+        <span class="input-hint" id="profile-name-status"></span>
+      </div>
+    `,
+    buttons: [
+      { text: getI18n('common.cancel') || '取消', type: 'secondary', action: 'cancel' },
+      { text: '创建', type: 'primary', action: 'confirm' }
+    ]
+  });
+  
+  const input = dialog.querySelector('#profile-name');
+  const statusEl = dialog.querySelector('#profile-name-status');
+  input?.focus();
+  
+  // 输入停顿后检查角色名是否可用
+  let checkTimer = null;
+  input?.addEventListener('input', () => {
+    clearTimeout(checkTimer);
+    const name = input.value.trim();
+    if (!name) {
+      statusEl.textContent = '';
+      return;
+    }
+    checkTimer = setTimeout(async () => {
+      const result = await ipcRenderer.invoke('check-profile-name', { name });
+      if (input.value.trim() !== name) return;
+      statusEl.textContent = !result.success ? (result.error || '')
+        : result.available ? '角色名可用'
+        : result.status === 'DUPLICATE' ? '该角色名已被使用' : '该角色名不符合要求';
+    }, 500);
+  });
+  
+  dialog.querySelector('[data-action="confirm"]')?.addEventListener('click', async () => {
+    const name = input?.value?.trim();
+    if (!name) return;
+    
+    const result = await ipcRenderer.invoke('create-minecraft-profile', { name });
+    if (result.success) {
+      closeDialog(dialog);
+      accounts = (await ipcRenderer.invoke('get-accounts')).accounts;
+      currentAccount = result.currentAccount || result.account?.id;
+      updateUI();
+      showToast('角色创建成功！欢迎 ' + (result.account?.username || ''), 'success');
+    } else {
+      showToast(result.error || '创建角色失败', 'error');
+    }
+  });
+  
+  dialog.querySelector('[data-action="cancel"]')?.addEventListener('click', () => {
+    closeDialog(dialog);
+  });
+}
+
 // 显示手动输入授权URL的对话框
 function showManualAuthDialog(authUrl) {
   console.log('[Account] Showing manual auth dialog');
@@ -581,6 +777,8 @@ function showManualAuthDialog(authUrl) {
             accountId: currentAccount 
           }, '*');
         }
+      } else if (result.code === 'no_profile') {
+        await showCreateProfileDialog(result);
       } else {
         showToast(result.error || '登录失败', 'error');
       }
@@ -611,6 +809,12 @@ async function showAddOfflineDialog() {
         <label>${getI18n('account.username') || '用户名'}</label>
         <input type="text" id="offline-username" placeholder="${getI18n('account.usernamePlaceholder') || '输入游戏内显示的名称'}" maxlength="16">
         <span class="input-hint">${getI18n('account.usernameHint') || '3-16个字符'}</span>
+        <label style="display: flex; align-items: center; gap: 6px;">
+          <input type="checkbox" id="offline-nonstandard">
+          <span>允许非标准用户名 (服务器支持时使用)</span>
+        </label>
+        <label>UUID (可选)</label>
+        <input type="text" id="offline-uuid" placeholder="留空则按用户名生成，与离线服务器一致">
       </div>
     `,
     buttons: [
@@ -624,12 +828,14 @@ async function showAddOfflineDialog() {
   
   dialog.querySelector('[data-action="confirm"]')?.addEventListener('click', async () => {
     const username = input?.value?.trim();
-    if (!username || username.length < 3) {
-      showToast(getI18n('account.usernameInvalid') || '用户名至少需要3个字符', 'error');
+    const allowNonstandard = dialog.querySelector('#offline-nonstandard')?.checked || false;
+    if (!username || (!allowNonstandard && !/^[A-Za-z0-9_]{3,16}$/.test(username))) {
+      showToast(getI18n('account.usernameInvalid') || '用户名需为 3-16 位英文字母、数字或下划线', 'error');
       return;
     }
     
-    const result = await ipcRenderer.invoke('add-offline-account', username);
+    const customUuid = dialog.querySelector('#offline-uuid')?.value?.trim();
+    const result = await ipcRenderer.invoke('add-offline-account', username, customUuid || null, allowNonstandard);
     closeDialog(dialog);
     
     if (result.success) {
@@ -654,6 +860,99 @@ async function showAddOfflineDialog() {
   });
 }
 
+// 外置登录对话框
+async function showAddYggdrasilDialog() {
+  const dialog = createDialog({
+    title: '外置登录',
+    content: `
+      <div class="dialog-input-group">
+        <label>认证服务器</label>
+        <input type="text" id="ygg-server" placeholder="https://example.com/api/yggdrasil">
+        <span class="input-hint" id="ygg-server-name">支持 authlib-injector 规范的认证服务器</span>
+        <label>邮箱或用户名</label>
+        <input type="text" id="ygg-username">
+        <label>密码</label>
+        <input type="password" id="ygg-password">
+        <div id="ygg-profile-group" style="display: none;">
+          <label>选择角色</label>
+          <select id="ygg-profile"></select>
+        </div>
+      </div>
+    `,
+    buttons: [
+      { text: getI18n('common.cancel') || '取消', type: 'secondary', action: 'cancel' },
+      { text: '登录', type: 'primary', action: 'confirm' }
+    ]
+  });
+  
+  const serverInput = dialog.querySelector('#ygg-server');
+  const serverName = dialog.querySelector('#ygg-server-name');
+  const profileGroup = dialog.querySelector('#ygg-profile-group');
+  const profileSelect = dialog.querySelector('#ygg-profile');
+  serverInput?.focus();
+  
+  // 输入服务器地址后显示服务器名称
+  serverInput?.addEventListener('change', async () => {
+    const url = serverInput.value.trim();
+    if (!url) return;
+    serverName.textContent = '正在连接认证服务器...';
+    const result = await ipcRenderer.invoke('resolve-auth-server', url);
+    serverName.textContent = result.success
+      ? (result.serverName || result.apiRoot)
+      : (result.error || '无法连接认证服务器');
+  });
+  
+  const confirmBtn = dialog.querySelector('[data-action="confirm"]');
+  confirmBtn?.addEventListener('click', async () => {
+    const serverUrl = serverInput?.value?.trim();
+    const username = dialog.querySelector('#ygg-username')?.value?.trim();
+    const password = dialog.querySelector('#ygg-password')?.value;
+    if (!serverUrl || !username || !password) {
+      showToast('请填写服务器地址、账户和密码', 'error');
+      return;
+    }
+    
+    confirmBtn.disabled = true;
+    try {
+      // 多角色账户已登录过一次，选择角色时沿用同一次登录，无需重新验证密码
+      const result = profileGroup.style.display === 'none'
+        ? await ipcRenderer.invoke('add-yggdrasil-account', { serverUrl, username, password })
+        : await ipcRenderer.invoke('select-yggdrasil-profile', profileSelect.value);
+      
+      if (result.needsProfileSelection) {
+        // 多角色账户，选择后再次提交
+        profileSelect.innerHTML = result.profiles
+          .map(p => `<option value="${p.id}">${p.name}</option>`)
+          .join('');
+        profileGroup.style.display = '';
+        showToast('该账户有多个角色，请选择一个', 'success');
+        return;
+      }
+      
+      if (result.success) {
+        closeDialog(dialog);
+        accounts = (await ipcRenderer.invoke('get-accounts')).accounts;
+        currentAccount = result.currentAccount || result.account?.id;
+        updateUI();
+        showToast('登录成功！欢迎 ' + (result.account?.username || ''), 'success');
+      } else {
+        // 选择角色失败时回到输入密码的步骤
+        profileGroup.style.display = 'none';
+        showToast(result.error || '登录失败', 'error');
+      }
+    } catch (error) {
+      console.error('[Account] Yggdrasil login error:', error);
+      showToast('登录失败: ' + error, 'error');
+    } finally {
+      confirmBtn.disabled = false;
+    }
+  });
+  
+  dialog.querySelector('[data-action="cancel"]')?.addEventListener('click', () => {
+    closeDialog(dialog);
+  });
+}
+
 // 切换账户对话框
 async function showSwitchAccountDialog() {
   if (accounts.length === 0) {
@@ -758,10 +1057,65 @@ async function changeSkin() {
   
   const accountType = account.account_type || account.type;
   if (accountType === 'microsoft') {
-    showToast(getI18n('account.microsoftSkinHint') || '微软账户请在官网更换皮肤', 'error');
+    await showMicrosoftSkinDialog(account);
+    return;
+  }
+  if (accountType === 'yggdrasil') {
+    showToast('外置登录账户请在认证服务器网站更换皮肤', 'error');
     return;
   }
   
+  await showOfflineSkinDialog(account);
+}
+
+// 离线账户皮肤与披风（启动时通过本地皮肤服务器在游戏内显示）
+async function showOfflineSkinDialog(account) {
+  const dialog = createDialog({
+    title: '皮肤与披风',
+    content: `
+      <div class="dialog-input-group">
+        <p style="margin-bottom: 12px; color: var(--text-secondary);">
+          离线账户的皮肤和披风会在单人游戏和局域网中显示
+        </p>
+        <div style="display: flex; gap: 8px; flex-wrap: wrap;">
+          <button class="btn-outline-small" id="offline-skin"><i class="ri-t-shirt-line"></i> 更换皮肤</button>
+          <button class="btn-outline-small" id="offline-cape"><i class="ri-flag-line"></i> 设置披风</button>
+          <button class="btn-outline-small" id="offline-cape-remove" ${account.cape ? '' : 'disabled'}><i class="ri-delete-bin-line"></i> 移除披风</button>
+        </div>
+      </div>
+    `,
+    buttons: [
+      { text: getI18n('common.close') || '关闭', type: 'secondary', action: 'cancel' }
+    ]
+  });
+  
+  const setCape = async (capePath) => {
+    const result = await ipcRenderer.invoke('set-offline-cape', { accountId: account.id, capePath });
+    if (result.success) {
+      const index = accounts.findIndex(a => a.id === account.id);
+      if (index >= 0) accounts[index] = result.account;
+      showToast(capePath ? '披风已设置' : '披风已移除', 'success');
+      closeDialog(dialog);
+    } else {
+      showToast(result.error || '设置披风失败', 'error');
+    }
+  };
+  
+  dialog.querySelector('#offline-skin')?.addEventListener('click', async () => {
+    closeDialog(dialog);
+    await selectOfflineSkin(account);
+  });
+  dialog.querySelector('#offline-cape')?.addEventListener('click', async () => {
+    const file = await ipcRenderer.invoke('select-skin-file');
+    if (file.success) await setCape(file.path);
+  });
+  dialog.querySelector('#offline-cape-remove')?.addEventListener('click', () => setCape(null));
+  dialog.querySelector('[data-action="cancel"]')?.addEventListener('click', () => {
+    closeDialog(dialog);
+  });
+}
+
+async function selectOfflineSkin(account) {
   const result = await ipcRenderer.invoke('select-skin-file');
   if (result.canceled) return;
   
@@ -786,6 +1140,129 @@ async function changeSkin() {
   }
 }
 
+// 微软账户皮肤与披风管理
+async function showMicrosoftSkinDialog(account) {
+  const dialog = createDialog({
+    title: '皮肤与披风',
+    content: `
+      <div class="dialog-input-group">
+        <label>皮肤模型</label>
+        <select id="skin-model">
+          <option value="classic" ${account.skin_model !== 'slim' ? 'selected' : ''}>经典 (Steve)</option>
+          <option value="slim" ${account.skin_model === 'slim' ? 'selected' : ''}>纤细 (Alex)</option>
+        </select>
+        <label>皮肤 URL</label>
+        <input type="text" id="skin-url" placeholder="https://...">
+        <div style="display: flex; gap: 8px; margin-top: 12px; flex-wrap: wrap;">
+          <button class="btn-outline-small" id="skin-upload-file"><i class="ri-upload-2-line"></i> 从文件上传</button>
+          <button class="btn-outline-small" id="skin-upload-url"><i class="ri-link"></i> 从 URL 上传</button>
+          <button class="btn-outline-small" id="skin-reset"><i class="ri-refresh-line"></i> 重置皮肤</button>
+        </div>
+        <label style="margin-top: 12px;">披风</label>
+        <select id="skin-cape"><option value="">加载中...</option></select>
+        <label style="margin-top: 12px;">皮肤库</label>
+        <div id="skin-library" style="display: flex; gap: 8px; flex-wrap: wrap; max-height: 160px; overflow-y: auto;"></div>
+      </div>
+    `,
+    buttons: [
+      { text: getI18n('common.close') || '关闭', type: 'secondary', action: 'cancel' }
+    ]
+  });
+  
+  const modelSelect = dialog.querySelector('#skin-model');
+  const capeSelect = dialog.querySelector('#skin-cape');
+  const libraryEl = dialog.querySelector('#skin-library');
+  
+  const onResult = (result) => {
+    if (result.success) {
+      const index = accounts.findIndex(a => a.id === account.id);
+      if (index >= 0 && result.account) accounts[index] = result.account;
+      updateUI();
+      showToast(getI18n('account.skinChanged') || '皮肤已更换', 'success');
+      if (window.parent && window.parent !== window) {
+        window.parent.postMessage({ action: 'avatar-updated', accountId: account.id }, '*');
+      }
+      loadLibrary();
+    } else {
+      showToast(result.error || getI18n('account.skinChangeFailed') || '更换失败', 'error');
+    }
+  };
+  
+  const upload = async (source) => {
+    showToast('正在上传皮肤...', 'success');
+    onResult(await ipcRenderer.invoke('upload-microsoft-skin', {
+      accountId: account.id,
+      source,
+      model: modelSelect.value
+    }));
+  };
+  
+  const loadLibrary = async () => {
+    const result = await ipcRenderer.invoke('get-skin-library');
+    const skins = result.skins || [];
+    libraryEl.innerHTML = skins.length === 0
+      ? '<span class="input-hint">暂无皮肤，上传过的皮肤会保存在这里</span>'
+      : skins.map(s => `
+        <button class="btn-outline-small" data-hash="${s.hash}" title="${s.name} (${s.model})">
+          ${s.name}
+        </button>
+      `).join('');
+    libraryEl.querySelectorAll('[data-hash]').forEach(btn => {
+      btn.addEventListener('click', async () => {
+        showToast('正在应用皮肤...', 'success');
+        onResult(await ipcRenderer.invoke('apply-library-skin', { accountId: account.id, hash: btn.dataset.hash }));
+      });
+    });
+  };
+  
+  dialog.querySelector('#skin-upload-file')?.addEventListener('click', async () => {
+    const file = await ipcRenderer.invoke('select-skin-file');
+    if (file.success) await upload(file.path);
+  });
+  
+  dialog.querySelector('#skin-upload-url')?.addEventListener('click', async () => {
+    const url = dialog.querySelector('#skin-url')?.value?.trim();
+    if (!url) {
+      showToast('请输入皮肤 URL', 'error');
+      return;
+    }
+    await upload(url);
+  });
+  
+  dialog.querySelector('#skin-reset')?.addEventListener('click', async () => {
+    onResult(await ipcRenderer.invoke('reset-microsoft-skin', { accountId: account.id }));
+  });
+  
+  capeSelect?.addEventListener('change', async () => {
+    const result = await ipcRenderer.invoke('equip-microsoft-cape', {
+      accountId: account.id,
+      capeId: capeSelect.value || null
+    });
+    if (result.success) {
+      showToast('披风已更新', 'success');
+    } else {
+      showToast(result.error || '更新披风失败', 'error');
+    }
+  });
+  
+  dialog.querySelector('[data-action="cancel"]')?.addEventListener('click', () => {
+    closeDialog(dialog);
+  });
+  
+  loadLibrary();
+  
+  const textures = await ipcRenderer.invoke('get-microsoft-textures', { accountId: account.id });
+  if (textures.success) {
+    const capes = textures.capes || [];
+    capeSelect.innerHTML = '<option value="">不显示披风</option>' + capes.map(c =>
+      `<option value="${c.id}" ${c.state === 'ACTIVE' ? 'selected' : ''}>${c.alias || c.id}</option>`
+    ).join('');
+  } else {
+    capeSelect.innerHTML = '<option value="">无法获取披风</option>';
+    showToast(textures.error || '获取皮肤信息失败', 'error');
+  }
+}
+
 // ═══════════════════════════════════════════════════════════
 // 对话框
 // ═══════════════════════════════════════════════════════════
@@ -76,8 +76,8 @@ async function loadMcVersions() {
   grid.innerHTML = '<div class="loading-state"><i class="ri-loader-4-line spin"></i><span>加载版本列表...</span></div>';
   
   try {
-    const res = await fetch('https://launchermeta.mojang.com/mc/game/version_manifest_v2.json');
-    const data = await res.json();
+    const data = await ipcRenderer.invoke('get-version-manifest');
+    if (!data?.success) throw new Error(data?.error || 'manifest unavailable');
     mcVersions = data.versions;
     renderMcVersions();
     populateLoaderMcVersions();
@@ -94,6 +94,16 @@ function populateLoaderMcVersions() {
   select.innerHTML = '<option value="">选择游戏版本</option>' + releases.slice(0, 50).map(v => `<option value="${v.id}">${v.id}</option>`).join('');
 }
 
+function versionTypeClass(type) {
+  if (type === 'release' || type === 'snapshot') return type;
+  if (type === 'experimental' || type === 'april_fools') return 'experimental';
+  return 'old';
+}
+
+function versionTypeText(type) {
+  return { release: '正式版', snapshot: '快照', experimental: '实验性', april_fools: '愚人节' }[type] || '旧版本';
+}
+
 function renderMcVersions() {
   const grid = document.getElementById('mc-version-grid');
   const search = document.getElementById('mc-search')?.value?.toLowerCase() || '';
@@ -103,6 +113,7 @@ function renderMcVersions() {
     if (currentFilter === 'release') return v.type === 'release';
     if (currentFilter === 'snapshot') return v.type === 'snapshot';
     if (currentFilter === 'old') return v.type === 'old_beta' || v.type === 'old_alpha';
+    if (currentFilter === 'experimental') return v.type === 'experimental' || v.type === 'april_fools';
     return true;
   }).slice(0, 100);
   
@@ -113,8 +124,8 @@ function renderMcVersions() {
   
   grid.innerHTML = filtered.map(v => {
     const installed = installedVersions.includes(v.id);
-    const typeClass = v.type === 'release' ? 'release' : v.type === 'snapshot' ? 'snapshot' : 'old';
-    const typeText = v.type === 'release' ? '正式版' : v.type === 'snapshot' ? '快照' : '旧版本';
+    const typeClass = versionTypeClass(v.type);
+    const typeText = versionTypeText(v.type);
     const date = v.releaseTime ? new Date(v.releaseTime).toLocaleDateString() : '';
     return `
       <div class="version-card ${installed ? 'installed' : ''}" data-id="${v.id}" data-url="${v.url}" data-type="${v.type}">
@@ -155,7 +166,7 @@ function openInstallWizard(versionId, versionUrl, versionType) {
   
   document.getElementById('selected-version-name').textContent = `Minecraft ${versionId}`;
   document.getElementById('selected-version-type').textContent = 
-    versionType === 'release' ? '正式版' : versionType === 'snapshot' ? '快照' : '旧版本';
+    versionTypeText(versionType);
   document.getElementById('wizard-version-name').value = versionId;
   
   document.querySelectorAll('.component-item').forEach(item => {
@@ -322,7 +333,9 @@ async function loadLoaderVersions(loader, select) {
     } else if (loader === 'forge') {
       const mc = wizardState.mcVersion;
       if (!loaderVersionsCache.forge[mc]) {
-        loaderVersionsCache.forge[mc] = await ipcRenderer.invoke('get-forge-versions', mc) || [];
+        const result = await ipcRenderer.invoke('get-forge-versions', mc);
+        // 出错时返回的是 { success: false, error }
+        loaderVersionsCache.forge[mc] = Array.isArray(result) ? result : [];
       }
       const vers = loaderVersionsCache.forge[mc];
       if (!vers.length) { select.innerHTML = '<option value="">此版本无 Forge</option>'; return; }
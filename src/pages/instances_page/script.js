@@ -936,7 +936,7 @@ async function launchVersion(versionId) {
           await ipcRenderer.invoke('execute-launcher-behavior', launcherBehavior);
         }, 500);
       }
-    } else if (result.error === 'duplicate_instance') {
+    } else if (result.code === 'duplicate_instance') {
       // 重复实例，显示确认对话框
       hideLaunchModal();
       const confirmed = await showDuplicateInstanceDialog(versionId);
@@ -1661,7 +1661,7 @@ async function loadJavaList() {
       for (const java of javaList) {
         const opt = document.createElement('option');
         opt.value = java.path;
-        opt.textContent = `${java.name} (Java ${java.version})`;
+        opt.textContent = `${java.name} (Java ${java.version}, ${java.is64bit ? '64' : '32'} 位)`;
         select.appendChild(opt);
       }
     }
@@ -3013,7 +3013,7 @@ async function loadLoaderVersionsForInstall(loaderType, gameVersion) {
       case 'forge':
         if (!loaderVersionsCache.forge[gameVersion]) {
           const result = await ipcRenderer.invoke('get-forge-versions', gameVersion);
-          loaderVersionsCache.forge[gameVersion] = result || [];
+          loaderVersionsCache.forge[gameVersion] = Array.isArray(result) ? result : [];
         }
         versions = loaderVersionsCache.forge[gameVersion].slice(0, 30);
         break;
@@ -660,7 +660,8 @@ function initGameSettings() {
   document.getElementById('select-java')?.addEventListener('click', selectJava);
   
   // 内存和窗口大小变化时自动保存
-  ['game-memory-min', 'game-memory-max', 'game-window-width', 'game-window-height'].forEach(id => {
+  ['game-memory-min', 'game-memory-max', 'game-window-width', 'game-window-height', 'crash-share-backend', 'crash-share-url',
+   'redact-tokens', 'redact-usernames', 'redact-home-dir', 'redact-ip-addresses', 'redact-uuids', 'redact-custom'].forEach(id => {
     document.getElementById(id)?.addEventListener('change', saveLauncherSettings);
   });
   
@@ -768,6 +769,20 @@ function populateLauncherSettings() {
   document.getElementById('game-window-width').value = windowSize.width || 854;
   document.getElementById('game-window-height').value = windowSize.height || 480;
   console.log('[Settings] Window size:', windowSize.width || 854, 'x', windowSize.height || 480);
+  
+  // 崩溃报告分享服务
+  const crashShare = launcherSettings.crash_share || launcherSettings.crashShare || {};
+  document.getElementById('crash-share-backend').value = crashShare.backend || 'mclogs';
+  document.getElementById('crash-share-url').value = crashShare.apiUrl || 'https://api.mclo.gs';
+  
+  // 导出和分享时的脱敏规则
+  const redaction = launcherSettings.redaction || {};
+  document.getElementById('redact-tokens').checked = redaction.tokens ?? true;
+  document.getElementById('redact-usernames').checked = redaction.usernames ?? true;
+  document.getElementById('redact-home-dir').checked = redaction.homeDir ?? true;
+  document.getElementById('redact-ip-addresses').checked = redaction.ipAddresses ?? true;
+  document.getElementById('redact-uuids').checked = redaction.uuids ?? false;
+  document.getElementById('redact-custom').value = (redaction.customPatterns || []).join(', ');
 }
 
 async function selectMinecraftDir() {
@@ -823,7 +838,7 @@ function showJavaSelectDialog(javaList) {
             <div class="java-item ${currentJavaPath === java.path ? 'selected' : ''}" data-path="${java.path}">
               <div class="java-info">
                 <span class="java-name">${java.name}</span>
-                <span class="java-version">Java ${java.version}</span>
+                <span class="java-version">Java ${java.version} · ${java.vendor} · ${java.is64bit ? '64 位' : '32 位'}${java.heapLimited ? ' · 内存设置过大' : ''}</span>
               </div>
               <span class="java-path-text">${java.path}</span>
             </div>
@@ -908,6 +923,19 @@ async function saveLauncherSettings() {
     windowSize: {
       width: parseInt(document.getElementById('game-window-width')?.value) || 854,
       height: parseInt(document.getElementById('game-window-height')?.value) || 480
+    },
+    crashShare: {
+      backend: document.getElementById('crash-share-backend')?.value || 'mclogs',
+      apiUrl: document.getElementById('crash-share-url')?.value.trim() || 'https://api.mclo.gs'
+    },
+    redaction: {
+      tokens: document.getElementById('redact-tokens')?.checked ?? true,
+      usernames: document.getElementById('redact-usernames')?.checked ?? true,
+      homeDir: document.getElementById('redact-home-dir')?.checked ?? true,
+      ipAddresses: document.getElementById('redact-ip-addresses')?.checked ?? true,
+      uuids: document.getElementById('redact-uuids')?.checked ?? false,
+      customPatterns: (document.getElementById('redact-custom')?.value || '')
+        .split(',').map(p => p.trim()).filter(Boolean)
     }
   };
   
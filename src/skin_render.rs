@@ -0,0 +1,281 @@
+use crate::error::LauncherError;
+use crate::state::AppState;
+use image::{Rgba, RgbaImage};
+use sha1::{Digest, Sha1};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+// 皮肤校验、旧版皮肤转换、模型检测和预览渲染
+
+const DEFAULT_RENDER_SIZES: [u32; 3] = [64, 128, 256];
+const MAX_RENDER_SIZE: u32 = 1024;
+
+/// 校验后的皮肤，统一为 64x64 布局（高清皮肤为其整数倍）
+pub struct Skin {
+    pub image: RgbaImage,
+    pub slim: bool,
+    // 是否由旧版 64x32 皮肤转换而来
+    pub converted: bool,
+}
+
+impl Skin {
+    fn scale(&self) -> u32 {
+        self.image.width() / 64
+    }
+
+    pub fn model(&self) -> &'static str {
+        if self.slim { "slim" } else { "classic" }
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>, LauncherError> {
+        let mut bytes = Vec::new();
+        self.image
+            .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)
+            .map_err(|e| LauncherError::Internal { message: e.to_string() })?;
+        Ok(bytes)
+    }
+}
+
+/// 解析并校验皮肤 PNG，旧版 64x32 皮肤会转换为 64x64
+pub fn load_skin(bytes: &[u8]) -> Result<Skin, LauncherError> {
+    let image = image::load_from_memory_with_format(bytes, image::ImageFormat::Png)
+        .map_err(|_| invalid_skin("skins.errors.notPng", serde_json::json!({})))?
+        .to_rgba8();
+
+    let (width, height) = image.dimensions();
+    if width < 64 || width % 64 != 0 || (height != width && height * 2 != width) {
+        return Err(invalid_skin("skins.errors.invalidSize", serde_json::json!({ "width": width, "height": height })));
+    }
+
+    let converted = height * 2 == width;
+    let image = if converted { convert_legacy(&image) } else { image };
+    let slim = detect_slim(&image);
+    Ok(Skin { image, slim, converted })
+}
+
+// 旧版 64x32 皮肤转换为 64x64，左侧肢体由右侧镜像得到（与原版处理方式一致）
+fn convert_legacy(legacy: &RgbaImage) -> RgbaImage {
+    let s = legacy.width() / 64;
+    let mut image = RgbaImage::new(legacy.width(), legacy.width());
+    image::imageops::replace(&mut image, legacy, 0, 0);
+
+    // (源 x, 源 y, 偏移 x, 偏移 y, 宽, 高)
+    const MIRRORED: [(u32, u32, i32, i32, u32, u32); 12] = [
+        (4, 16, 16, 32, 4, 4),
+        (8, 16, 16, 32, 4, 4),
+        (0, 20, 24, 32, 4, 12),
+        (4, 20, 16, 32, 4, 12),
+        (8, 20, 8, 32, 4, 12),
+        (12, 20, 16, 32, 4, 12),
+        (44, 16, -8, 32, 4, 4),
+        (48, 16, -8, 32, 4, 4),
+        (40, 20, 0, 32, 4, 12),
+        (44, 20, -8, 32, 4, 12),
+        (48, 20, -16, 32, 4, 12),
+        (52, 20, -8, 32, 4, 12),
+    ];
+    for (sx, sy, dx, dy, w, h) in MIRRORED {
+        for y in 0..h * s {
+            for x in 0..w * s {
+                let pixel = *legacy.get_pixel(sx * s + x, sy * s + y);
+                let tx = ((sx as i32 + dx) * s as i32) as u32 + (w * s - 1 - x);
+                let ty = ((sy as i32 + dy) * s as i32) as u32 + y;
+                image.put_pixel(tx, ty, pixel);
+            }
+        }
+    }
+
+    // 旧版皮肤的帽子层常被填成不透明色块，整块不透明时视为没有帽子层
+    let hat = (32 * s, 0, 32 * s, 16 * s);
+    let opaque = (hat.1..hat.1 + hat.3)
+        .all(|y| (hat.0..hat.0 + hat.2).all(|x| image.get_pixel(x, y)[3] == 255));
+    if opaque {
+        for y in hat.1..hat.1 + hat.3 {
+            for x in hat.0..hat.0 + hat.2 {
+                image.put_pixel(x, y, Rgba([0, 0, 0, 0]));
+            }
+        }
+    }
+
+    image
+}
+
+// 纤细模型 (Alex) 手臂宽 3 像素，右臂贴图 x=54..56, y=20..32 区域不使用，应为透明
+fn detect_slim(image: &RgbaImage) -> bool {
+    let s = image.width() / 64;
+    (20 * s..32 * s).all(|y| (54 * s..56 * s).all(|x| image.get_pixel(x, y)[3] == 0))
+}
+
+// 复制贴图区域到画布 (单位为皮肤像素)，alpha 混合
+fn blit(canvas: &mut RgbaImage, skin: &Skin, uv: (u32, u32), size: (u32, u32), dest: (u32, u32), unit: u32) {
+    let s = skin.scale();
+    for y in 0..size.1 * unit {
+        for x in 0..size.0 * unit {
+            let src = skin.image.get_pixel((uv.0 * unit + x) * s / unit, (uv.1 * unit + y) * s / unit);
+            blend(canvas.get_pixel_mut(dest.0 * unit + x, dest.1 * unit + y), *src, 1.0);
+        }
+    }
+}
+
+fn blend(dest: &mut Rgba<u8>, src: Rgba<u8>, shade: f32) {
+    let alpha = src[3] as f32 / 255.0;
+    if alpha == 0.0 {
+        return;
+    }
+    for i in 0..3 {
+        dest[i] = (src[i] as f32 * shade * alpha + dest[i] as f32 * (1.0 - alpha)) as u8;
+    }
+    dest[3] = (alpha * 255.0 + dest[3] as f32 * (1.0 - alpha)).min(255.0) as u8;
+}
+
+/// 渲染正面或背面全身预览，输出尺寸为 size/2 x size
+pub fn render_body(skin: &Skin, back: bool, size: u32) -> RgbaImage {
+    let unit = (size / 32).max(1);
+    let mut canvas = RgbaImage::new(16 * unit, 32 * unit);
+    let arm = if skin.slim { 3 } else { 4 };
+
+    // (底层 UV, 外层 UV, 尺寸, 画布位置)
+    let parts = if back {
+        [
+            ((24, 8), (56, 8), (8, 8), (4, 0)),
+            ((32, 20), (32, 36), (8, 12), (4, 8)),
+            ((48 + arm, 20), (48 + arm, 36), (arm, 12), (12, 8)),
+            ((40 + arm, 52), (56 + arm, 52), (arm, 12), (4 - arm, 8)),
+            ((12, 20), (12, 36), (4, 12), (8, 20)),
+            ((28, 52), (12, 52), (4, 12), (4, 20)),
+        ]
+    } else {
+        [
+            ((8, 8), (40, 8), (8, 8), (4, 0)),
+            ((20, 20), (20, 36), (8, 12), (4, 8)),
+            ((44, 20), (44, 36), (arm, 12), (4 - arm, 8)),
+            ((36, 52), (52, 52), (arm, 12), (12, 8)),
+            ((4, 20), (4, 36), (4, 12), (4, 20)),
+            ((20, 52), (4, 52), (4, 12), (8, 20)),
+        ]
+    };
+
+    for (base, _, size, dest) in &parts {
+        blit(&mut canvas, skin, *base, *size, *dest, unit);
+    }
+    for (_, overlay, size, dest) in &parts {
+        blit(&mut canvas, skin, *overlay, *size, *dest, unit);
+    }
+    canvas
+}
+
+/// 渲染等轴测头像（顶面、正面和右侧面），含帽子层
+pub fn render_head(skin: &Skin, size: u32) -> RgbaImage {
+    let mut canvas = RgbaImage::new(size, size);
+    let s = skin.scale() as f32;
+    // 帽子层比头部大 1/8，需要留出空间
+    let texel = size as f32 / 18.0;
+    let (cos30, sin30) = (30f32.to_radians().cos(), 0.5f32);
+    let center = (size as f32 / 2.0, size as f32 / 2.0);
+
+    // (贴图 UV, 原点, U 方向, V 方向, 明暗)
+    let faces = |scale: f32, top: (u32, u32), front: (u32, u32), side: (u32, u32)| {
+        let l = texel * scale;
+        let top_vertex = (center.0, center.1 - 8.0 * l);
+        let back_left = (top_vertex.0 - 8.0 * cos30 * l, top_vertex.1 + 8.0 * sin30 * l);
+        let front_corner = (center.0, center.1);
+        vec![
+            (top, back_left, (cos30 * l, -sin30 * l), (cos30 * l, sin30 * l), 1.0),
+            (front, front_corner, (cos30 * l, -sin30 * l), (0.0, l), 0.9),
+            (side, back_left, (cos30 * l, sin30 * l), (0.0, l), 0.75),
+        ]
+    };
+    let layers = [
+        faces(1.0, (8, 0), (8, 8), (0, 8)),
+        faces(9.0 / 8.0, (40, 0), (40, 8), (32, 8)),
+    ];
+
+    for (uv, origin, u, v, shade) in layers.iter().flatten() {
+        let det = u.0 * v.1 - u.1 * v.0;
+        for py in 0..size {
+            for px in 0..size {
+                let dx = px as f32 + 0.5 - origin.0;
+                let dy = py as f32 + 0.5 - origin.1;
+                let tu = (dx * v.1 - dy * v.0) / det;
+                let tv = (u.0 * dy - u.1 * dx) / det;
+                if !(0.0..8.0).contains(&tu) || !(0.0..8.0).contains(&tv) {
+                    continue;
+                }
+                let src = skin.image.get_pixel(
+                    ((uv.0 as f32 + tu) * s) as u32,
+                    ((uv.1 as f32 + tv) * s) as u32,
+                );
+                blend(canvas.get_pixel_mut(px, py), *src, *shade);
+            }
+        }
+    }
+    canvas
+}
+
+fn invalid_skin(key: &str, params: serde_json::Value) -> LauncherError {
+    LauncherError::InvalidSkin { message: crate::i18n::t(key, &params) }
+}
+
+/// 读取本地文件或下载皮肤
+pub async fn fetch_skin_bytes(source: &str) -> Result<Vec<u8>, LauncherError> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let download_failed = |e: reqwest::Error| {
+            invalid_skin("skins.errors.downloadFailed", serde_json::json!({ "message": e.to_string() }))
+        };
+        Ok(reqwest::get(source)
+            .await
+            .map_err(download_failed)?
+            .error_for_status()
+            .map_err(download_failed)?
+            .bytes()
+            .await
+            .map_err(download_failed)?
+            .to_vec())
+    } else if Path::new(source).exists() {
+        std::fs::read(source)
+            .map_err(|e| invalid_skin("skins.errors.readFailed", serde_json::json!({ "message": e.to_string() })))
+    } else {
+        Err(invalid_skin("skins.errors.fileMissing", serde_json::json!({ "path": source })))
+    }
+}
+
+// 渲染皮肤预览，kind 为 front / back / head，按皮肤哈希缓存
+#[tauri::command]
+pub async fn render_skin(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    source: String,
+    kind: String,
+    sizes: Option<Vec<u32>>,
+) -> Result<serde_json::Value, LauncherError> {
+    // kind 会进入缓存文件名，先校验再使用
+    let render: fn(&Skin, u32) -> RgbaImage = match kind.as_str() {
+        "front" => |skin, size| render_body(skin, false, size),
+        "back" => |skin, size| render_body(skin, true, size),
+        "head" => render_head,
+        _ => return Err(invalid_skin("skins.errors.unknownRenderKind", serde_json::json!({ "kind": kind }))),
+    };
+    let cache_dir = state.lock().await.get_cache_dir().join("skin_renders");
+
+    let bytes = fetch_skin_bytes(&source).await?;
+    let hash = format!("{:x}", Sha1::digest(&bytes));
+    let skin = load_skin(&bytes)?;
+    std::fs::create_dir_all(&cache_dir)?;
+
+    let sizes = sizes.unwrap_or_else(|| DEFAULT_RENDER_SIZES.to_vec());
+    let mut renders = serde_json::Map::new();
+    for size in sizes {
+        let size = size.clamp(32, MAX_RENDER_SIZE);
+        let path = cache_dir.join(format!("{}_{}_{}.png", hash, kind, size));
+        if !path.exists() {
+            render(&skin, size).save(&path).map_err(|e| LauncherError::Io { message: e.to_string() })?;
+        }
+        renders.insert(size.to_string(), serde_json::json!(path.to_string_lossy()));
+    }
+
+    Ok(serde_json::json!({
+        "success": true,
+        "model": skin.model(),
+        "renders": renders
+    }))
+}
@@ -0,0 +1,255 @@
+use crate::accounts::Account;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use rsa::pkcs1v15::SigningKey;
+use rsa::pkcs8::{EncodePublicKey, LineEnding};
+use rsa::rand_core::OsRng;
+use rsa::signature::{SignatureEncoding, Signer};
+use rsa::RsaPrivateKey;
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+
+// 本地 Yggdrasil 材质服务器
+// 离线账户启动时通过 authlib-injector 指向本服务器，使游戏（单人及局域网）能加载自定义皮肤和披风。
+// 只监听 127.0.0.1，签名密钥每次启动启动器时临时生成。
+
+static SKIN_SERVER: OnceCell<Arc<SkinServer>> = OnceCell::const_new();
+
+const RSA_BITS: usize = 2048;
+
+#[derive(Clone)]
+struct OfflineProfile {
+    uuid: String,
+    name: String,
+    skin: Option<(String, bool)>,
+    cape: Option<String>,
+}
+
+pub struct SkinServer {
+    port: u16,
+    // 材质签名使用 SHA1withRSA，与 Yggdrasil 规范一致
+    signing_key: SigningKey<Sha1>,
+    public_key_pem: String,
+    profiles: Mutex<HashMap<String, OfflineProfile>>,
+    // 材质哈希 -> 文件路径
+    textures: Mutex<HashMap<String, PathBuf>>,
+}
+
+impl SkinServer {
+    fn bind() -> Result<Arc<Self>, String> {
+        let server = tiny_http::Server::http("127.0.0.1:0")
+            .map_err(|e| skin_error("serverStartFailed", serde_json::json!({ "message": e.to_string() })))?;
+        let port = server.server_addr().to_ip()
+            .map(|addr| addr.port())
+            .ok_or_else(|| skin_error("serverPortUnavailable", serde_json::json!({})))?;
+
+        println!("[SkinServer] Generating signing key...");
+        let private_key = RsaPrivateKey::new(&mut OsRng, RSA_BITS)
+            .map_err(|e| skin_error("signingKeyFailed", serde_json::json!({ "message": e.to_string() })))?;
+        let public_key_pem = private_key.to_public_key()
+            .to_public_key_pem(LineEnding::LF)
+            .map_err(|e| skin_error("signingKeyFailed", serde_json::json!({ "message": e.to_string() })))?;
+        let skin_server = Arc::new(Self {
+            port,
+            signing_key: SigningKey::new(private_key),
+            public_key_pem,
+            profiles: Mutex::new(HashMap::new()),
+            textures: Mutex::new(HashMap::new()),
+        });
+
+        let handle = skin_server.clone();
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                handle.handle(request);
+            }
+        });
+
+        println!("[SkinServer] Listening on 127.0.0.1:{}", port);
+        Ok(skin_server)
+    }
+
+    pub fn api_root(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    fn metadata(&self) -> serde_json::Value {
+        serde_json::json!({
+            "meta": {
+                "serverName": "ImagineLauncher",
+                "implementationName": "imagine-launcher",
+                "implementationVersion": env!("CARGO_PKG_VERSION"),
+                "feature.non_email_login": true
+            },
+            "skinDomains": ["127.0.0.1", "localhost"],
+            "signaturePublickey": self.public_key_pem
+        })
+    }
+
+    // 注册账户的资料和材质文件
+    fn register(&self, account: &Account) {
+        let mut textures = self.textures.lock().unwrap();
+        let mut add_texture = |path: &str| -> Option<String> {
+            let bytes = std::fs::read(path).ok()?;
+            let hash = format!("{:x}", Sha1::digest(&bytes));
+            textures.insert(hash.clone(), PathBuf::from(path));
+            Some(hash)
+        };
+
+        let skin = account.skin.as_deref()
+            .and_then(&mut add_texture)
+            .map(|hash| (hash, account.skin_model.as_deref() == Some("slim")));
+        let cape = account.cape.as_deref().and_then(&mut add_texture);
+
+        self.profiles.lock().unwrap().insert(account.uuid.clone(), OfflineProfile {
+            uuid: account.uuid.clone(),
+            name: account.username.clone(),
+            skin,
+            cape,
+        });
+    }
+
+    fn find_by_name(&self, name: &str) -> Option<OfflineProfile> {
+        self.profiles.lock().unwrap().values()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+            .cloned()
+    }
+
+    fn profile_json(&self, profile: &OfflineProfile, signed: bool) -> serde_json::Value {
+        let root = self.api_root();
+        let mut textures = serde_json::Map::new();
+        if let Some((hash, slim)) = &profile.skin {
+            let mut skin = serde_json::json!({ "url": format!("{}/textures/{}", root, hash) });
+            if *slim {
+                skin["metadata"] = serde_json::json!({ "model": "slim" });
+            }
+            textures.insert("SKIN".to_string(), skin);
+        }
+        if let Some(hash) = &profile.cape {
+            textures.insert("CAPE".to_string(), serde_json::json!({ "url": format!("{}/textures/{}", root, hash) }));
+        }
+
+        let value = BASE64.encode(serde_json::json!({
+            "timestamp": chrono::Utc::now().timestamp_millis(),
+            "profileId": profile.uuid,
+            "profileName": profile.name,
+            "textures": textures
+        }).to_string());
+
+        let mut property = serde_json::json!({ "name": "textures", "value": value });
+        if signed {
+            property["signature"] = serde_json::json!(BASE64.encode(self.signing_key.sign(value.as_bytes()).to_bytes()));
+        }
+
+        serde_json::json!({
+            "id": profile.uuid,
+            "name": profile.name,
+            "properties": [property]
+        })
+    }
+
+    fn handle(&self, mut request: tiny_http::Request) {
+        let url = request.url().to_string();
+        let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+        let param = |key: &str| {
+            query.split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| urlencoding::decode(v).map(|s| s.into_owned()).unwrap_or_default())
+        };
+
+        let response = match (request.method(), path) {
+            (tiny_http::Method::Get, "/") => json_response(200, &self.metadata()),
+            (tiny_http::Method::Get, p) if p.starts_with("/sessionserver/session/minecraft/profile/") => {
+                let uuid = p.rsplit('/').next().unwrap_or_default().replace('-', "");
+                let profile = self.profiles.lock().unwrap().get(&uuid).cloned();
+                match profile {
+                    Some(profile) => {
+                        let signed = param("unsigned").as_deref() == Some("false");
+                        json_response(200, &self.profile_json(&profile, signed))
+                    }
+                    None => empty_response(204),
+                }
+            }
+            (tiny_http::Method::Get, "/sessionserver/session/minecraft/hasJoined") => {
+                match param("username").and_then(|name| self.find_by_name(&name)) {
+                    Some(profile) => json_response(200, &self.profile_json(&profile, true)),
+                    None => empty_response(204),
+                }
+            }
+            // 离线账户无需验证加入服务器
+            (tiny_http::Method::Post, "/sessionserver/session/minecraft/join") => empty_response(204),
+            (tiny_http::Method::Post, "/api/profiles/minecraft") => {
+                let mut body = String::new();
+                let _ = request.as_reader().read_to_string(&mut body);
+                let names: Vec<String> = serde_json::from_str(&body).unwrap_or_default();
+                let found: Vec<serde_json::Value> = names.iter()
+                    .filter_map(|name| self.find_by_name(name))
+                    .map(|p| serde_json::json!({ "id": p.uuid, "name": p.name }))
+                    .collect();
+                json_response(200, &serde_json::json!(found))
+            }
+            (tiny_http::Method::Get, p) if p.starts_with("/textures/") => {
+                let hash = p.trim_start_matches("/textures/");
+                let file = self.textures.lock().unwrap().get(hash).cloned();
+                match file.and_then(|f| std::fs::read(f).ok()) {
+                    Some(bytes) => tiny_http::Response::from_data(bytes)
+                        .with_header(header("Content-Type", "image/png")),
+                    None => empty_response(404),
+                }
+            }
+            _ => empty_response(404),
+        };
+
+        let _ = request.respond(response);
+    }
+}
+
+fn skin_error(key: &str, params: serde_json::Value) -> String {
+    crate::i18n::t(&format!("skins.errors.{}", key), &params)
+}
+
+fn header(name: &str, value: &str) -> tiny_http::Header {
+    tiny_http::Header::from_bytes(name.as_bytes(), value.as_bytes()).unwrap()
+}
+
+fn json_response(status: u16, body: &serde_json::Value) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    tiny_http::Response::from_data(body.to_string().into_bytes())
+        .with_status_code(status)
+        .with_header(header("Content-Type", "application/json; charset=utf-8"))
+}
+
+fn empty_response(status: u16) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    tiny_http::Response::from_data(Vec::new()).with_status_code(status)
+}
+
+/// 启动（或复用）本地皮肤服务器，并发调用只会启动一个实例，启动失败时下次调用会重试
+pub async fn ensure_started() -> Result<Arc<SkinServer>, String> {
+    SKIN_SERVER
+        .get_or_try_init(|| async {
+            // 生成 RSA 密钥较慢，放到阻塞线程
+            tokio::task::spawn_blocking(SkinServer::bind)
+                .await
+                .map_err(|e| e.to_string())?
+        })
+        .await
+        .cloned()
+}
+
+/// 离线账户是否需要通过皮肤服务器加载材质
+pub fn needs_skin_server(account: &Account) -> bool {
+    account.account_type == "offline" && (account.skin.is_some() || account.cape.is_some())
+}
+
+/// 为离线账户构建 authlib-injector 参数，指向本地皮肤服务器
+pub async fn build_agent_args(data_dir: &Path, account: &Account) -> Result<Vec<String>, String> {
+    let server = ensure_started().await?;
+    server.register(account);
+
+    let jar_path = crate::yggdrasil::ensure_authlib_injector(data_dir).await?;
+    Ok(vec![
+        format!("-javaagent:{}={}", jar_path.display(), server.api_root()),
+        format!("-Dauthlibinjector.yggdrasil.prefetched={}", BASE64.encode(server.metadata().to_string())),
+    ])
+}
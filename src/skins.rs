@@ -0,0 +1,408 @@
+use crate::accounts::{Account, MinecraftProfile};
+use crate::error::LauncherError;
+use crate::state::AppState;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+const PROFILE_API: &str = "https://api.minecraftservices.com/minecraft/profile";
+
+/// 皮肤库条目，文件保存在 skins/library/<hash>.png
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SkinLibraryEntry {
+    pub hash: String,
+    pub name: String,
+    // classic / slim
+    pub model: String,
+    pub added_at: i64,
+    #[serde(default)]
+    pub last_used_at: Option<i64>,
+}
+
+fn library_dir(skins_dir: &Path) -> PathBuf {
+    skins_dir.join("library")
+}
+
+fn load_library(skins_dir: &Path) -> Vec<SkinLibraryEntry> {
+    std::fs::read_to_string(skins_dir.join("library.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_library(skins_dir: &Path, library: &[SkinLibraryEntry]) -> Result<(), LauncherError> {
+    std::fs::create_dir_all(skins_dir)?;
+    let content = serde_json::to_string_pretty(library)
+        .map_err(|e| LauncherError::Internal { message: e.to_string() })?;
+    std::fs::write(skins_dir.join("library.json"), content)?;
+    Ok(())
+}
+
+fn normalize_model(model: Option<&str>) -> String {
+    match model {
+        Some(m) if m.eq_ignore_ascii_case("slim") => "slim".to_string(),
+        _ => "classic".to_string(),
+    }
+}
+
+// 读取皮肤文件或下载皮肤 URL，校验后返回 (PNG 数据, 检测到的模型)
+async fn load_skin_bytes(source: &str) -> Result<(Vec<u8>, &'static str), LauncherError> {
+    let bytes = crate::skin_render::fetch_skin_bytes(source).await?;
+    let skin = crate::skin_render::load_skin(&bytes)?;
+    // 旧版皮肤使用转换后的数据
+    let bytes = if skin.converted { skin.to_png()? } else { bytes };
+    Ok((bytes, skin.model()))
+}
+
+// 将皮肤加入皮肤库，相同内容只保存一份
+fn add_to_library(skins_dir: &Path, bytes: &[u8], name: &str, model: &str) -> Result<SkinLibraryEntry, LauncherError> {
+    let hash = format!("{:x}", Sha1::digest(bytes));
+    let dir = library_dir(skins_dir);
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join(format!("{}.png", hash)), bytes)?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut library = load_library(skins_dir);
+    let entry = match library.iter_mut().find(|e| e.hash == hash) {
+        Some(existing) => {
+            existing.model = model.to_string();
+            existing.last_used_at = Some(now);
+            existing.clone()
+        }
+        None => {
+            let entry = SkinLibraryEntry {
+                hash,
+                name: name.to_string(),
+                model: model.to_string(),
+                added_at: now,
+                last_used_at: Some(now),
+            };
+            library.push(entry.clone());
+            entry
+        }
+    };
+    save_library(skins_dir, &library)?;
+    Ok(entry)
+}
+
+fn touch_library_entry(skins_dir: &Path, hash: &str) {
+    let mut library = load_library(skins_dir);
+    if let Some(entry) = library.iter_mut().find(|e| e.hash == hash) {
+        entry.last_used_at = Some(chrono::Utc::now().timestamp_millis());
+        let _ = save_library(skins_dir, &library);
+    }
+}
+
+// 解析 Minecraft 服务的响应，失败时返回服务端错误信息
+async fn profile_response(response: reqwest::Response) -> Result<serde_json::Value, LauncherError> {
+    let status = response.status();
+    let json: serde_json::Value = response.json().await.unwrap_or(serde_json::Value::Null);
+    if !status.is_success() {
+        let message = json["errorMessage"].as_str()
+            .or_else(|| json["error"].as_str())
+            .or(status.canonical_reason())
+            .unwrap_or_default()
+            .to_string();
+        println!("[Skins] Profile API error ({}): {}", status, message);
+        return Err(LauncherError::ProfileApi { status: status.as_u16(), message });
+    }
+    Ok(json)
+}
+
+// 用 profile 响应更新账户的皮肤和披风
+async fn apply_profile(
+    state: &Arc<Mutex<AppState>>,
+    account_id: &str,
+    profile: &serde_json::Value,
+) -> Result<Option<Account>, LauncherError> {
+    let parsed = MinecraftProfile::from_json(profile);
+    let mut app_state = state.lock().await;
+    let avatar_path = app_state.get_avatars_dir().join(format!("{}.png", account_id));
+
+    let account = app_state.accounts_data.accounts.iter_mut().find(|a| a.id == account_id);
+    let updated = match (account, parsed) {
+        (Some(acc), Some(profile)) => {
+            acc.skin = profile.skin_url;
+            acc.skin_model = profile.skin_model;
+            acc.cape = profile.cape_url;
+            Some(acc.clone())
+        }
+        _ => None,
+    };
+    // 皮肤变化后重新生成头像
+    let _ = std::fs::remove_file(avatar_path);
+    app_state.save_accounts().map_err(|e| LauncherError::Io { message: e.to_string() })?;
+    Ok(updated)
+}
+
+async fn upload_skin_bytes(access_token: &str, bytes: &[u8], model: &str) -> Result<serde_json::Value, LauncherError> {
+    let file = reqwest::multipart::Part::bytes(bytes.to_vec())
+        .file_name("skin.png")
+        .mime_str("image/png")?;
+    let form = reqwest::multipart::Form::new()
+        .text("variant", model.to_string())
+        .part("file", file);
+    let response = reqwest::Client::new()
+        .post(format!("{}/skins", PROFILE_API))
+        .bearer_auth(access_token)
+        .multipart(form)
+        .send()
+        .await?;
+    profile_response(response).await
+}
+
+fn skin_result(account: Option<Account>) -> serde_json::Value {
+    serde_json::json!({
+        "success": true,
+        "account": account
+    })
+}
+
+// 获取微软账户拥有的全部皮肤和披风
+#[tauri::command]
+pub async fn get_microsoft_textures(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    account_id: String,
+) -> Result<serde_json::Value, LauncherError> {
+    let account = crate::accounts::valid_microsoft_account(&state, &account_id).await?;
+    let response = reqwest::Client::new()
+        .get(PROFILE_API)
+        .bearer_auth(account.access_token.as_deref().unwrap_or_default())
+        .send()
+        .await?;
+    let profile = profile_response(response).await?;
+    apply_profile(&state, &account_id, &profile).await?;
+
+    Ok(serde_json::json!({
+        "success": true,
+        "skins": profile["skins"],
+        "capes": profile["capes"]
+    }))
+}
+
+// 上传皮肤到微软账户，source 可以是本地文件或 URL
+#[tauri::command]
+pub async fn upload_microsoft_skin(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    account_id: String,
+    source: String,
+    model: Option<String>,
+) -> Result<serde_json::Value, LauncherError> {
+    let account = crate::accounts::valid_microsoft_account(&state, &account_id).await?;
+    let (bytes, detected) = load_skin_bytes(&source).await?;
+    // 未指定模型时使用自动检测结果
+    let model = normalize_model(model.as_deref().or(Some(detected)));
+
+    let skins_dir = state.lock().await.get_skins_dir();
+    let name = Path::new(&source).file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "skin".to_string());
+    add_to_library(&skins_dir, &bytes, &name, &model)?;
+
+    println!("[Skins] Uploading {} skin for {}", model, account.username);
+    let profile = upload_skin_bytes(account.access_token.as_deref().unwrap_or_default(), &bytes, &model).await?;
+    Ok(skin_result(apply_profile(&state, &account_id, &profile).await?))
+}
+
+// 重置为默认皮肤
+#[tauri::command]
+pub async fn reset_microsoft_skin(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    account_id: String,
+) -> Result<serde_json::Value, LauncherError> {
+    let account = crate::accounts::valid_microsoft_account(&state, &account_id).await?;
+    let response = reqwest::Client::new()
+        .delete(format!("{}/skins/active", PROFILE_API))
+        .bearer_auth(account.access_token.as_deref().unwrap_or_default())
+        .send()
+        .await?;
+    let profile = profile_response(response).await?;
+    Ok(skin_result(apply_profile(&state, &account_id, &profile).await?))
+}
+
+// 装备披风，cape_id 为空时隐藏披风
+#[tauri::command]
+pub async fn equip_microsoft_cape(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    account_id: String,
+    cape_id: Option<String>,
+) -> Result<serde_json::Value, LauncherError> {
+    let account = crate::accounts::valid_microsoft_account(&state, &account_id).await?;
+    let client = reqwest::Client::new();
+    let url = format!("{}/capes/active", PROFILE_API);
+    let request = match &cape_id {
+        Some(id) => client.put(&url).json(&serde_json::json!({ "capeId": id })),
+        None => client.delete(&url),
+    };
+    let response = request
+        .bearer_auth(account.access_token.as_deref().unwrap_or_default())
+        .send()
+        .await?;
+    let profile = profile_response(response).await?;
+    Ok(skin_result(apply_profile(&state, &account_id, &profile).await?))
+}
+
+#[tauri::command]
+pub async fn get_skin_library(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+) -> Result<serde_json::Value, LauncherError> {
+    let skins_dir = state.lock().await.get_skins_dir();
+    let dir = library_dir(&skins_dir);
+
+    // 最近使用的排在前面
+    let mut library = load_library(&skins_dir);
+    library.sort_by_key(|e| std::cmp::Reverse(e.last_used_at.unwrap_or(e.added_at)));
+
+    let skins: Vec<serde_json::Value> = library.iter()
+        .map(|e| {
+            let mut value = serde_json::to_value(e).unwrap_or_default();
+            value["path"] = serde_json::json!(dir.join(format!("{}.png", e.hash)).to_string_lossy());
+            value
+        })
+        .collect();
+
+    Ok(serde_json::json!({
+        "success": true,
+        "skins": skins
+    }))
+}
+
+#[tauri::command]
+pub async fn add_skin_to_library(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    source: String,
+    name: Option<String>,
+    model: Option<String>,
+) -> Result<serde_json::Value, LauncherError> {
+    let skins_dir = state.lock().await.get_skins_dir();
+    let (bytes, detected) = load_skin_bytes(&source).await?;
+    let name = name.filter(|n| !n.trim().is_empty()).unwrap_or_else(|| {
+        Path::new(&source).file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "skin".to_string())
+    });
+    let entry = add_to_library(&skins_dir, &bytes, name.trim(), &normalize_model(model.as_deref().or(Some(detected))))?;
+
+    Ok(serde_json::json!({
+        "success": true,
+        "skin": entry
+    }))
+}
+
+#[tauri::command]
+pub async fn remove_skin_from_library(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    hash: String,
+) -> Result<serde_json::Value, LauncherError> {
+    let skins_dir = state.lock().await.get_skins_dir();
+    let mut library = load_library(&skins_dir);
+    library.retain(|e| e.hash != hash);
+    save_library(&skins_dir, &library)?;
+    let _ = std::fs::remove_file(library_dir(&skins_dir).join(format!("{}.png", hash)));
+
+    Ok(serde_json::json!({ "success": true }))
+}
+
+// 将皮肤库中的皮肤应用到账户：微软账户上传到官方，离线账户设为本地皮肤
+// 外置登录账户的皮肤由认证服务器管理，需要在其网站更换
+#[tauri::command]
+pub async fn apply_library_skin(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    account_id: String,
+    hash: String,
+) -> Result<serde_json::Value, LauncherError> {
+    let (skins_dir, account) = {
+        let app_state = state.lock().await;
+        let account = app_state.accounts_data.accounts.iter().find(|a| a.id == account_id).cloned();
+        (app_state.get_skins_dir(), account)
+    };
+    let account = account.ok_or_else(|| LauncherError::AccountNotFound { account_id: account_id.clone() })?;
+    let entry = load_library(&skins_dir).into_iter()
+        .find(|e| e.hash == hash)
+        .ok_or_else(|| LauncherError::SkinNotFound { hash: hash.clone() })?;
+    let skin_path = library_dir(&skins_dir).join(format!("{}.png", hash));
+
+    let updated = match account.account_type.as_str() {
+        "microsoft" => {
+            let account = crate::accounts::valid_microsoft_account(&state, &account_id).await?;
+            let bytes = std::fs::read(&skin_path)?;
+            let profile = upload_skin_bytes(account.access_token.as_deref().unwrap_or_default(), &bytes, &entry.model).await?;
+            apply_profile(&state, &account_id, &profile).await?
+        }
+        "offline" => {
+            let dest = skins_dir.join(format!("{}.png", account_id));
+            let mut app_state = state.lock().await;
+            let avatar_path = app_state.get_avatars_dir().join(format!("{}.png", account_id));
+            std::fs::copy(&skin_path, &dest)?;
+            let updated = app_state.accounts_data.accounts.iter_mut()
+                .find(|a| a.id == account_id)
+                .map(|acc| {
+                    acc.skin = Some(dest.to_string_lossy().to_string());
+                    acc.skin_model = Some(entry.model.clone());
+                    acc.clone()
+                });
+            let _ = std::fs::remove_file(avatar_path);
+            app_state.save_accounts().map_err(|e| LauncherError::Io { message: e.to_string() })?;
+            updated
+        }
+        _ => {
+            return Err(LauncherError::UnsupportedAccountType { account_id, expected: "microsoft" });
+        }
+    };
+
+    touch_library_entry(&skins_dir, &hash);
+    Ok(skin_result(updated))
+}
+
+// 设置离线账户的披风，cape_path 为空时移除
+#[tauri::command]
+pub async fn set_offline_cape(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    account_id: String,
+    cape_path: Option<String>,
+) -> Result<serde_json::Value, LauncherError> {
+    let mut app_state = state.lock().await;
+    let dest_path = app_state.get_skins_dir().join(format!("{}_cape.png", account_id));
+
+    let acc = app_state.accounts_data.accounts.iter_mut()
+        .find(|a| a.id == account_id)
+        .ok_or_else(|| LauncherError::AccountNotFound { account_id: account_id.clone() })?;
+    if acc.account_type != "offline" {
+        return Err(LauncherError::UnsupportedAccountType { account_id, expected: "offline" });
+    }
+
+    match cape_path {
+        Some(path) => {
+            // 披风材质为 64x32，高清披风按比例放大
+            let img = image::open(&path).map_err(|_| LauncherError::InvalidSkin {
+                message: crate::i18n::t("skins.errors.capeNotPng", &serde_json::json!({})),
+            })?;
+            if img.width() < 64 || img.width() * 32 != img.height() * 64 {
+                return Err(LauncherError::InvalidSkin {
+                    message: crate::i18n::t("skins.errors.capeSize", &serde_json::json!({
+                        "width": img.width(),
+                        "height": img.height()
+                    })),
+                });
+            }
+            // 按 PNG 重新编码，其他格式的图片不会以 image/png 提供给游戏
+            img.save_with_format(&dest_path, image::ImageFormat::Png)
+                .map_err(|e| LauncherError::Io { message: e.to_string() })?;
+            acc.cape = Some(dest_path.to_string_lossy().to_string());
+        }
+        None => {
+            let _ = std::fs::remove_file(&dest_path);
+            acc.cape = None;
+        }
+    }
+    let account = acc.clone();
+    app_state.save_accounts().map_err(|e| LauncherError::Io { message: e.to_string() })?;
+
+    Ok(serde_json::json!({
+        "success": true,
+        "account": account
+    }))
+}
@@ -1,5 +1,6 @@
 use crate::accounts::Account;
 use crate::config::AppConfig;
+use crate::credentials::CredentialStore;
 use crate::launcher::LauncherSettings;
 use crate::minecraft::RunningInstance;
 use serde::{Deserialize, Serialize};
@@ -30,9 +31,17 @@ pub struct AppState {
     pub data_dir: PathBuf,
     pub config: AppConfig,
     pub accounts_data: AccountsData,
+    pub credentials: CredentialStore,
     pub launcher_settings: LauncherSettings,
     pub running_instances: Arc<RwLock<HashMap<u32, RunningInstance>>>,
     pub last_crash_data: Option<serde_json::Value>,
+    // 最近一次分享预览的内容，确认分享时上传的就是这份内容
+    pub crash_share_preview: Option<String>,
+    pub bisect_session: Option<crate::bisect::BisectSession>,
+    // 外置登录等待选择角色的登录结果
+    pub pending_yggdrasil_login: Option<crate::yggdrasil::PendingLogin>,
+    // 微软账户尚未创建角色时暂存的令牌
+    pub pending_microsoft_profile: Option<crate::accounts::PendingProfile>,
 }
 
 impl AppState {
@@ -46,25 +55,44 @@ impl AppState {
         fs::create_dir_all(&data_dir)?;
         fs::create_dir_all(data_dir.join("skins"))?;
         fs::create_dir_all(data_dir.join("avatars"))?;
+        fs::create_dir_all(data_dir.join("cache"))?;
 
         // 加载配置
         let config = Self::load_config(&app_handle)?;
+        crate::i18n::set_language(&config.app.language);
         
         // 加载账户数据
-        let accounts_data = Self::load_accounts(&data_dir);
+        let credentials = CredentialStore::new(&data_dir);
+        let (accounts_data, migrated) = Self::load_accounts(&data_dir, &credentials);
         
         // 加载启动器设置
         let launcher_settings = Self::load_launcher_settings(&data_dir);
 
-        Ok(Self {
+        let state = Self {
             app_handle,
             data_dir,
             config,
             accounts_data,
+            credentials,
             launcher_settings,
             running_instances: Arc::new(RwLock::new(HashMap::new())),
             last_crash_data: None,
-        })
+            crash_share_preview: None,
+            bisect_session: None,
+            pending_yggdrasil_login: None,
+            pending_microsoft_profile: None,
+        };
+
+        // 旧版本的 accounts.json 中包含明文令牌，迁移到凭据存储后重写
+        if migrated {
+            println!("[State] Migrating plaintext account tokens to credential store");
+            // 凭据未能保存时保留旧文件，下次启动重试
+            if let Err(e) = state.save_accounts() {
+                println!("[State] Token migration failed, keeping accounts.json unchanged: {}", e);
+            }
+        }
+
+        Ok(state)
     }
 
     fn load_config(app_handle: &AppHandle) -> Result<AppConfig, Box<dyn std::error::Error>> {
@@ -110,7 +138,7 @@ impl AppState {
         Ok(AppConfig::default())
     }
 
-    fn load_accounts(data_dir: &PathBuf) -> AccountsData {
+    fn load_accounts(data_dir: &PathBuf, credentials: &CredentialStore) -> (AccountsData, bool) {
         let accounts_file = data_dir.join("accounts.json");
         if accounts_file.exists() {
             if let Ok(content) = fs::read_to_string(&accounts_file) {
@@ -118,11 +146,22 @@ impl AppState {
                     // 清理损坏的账户
                     let mut data = data;
                     data.accounts.retain(|a| !a.id.is_empty() && !a.username.is_empty());
-                    return data;
+                    
+                    // 文件中仍有明文令牌则需要迁移，否则从凭据存储读取
+                    let mut migrated = false;
+                    for account in &mut data.accounts {
+                        if account.access_token.is_some() || account.refresh_token.is_some() {
+                            migrated = true;
+                        } else if let Some(secrets) = credentials.load(&account.id) {
+                            account.access_token = secrets.access_token;
+                            account.refresh_token = secrets.refresh_token;
+                        }
+                    }
+                    return (data, migrated);
                 }
             }
         }
-        AccountsData::default()
+        (AccountsData::default(), false)
     }
 
     fn load_launcher_settings(data_dir: &PathBuf) -> LauncherSettings {
@@ -161,6 +200,10 @@ impl AppState {
     }
 
     pub fn save_accounts(&self) -> Result<(), Box<dyn std::error::Error>> {
+        // 令牌单独保存，accounts.json 只包含非敏感信息。
+        // 凭据确认保存后才重写，迁移时旧文件中的明文令牌在此之前不会丢失
+        self.credentials.sync(&self.accounts_data.accounts)?;
+        
         let accounts_file = self.data_dir.join("accounts.json");
         let content = serde_json::to_string_pretty(&self.accounts_data)?;
         fs::write(accounts_file, content)?;
@@ -184,4 +227,8 @@ impl AppState {
     pub fn get_avatars_dir(&self) -> PathBuf {
         self.data_dir.join("avatars")
     }
+
+    pub fn get_cache_dir(&self) -> PathBuf {
+        self.data_dir.join("cache")
+    }
 }
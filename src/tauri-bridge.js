@@ -120,7 +120,7 @@ const ipcRenderer = {
     } else if (args.length > 0) {
       switch (command) {
         case 'add_offline_account':
-          params = { username: args[0] };
+          params = { username: args[0], customUuid: args[1] || null, allowNonstandard: args[2] || false };
           break;
         case 'remove_account':
         case 'switch_account':
@@ -129,7 +129,10 @@ const ipcRenderer = {
           params = { accountId: args[0] };
           break;
         case 'update_account_username':
-          params = { accountId: args[0], newUsername: args[1] };
+          params = { accountId: args[0], newUsername: args[1], allowNonstandard: args[2] || false };
+          break;
+        case 'migrate_offline_player_data':
+          params = { oldUuid: args[0], newUuid: args[1] };
           break;
         case 'set_offline_skin':
           params = { accountId: args[0], skinPath: args[1] };
@@ -146,6 +149,8 @@ const ipcRenderer = {
           break;
         case 'get_version_info':
         case 'get_required_java_version':
+        case 'install_java_runtime':
+        case 'validate_java_config':
           params = { versionId: args[0] };
           break;
         case 'launch_game':
@@ -167,6 +172,16 @@ const ipcRenderer = {
           }
           console.log('[Bridge] launch_game params:', params);
           break;
+        case 'generate_launch_script':
+          // versionId, savePath, { accountId, shell, includeToken }
+          params = {
+            versionId: args[0],
+            savePath: args[1],
+            accountId: args[2]?.accountId || null,
+            shell: args[2]?.shell || null,
+            includeToken: args[2]?.includeToken || false
+          };
+          break;
         case 'save_config':
           params = { newConfig: args[0] };
           break;
@@ -183,7 +198,14 @@ const ipcRenderer = {
         case 'manual_auth_callback':
           params = { callbackUrl: args[0] };
           break;
+        case 'resolve_auth_server':
+          params = { serverUrl: args[0] };
+          break;
+        case 'select_yggdrasil_profile':
+          params = { profileId: args[0] };
+          break;
         case 'refresh_microsoft_account':
+        case 'refresh_yggdrasil_account':
           params = { accountId: args[0] };
           break;
         case 'get_instance_logs':
@@ -206,6 +228,12 @@ const ipcRenderer = {
         case 'delete_mod':
           params = { modsDir: args[0], file: args[1] };
           break;
+        case 'start_mod_bisect':
+          params = { versionId: args[0], accountId: args[1] || null };
+          break;
+        case 'share_crash_report':
+          params = { preview: args[0] || false };
+          break;
         case 'copy_mod_to_dir':
           params = { sourcePath: args[0], modsDir: args[1] };
           break;
@@ -232,7 +260,7 @@ const ipcRenderer = {
           break;
         // 下载相关
         case 'download_minecraft_version':
-          params = { versionId: args[0], manifestUrl: args[1] };
+          params = { versionId: args[0], manifestUrl: args[1] || null };
           break;
         case 'install_fabric':
           params = { mcVersion: args[0], loaderVersion: args[1] };
@@ -256,6 +284,11 @@ const ipcRenderer = {
       return await tauri.core.invoke(command, params);
     } catch (error) {
       console.error(`Tauri invoke error (${command}):`, error);
+      // 后端的 LauncherError 带有 code，按 { success: false, error } 的约定作为结果返回
+      // error 已由后端按当前语言渲染
+      if (error && typeof error === 'object' && error.code) {
+        return error;
+      }
       throw error;
     }
   },
@@ -0,0 +1,454 @@
+use crate::accounts::{Account, RefreshError};
+use crate::error::LauncherError;
+use crate::state::AppState;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+// authlib-injector 外置登录 (Yggdrasil API)
+// 规范: https://github.com/yushijinhun/authlib-injector/wiki
+
+const AUTHLIB_INJECTOR_LATEST_URL: &str = "https://authlib-injector.yushi.moe/artifact/latest.json";
+const AUTHLIB_INJECTOR_FILE: &str = "authlib-injector.jar";
+
+// 语言文件中 yggdrasil.* 下的提示文本
+fn t(key: &str, params: serde_json::Value) -> String {
+    crate::i18n::t(&format!("yggdrasil.{}", key), &params)
+}
+
+/// 多角色账户等待选择角色时暂存的登录结果，选择后用同一个令牌绑定角色，无需再次输入密码
+pub struct PendingLogin {
+    api_root: String,
+    server_name: Option<String>,
+    access_token: String,
+    client_token: String,
+    profiles: Vec<serde_json::Value>,
+}
+
+/// 解析认证服务器地址，支持 X-Authlib-Injector-API-Location (ALI) 跳转
+pub async fn resolve_api_root(server_url: &str) -> Result<String, String> {
+    let server_url = server_url.trim();
+    let server_url = if server_url.starts_with("http://") || server_url.starts_with("https://") {
+        server_url.to_string()
+    } else {
+        format!("https://{}", server_url)
+    };
+
+    let response = reqwest::Client::new()
+        .get(&server_url)
+        .send()
+        .await
+        .map_err(|e| t("connectFailed", serde_json::json!({ "message": e.to_string() })))?;
+
+    let base = response.url().clone();
+    let api_root = match response.headers()
+        .get("X-Authlib-Injector-API-Location")
+        .and_then(|v| v.to_str().ok())
+    {
+        // ALI 可以是相对地址
+        Some(location) => base.join(location).map_err(|e| e.to_string())?.to_string(),
+        None => server_url,
+    };
+
+    Ok(api_root.trim_end_matches('/').to_string())
+}
+
+/// 获取认证服务器元数据（API 根地址的响应）
+pub async fn fetch_metadata(api_root: &str) -> Result<serde_json::Value, String> {
+    let metadata: serde_json::Value = reqwest::Client::new()
+        .get(api_root)
+        .send()
+        .await
+        .map_err(|e| t("metadataFailed", serde_json::json!({ "message": e.to_string() })))?
+        .json()
+        .await
+        .map_err(|e| t("metadataFailed", serde_json::json!({ "message": e.to_string() })))?;
+
+    if metadata.get("meta").is_none() {
+        return Err(t("invalidServer", serde_json::json!({})));
+    }
+    Ok(metadata)
+}
+
+fn endpoint(api_root: &str, path: &str) -> String {
+    format!("{}/{}", api_root.trim_end_matches('/'), path)
+}
+
+// 发送 Yggdrasil 请求，返回 (状态码, 响应 JSON)
+async fn post_json(url: &str, body: serde_json::Value) -> Result<(u16, serde_json::Value), String> {
+    let response = reqwest::Client::new()
+        .post(url)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let status = response.status().as_u16();
+    let json = response.json().await.unwrap_or(serde_json::Value::Null);
+    Ok((status, json))
+}
+
+fn yggdrasil_error(json: &serde_json::Value) -> String {
+    json["errorMessage"].as_str()
+        .or_else(|| json["error"].as_str())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| t("authFailed", serde_json::json!({})))
+}
+
+/// 外置登录账户的 ID 由认证服务器和角色共同决定，不同服务器上 UUID 相同的角色不会互相覆盖
+fn yggdrasil_account_id(api_root: &str, profile_id: &str) -> String {
+    use sha1::{Digest, Sha1};
+    let server_hash: String = Sha1::digest(api_root.trim_end_matches('/').as_bytes())
+        .iter()
+        .take(4)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("yggdrasil-{}-{}", server_hash, profile_id)
+}
+
+// 从 session 服务器获取角色皮肤地址
+async fn fetch_skin_url(api_root: &str, uuid: &str) -> Option<String> {
+    let profile: serde_json::Value = reqwest::Client::new()
+        .get(endpoint(api_root, &format!("sessionserver/session/minecraft/profile/{}", uuid)))
+        .send()
+        .await
+        .ok()?
+        .json()
+        .await
+        .ok()?;
+
+    let textures = profile["properties"].as_array()?
+        .iter()
+        .find(|p| p["name"] == "textures")?["value"]
+        .as_str()?;
+    let decoded: serde_json::Value = serde_json::from_slice(&BASE64.decode(textures).ok()?).ok()?;
+    decoded.pointer("/textures/SKIN/url")?.as_str().map(|s| s.to_string())
+}
+
+fn account_from_profile(
+    api_root: &str,
+    server_name: Option<String>,
+    profile: &serde_json::Value,
+    access_token: &str,
+    client_token: &str,
+) -> Option<Account> {
+    let uuid = profile["id"].as_str()?.to_string();
+    Some(Account {
+        id: yggdrasil_account_id(api_root, &uuid),
+        account_type: "yggdrasil".to_string(),
+        username: profile["name"].as_str()?.to_string(),
+        uuid,
+        access_token: Some(access_token.to_string()),
+        refresh_token: None,
+        skin: None,
+        created_at: Some(chrono::Utc::now().timestamp_millis()),
+        token_expires_at: None,
+        ms_token_expires_at: None,
+        ms_token_url: None,
+        auth_server: Some(api_root.to_string()),
+        auth_server_name: server_name,
+        client_token: Some(client_token.to_string()),
+        skin_model: None,
+        cape: None,
+    })
+}
+
+// 查询认证服务器信息，供前端添加账户前确认
+#[tauri::command]
+pub async fn resolve_auth_server(server_url: String) -> Result<serde_json::Value, LauncherError> {
+    let result = async {
+        let api_root = resolve_api_root(&server_url).await?;
+        let metadata = fetch_metadata(&api_root).await?;
+        Ok::<_, String>((api_root, metadata))
+    }.await;
+
+    match result {
+        Ok((api_root, metadata)) => Ok(serde_json::json!({
+            "success": true,
+            "apiRoot": api_root,
+            "serverName": metadata.pointer("/meta/serverName"),
+            "metadata": metadata
+        })),
+        Err(message) => Err(LauncherError::Network { message }),
+    }
+}
+
+// 添加外置登录账户，多角色账户需要先返回角色列表，由 select_yggdrasil_profile 完成登录
+#[tauri::command]
+pub async fn add_yggdrasil_account(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    server_url: String,
+    username: String,
+    password: String,
+) -> Result<serde_json::Value, LauncherError> {
+    let network = |message| LauncherError::Network { message };
+    let auth_failed = |key: &str| LauncherError::Auth { message: t(key, serde_json::json!({})) };
+
+    let api_root = resolve_api_root(&server_url).await.map_err(network)?;
+    let metadata = fetch_metadata(&api_root).await.map_err(network)?;
+    let server_name = metadata.pointer("/meta/serverName").and_then(|v| v.as_str()).map(|s| s.to_string());
+
+    println!("[Yggdrasil] Authenticating {} against {}", username, api_root);
+
+    let client_token = uuid::Uuid::new_v4().simple().to_string();
+    let (status, auth) = post_json(&endpoint(&api_root, "authserver/authenticate"), serde_json::json!({
+        "agent": { "name": "Minecraft", "version": 1 },
+        "username": username,
+        "password": password,
+        "clientToken": client_token,
+        "requestUser": true
+    })).await.map_err(network)?;
+
+    if status != 200 {
+        println!("[Yggdrasil] Authenticate failed ({}): {}", status, yggdrasil_error(&auth));
+        return Err(LauncherError::Auth { message: yggdrasil_error(&auth) });
+    }
+
+    let access_token = auth["accessToken"].as_str().ok_or_else(|| auth_failed("invalidResponse"))?.to_string();
+    let client_token = auth["clientToken"].as_str().unwrap_or(&client_token).to_string();
+    let profiles = auth["availableProfiles"].as_array().cloned().unwrap_or_default();
+
+    let pending = PendingLogin { api_root, server_name, access_token, client_token, profiles };
+    match auth.get("selectedProfile").filter(|p| !p.is_null()) {
+        Some(selected) => {
+            let account = account_from_profile(
+                &pending.api_root, pending.server_name, selected, &pending.access_token, &pending.client_token,
+            ).ok_or_else(|| auth_failed("invalidProfile"))?;
+            save_account(&state, account).await
+        }
+        None if pending.profiles.is_empty() => Err(auth_failed("noProfiles")),
+        None if pending.profiles.len() > 1 => {
+            let profiles = pending.profiles.clone();
+            state.lock().await.pending_yggdrasil_login = Some(pending);
+            Ok(serde_json::json!({
+                "success": false,
+                "needsProfileSelection": true,
+                "profiles": profiles
+            }))
+        }
+        None => {
+            let profile_id = pending.profiles[0]["id"].as_str().unwrap_or_default().to_string();
+            let account = bind_profile(pending, &profile_id).await?;
+            save_account(&state, account).await
+        }
+    }
+}
+
+// 为多角色账户选择角色，使用 add_yggdrasil_account 暂存的令牌
+#[tauri::command]
+pub async fn select_yggdrasil_profile(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    profile_id: String,
+) -> Result<serde_json::Value, LauncherError> {
+    let pending = state.lock().await.pending_yggdrasil_login.take()
+        .ok_or_else(|| LauncherError::Auth { message: t("noPendingLogin", serde_json::json!({})) })?;
+    let account = bind_profile(pending, &profile_id).await?;
+    save_account(&state, account).await
+}
+
+// 通过 refresh 将令牌绑定到所选角色
+async fn bind_profile(pending: PendingLogin, profile_id: &str) -> Result<Account, LauncherError> {
+    let auth_failed = |key: &str| LauncherError::Auth { message: t(key, serde_json::json!({})) };
+    let profile = pending.profiles.iter()
+        .find(|p| p["id"].as_str() == Some(profile_id))
+        .ok_or_else(|| auth_failed("profileNotFound"))?;
+
+    let (status, refreshed) = post_json(&endpoint(&pending.api_root, "authserver/refresh"), serde_json::json!({
+        "accessToken": pending.access_token,
+        "clientToken": pending.client_token,
+        "selectedProfile": { "id": profile["id"], "name": profile["name"] }
+    })).await.map_err(|message| LauncherError::Network { message })?;
+    if status != 200 {
+        return Err(LauncherError::Auth { message: yggdrasil_error(&refreshed) });
+    }
+    let access_token = refreshed["accessToken"].as_str().ok_or_else(|| auth_failed("invalidResponse"))?;
+    let client_token = refreshed["clientToken"].as_str().unwrap_or(&pending.client_token);
+
+    account_from_profile(&pending.api_root, pending.server_name.clone(), profile, access_token, client_token)
+        .ok_or_else(|| auth_failed("invalidProfile"))
+}
+
+// 保存登录得到的账户并设为当前账户；同一服务器同一角色的旧账户（包括旧版按 UUID 命名的）被替换
+async fn save_account(
+    state: &tauri::State<'_, Arc<Mutex<AppState>>>,
+    mut account: Account,
+) -> Result<serde_json::Value, LauncherError> {
+    if let Some(api_root) = &account.auth_server {
+        account.skin = fetch_skin_url(api_root, &account.uuid).await;
+    }
+
+    println!("[Yggdrasil] Added account {} ({})", account.username, account.id);
+
+    let mut app_state = state.lock().await;
+    let data = &mut app_state.accounts_data;
+    let existing = data.accounts.iter().position(|a| {
+        a.id == account.id
+            || (a.account_type == "yggdrasil" && a.uuid == account.uuid && a.auth_server == account.auth_server)
+    });
+    match existing {
+        Some(index) => data.accounts[index] = account.clone(),
+        None => data.accounts.push(account.clone()),
+    }
+    data.current_account = Some(account.id.clone());
+    app_state.save_accounts().map_err(|e| LauncherError::Io { message: e.to_string() })?;
+
+    Ok(serde_json::json!({
+        "success": true,
+        "account": account,
+        "currentAccount": app_state.accounts_data.current_account
+    }))
+}
+
+#[tauri::command]
+pub async fn refresh_yggdrasil_account(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    account_id: String,
+) -> Result<serde_json::Value, LauncherError> {
+    let account = {
+        let app_state = state.lock().await;
+        app_state.accounts_data.accounts.iter()
+            .find(|a| a.id == account_id)
+            .cloned()
+            .ok_or_else(|| LauncherError::AccountNotFound { account_id: account_id.clone() })?
+    };
+    if account.account_type != "yggdrasil" {
+        return Err(LauncherError::UnsupportedAccountType { account_id, expected: "yggdrasil" });
+    }
+
+    let mut refreshed = refresh_token(&account).await.map_err(|e| {
+        println!("[Yggdrasil] Refresh failed: {}", e);
+        e.into_launcher_error(&account_id)
+    })?;
+    if let Some(api_root) = &refreshed.auth_server {
+        refreshed.skin = fetch_skin_url(api_root, &refreshed.uuid).await.or(refreshed.skin);
+    }
+
+    let mut app_state = state.lock().await;
+    if let Some(acc) = app_state.accounts_data.accounts.iter_mut().find(|a| a.id == account_id) {
+        *acc = refreshed.clone();
+    }
+    app_state.save_accounts().map_err(|e| LauncherError::Io { message: e.to_string() })?;
+
+    Ok(serde_json::json!({
+        "success": true,
+        "account": refreshed
+    }))
+}
+
+/// 检查令牌是否仍然有效 (204 为有效)
+pub async fn validate_token(account: &Account) -> Result<bool, String> {
+    let api_root = account.auth_server.as_deref().ok_or_else(|| t("missingServer", serde_json::json!({})))?;
+    let (status, _) = post_json(&endpoint(api_root, "authserver/validate"), serde_json::json!({
+        "accessToken": account.access_token,
+        "clientToken": account.client_token
+    })).await?;
+    Ok(status == 204)
+}
+
+/// 刷新令牌，服务器拒绝时返回 InvalidGrant 需要重新登录
+pub async fn refresh_token(account: &Account) -> Result<Account, RefreshError> {
+    let api_root = account.auth_server.as_deref()
+        .ok_or_else(|| RefreshError::Other(t("missingServer", serde_json::json!({}))))?;
+    let access_token = account.access_token.as_deref()
+        .ok_or_else(|| RefreshError::InvalidGrant(t("missingToken", serde_json::json!({}))))?;
+
+    let (status, json) = post_json(&endpoint(api_root, "authserver/refresh"), serde_json::json!({
+        "accessToken": access_token,
+        "clientToken": account.client_token,
+        "requestUser": true
+    })).await?;
+
+    match status {
+        200 => {
+            let mut updated = account.clone();
+            updated.access_token = json["accessToken"].as_str().map(|s| s.to_string());
+            if let Some(name) = json.pointer("/selectedProfile/name").and_then(|v| v.as_str()) {
+                updated.username = name.to_string();
+            }
+            if updated.access_token.is_none() {
+                return Err(RefreshError::Other(t("invalidResponse", serde_json::json!({}))));
+            }
+            Ok(updated)
+        }
+        // ForbiddenOperationException: 令牌已失效
+        401 | 403 => Err(RefreshError::InvalidGrant(yggdrasil_error(&json))),
+        _ => Err(RefreshError::Other(yggdrasil_error(&json))),
+    }
+}
+
+/// 吊销令牌，删除账户时调用，失败不影响删除
+pub async fn invalidate_token(account: &Account) {
+    if let (Some(api_root), Some(access_token)) = (&account.auth_server, &account.access_token) {
+        let _ = post_json(&endpoint(api_root, "authserver/invalidate"), serde_json::json!({
+            "accessToken": access_token,
+            "clientToken": account.client_token
+        })).await;
+    }
+}
+
+/// 启动前确保令牌有效，必要时刷新
+pub async fn ensure_valid_token(account: &Account) -> Result<Account, RefreshError> {
+    if validate_token(account).await? {
+        return Ok(account.clone());
+    }
+    println!("[Yggdrasil] Token invalid, refreshing for {}", account.username);
+    refresh_token(account).await
+}
+
+/// 下载 authlib-injector，已存在时直接使用
+pub async fn ensure_authlib_injector(data_dir: &Path) -> Result<PathBuf, String> {
+    let jar_path = data_dir.join(AUTHLIB_INJECTOR_FILE);
+    if jar_path.exists() {
+        return Ok(jar_path);
+    }
+
+    println!("[Yggdrasil] Downloading authlib-injector...");
+    let client = reqwest::Client::new();
+    let latest: serde_json::Value = client
+        .get(AUTHLIB_INJECTOR_LATEST_URL)
+        .send()
+        .await
+        .map_err(|e| t("authlibInfoFailed", serde_json::json!({ "message": e.to_string() })))?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let url = latest["download_url"].as_str()
+        .ok_or_else(|| t("authlibInfoFailed", serde_json::json!({ "message": "download_url" })))?;
+    let bytes = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| t("authlibDownloadFailed", serde_json::json!({ "message": e.to_string() })))?
+        .bytes()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(expected) = latest.pointer("/checksums/sha256").and_then(|v| v.as_str()) {
+        let actual = ring::digest::digest(&ring::digest::SHA256, &bytes);
+        let actual: String = actual.as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+        if actual != expected.to_lowercase() {
+            return Err(t("authlibChecksum", serde_json::json!({})));
+        }
+    }
+
+    std::fs::write(&jar_path, &bytes).map_err(|e| e.to_string())?;
+    println!("[Yggdrasil] authlib-injector {} saved to {:?}", latest["version"], jar_path);
+    Ok(jar_path)
+}
+
+/// 构建 authlib-injector 的 JVM 参数，预取元数据以减少游戏启动时的网络请求
+pub async fn build_agent_args(data_dir: &Path, account: &Account) -> Result<Vec<String>, String> {
+    let api_root = account.auth_server.as_deref().ok_or_else(|| t("missingServer", serde_json::json!({})))?;
+    let jar_path = ensure_authlib_injector(data_dir).await?;
+
+    let mut args = vec![format!("-javaagent:{}={}", jar_path.display(), api_root)];
+    if let Ok(metadata) = fetch_metadata(api_root).await {
+        args.push(format!(
+            "-Dauthlibinjector.yggdrasil.prefetched={}",
+            BASE64.encode(metadata.to_string())
+        ));
+    }
+    Ok(args)
+}